@@ -0,0 +1,807 @@
+use crate::token::Token;
+use crate::statement::*;
+use crate::diagnostic::*;
+use crate::symbols::Symbols;
+
+struct Analyzer {
+    symbols: Symbols,
+
+    // The name of the procedure being analyzed, and whether it returns a
+    // value (like functions and `Property Get`s do).
+    procedure: Option<(Token, bool)>,
+
+    // The kind (i.e. `For`) of the loops around the statement being analyzed,
+    // and the labels of the labelled ones.
+    loops: Vec<Token>,
+
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        return Self {
+            symbols: Symbols::new(),
+            procedure: None,
+            loops: vec!(),
+            diagnostics: vec!(),
+        };
+    }
+
+    pub fn analyze(&mut self, statements: &Vec<Statement>, classes: &Vec<String>) -> Vec<Diagnostic> {
+        self.symbols.declare_module(statements, classes);
+        self.analyze_module(statements);
+
+        return self.diagnostics.clone();
+    }
+
+    fn analyze_module(&mut self, statements: &Vec<Statement>) {
+        for statement in statements {
+            match statement {
+                Statement::Subroutine(data) => self.analyze_subroutine(data),
+                Statement::Function(data) => self.analyze_function(data),
+                Statement::Property(data) => self.analyze_property(data),
+                Statement::Variable(data) => self.analyze_variable(data),
+                Statement::Begin(data) => self.analyze_begin_body(&data.attributes),
+
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.analyze_module(body); }
+                },
+
+                // No analysis aplicable.
+                _ => (),
+            }
+        }
+    }
+
+    // Used for both `Begin` and `BeginProperty` blocks.
+    fn analyze_begin_body(&mut self, attributes: &Vec<Statement>) {
+        for statement in attributes {
+            match statement {
+                Statement::Begin(data) => self.analyze_begin_body(&data.attributes),
+                Statement::BeginProperty(data) => self.analyze_begin_body(&data.attributes),
+
+                Statement::Unparsed(data) => {
+                    let line = data.tokens.iter()
+                        .map(|token| String::from_utf8_lossy(&token.get_lexeme()).to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ");
+
+                    self.diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        message: format!("the designer line `{}` couldn't be parsed", line),
+                    });
+                },
+
+                // No analysis aplicable.
+                _ => (),
+            }
+        }
+    }
+
+    fn analyze_subroutine(&mut self, data: &SubroutineStatement) {
+        self.procedure = Some((data.name.clone(), false));
+        self.symbols.declare_procedure(&data.name, None, &data.arguments, &data.body);
+
+        self.analyze_body(&data.body);
+    }
+
+    fn analyze_function(&mut self, data: &FunctionStatement) {
+        self.procedure = Some((data.name.clone(), true));
+        self.symbols.declare_procedure(&data.name, data.kind.as_ref(), &data.arguments, &data.body);
+
+        self.analyze_body(&data.body);
+    }
+
+    fn analyze_property(&mut self, data: &PropertyStatement) {
+        let returns_value = data.accessor == Token::Get;
+
+        let kind = match returns_value {
+            true => data.kind.as_ref(),
+            false => None,
+        };
+
+        self.procedure = Some((data.name.clone(), returns_value));
+        self.symbols.declare_procedure(&data.name, kind, &data.arguments, &data.body);
+
+        self.analyze_body(&data.body);
+    }
+
+    fn analyze_body(&mut self, body: &Vec<Statement>) {
+        for statement in body {
+            match statement {
+                Statement::Variable(data) => self.analyze_variable(data),
+                Statement::Assignment(data) => self.analyze_assignment(data),
+                Statement::Return(data) => self.analyze_return(data),
+                Statement::Exit(data) => self.analyze_exit(data),
+                Statement::Continue(data) => self.analyze_continue(data),
+
+                Statement::ProcedureCall(data) => self.analyze_procedure_call(data),
+
+                Statement::If(data) => {
+                    for body in data.get_bodies() { self.analyze_body(body); }
+                },
+
+                Statement::For(data) => self.analyze_loop(Token::For, &data.body),
+                Statement::ForEach(data) => self.analyze_loop(Token::For, &data.body),
+                Statement::ForRange(data) => self.analyze_loop(Token::For, &data.body),
+                Statement::LabelledLoop(data) => self.analyze_loop(data.label.clone(), &data.body),
+                Statement::Do(data) => self.analyze_loop(Token::Do, &data.body),
+                Statement::While(data) => self.analyze_loop(Token::While, &data.body),
+
+                Statement::Try(data) => {
+                    for body in data.get_bodies() { self.analyze_body(body); }
+                },
+
+                Statement::Defer(data) => self.analyze_body(&data.body),
+                Statement::Using(data) => self.analyze_body(&data.body),
+
+                Statement::Match(data) => {
+                    self.analyze_match(data);
+
+                    for arm in &data.arms { self.analyze_body(&arm.body); }
+                },
+
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.analyze_body(body); }
+                },
+
+                // No analysis aplicable.
+                _ => (),
+            }
+        }
+    }
+
+    fn analyze_variable(&mut self, data: &VariableStatement) {
+        if let Some(value) = &data.value {
+            self.analyze_expression(value);
+        }
+
+        // NOTE: Variables declared inside loops are reset at each iteration
+        // (see `Scoper::reset`), which needs their data type's default value.
+        if let (false, None, None, Some(kind)) = (self.loops.is_empty(), &data.value, &data.bounds, &data.kind) {
+            if data.scope != Token::Static && self.symbols.get_default_value(kind).is_none() {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    message: format!(
+                        "`{}` isn't reset at each iteration, since the default value of `{}` isn't known",
+                        String::from_utf8_lossy(&data.name.get_lexeme()), String::from_utf8_lossy(&kind.get_lexeme()),
+                    ),
+                });
+            }
+        }
+
+        // NOTE: The data type of variables declared without one is inferred
+        // from their value (see `Symbols::infer_kind`), which isn't known for
+        // most calls and members, so they may hold objects which need `Set`.
+        if let (None, Some(value @ (Statement::Call(_) | Statement::Member(_)))) = (&data.kind, data.value.as_deref()) {
+            if self.symbols.infer_kind(value).is_none() {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    message: format!(
+                        "the data type of `{}` can't be inferred from its value, so it's a `Variant` assigned without `Set` (use `As` if it holds an object)",
+                        String::from_utf8_lossy(&data.name.get_lexeme()),
+                    ),
+                });
+            }
+        }
+
+        let literal = match data.value.as_deref() {
+            Some(Statement::ArrayLiteral(literal)) => literal,
+            _ => return,
+        };
+
+        let name = String::from_utf8_lossy(&data.name.get_lexeme()).to_string();
+
+        let shape = match self.analyze_array_literal(&name, literal) {
+            Some(shape) => shape,
+            None => return,
+        };
+
+        // NOTE: `Variant`s can hold arrays too.
+        let bounds = match (&data.bounds, &data.kind) {
+            (Some(bounds), _) => bounds,
+            (None, Some(kind)) if !self.symbols.is_variant_kind(kind) => {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    message: format!("`{}` is assigned an array but it isn't one", name),
+                });
+
+                return;
+            },
+
+            _ => return,
+        };
+
+        // NOTE: Dynamic arrays are resized to fit their value.
+        if bounds.is_empty() {
+            return;
+        }
+
+        if bounds.len() != shape.len() {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                message: format!("`{}` has {} dimensions but its value has {}", name, bounds.len(), shape.len()),
+            });
+
+            return;
+        }
+
+        for (dimension, (bound, length)) in bounds.iter().zip(shape).enumerate() {
+            let declared_length = match (bound.get_lower(self.symbols.get_base()), bound.get_upper()) {
+                (Some(lower), Some(upper)) => upper - lower + 1,
+                _ => continue,
+            };
+
+            if declared_length != length as i64 {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    message: format!(
+                        "`{}` has {} elements in its dimension {} but its value has {}",
+                        name, declared_length, dimension + 1, length,
+                    ),
+                });
+            }
+        }
+    }
+
+    // Returns the length of each dimension of an array literal, reporting the
+    // ones whose nested arrays have different lengths.
+    fn analyze_array_literal(&mut self, name: &str, literal: &ArrayLiteralStatement) -> Option<Vec<usize>> {
+        let shape = literal.get_shape();
+
+        if shape.is_none() {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                message: format!("the nested arrays assigned to `{}` have different lengths", name),
+            });
+        }
+
+        return shape;
+    }
+
+    fn analyze_assignment(&mut self, data: &AssignmentStatement) {
+        self.analyze_expression(&data.right);
+
+        if let (Statement::ArrayLiteral(literal), Statement::Value(name)) = (&*data.right, &*data.left) {
+            self.analyze_array_literal(&String::from_utf8_lossy(&name.value.get_lexeme()), literal);
+
+            return;
+        }
+
+        if let Some(Token::Set) = data.modifier {
+            return;
+        }
+
+        // NOTE: Only variables are checked, since the data type of arrays'
+        // elements and objects' members isn't known.
+        let name = match &*data.left {
+            Statement::Value(value) => &value.value,
+            _ => return,
+        };
+
+        // NOTE: Variables declared by `Let` get `Set` from the transformer.
+        if data.modifier == Some(Token::Let) && self.symbols.take_undeclared(name) {
+            return;
+        }
+
+        // NOTE: Variables of unknown data types (i.e. `VbMsgBoxResult`) are
+        // only warned about when they're assigned a known object.
+        let kind = self.symbols.find_kind(name);
+
+        if self.symbols.is_object_assignment(kind.as_ref(), &data.right) {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                message: format!(
+                    "object variable `{}` is assigned without `Set` (runtime error 91)",
+                    String::from_utf8_lossy(&name.get_lexeme()),
+                ),
+            });
+        }
+    }
+
+    fn analyze_loop(&mut self, kind: Token, body: &Vec<Statement>) {
+        self.loops.push(kind);
+        self.analyze_body(body);
+        self.loops.pop();
+    }
+
+    fn analyze_exit(&mut self, data: &ExitStatement) {
+        if let Token::Identifier(_) = data.block {
+            self.analyze_loop_label("Exit", &data.block);
+        }
+    }
+
+    fn analyze_continue(&mut self, data: &ContinueStatement) {
+        if let Token::Identifier(_) = data.block {
+            self.analyze_loop_label("Continue", &data.block);
+
+            return;
+        }
+
+        if self.loops.contains(&data.block) {
+            return;
+        }
+
+        let kind = match data.block {
+            Token::For => "For",
+            Token::Do => "Do",
+            _ => "While",
+        };
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: format!("`Continue {}` can only be used inside a `{}` loop", kind, kind),
+        });
+    }
+
+    // NOTE: Labels are case insensitive, like anything else in VB6.
+    fn analyze_loop_label(&mut self, keyword: &str, label: &Token) {
+        let label = String::from_utf8_lossy(&label.get_lexeme()).to_string();
+
+        let is_known = self.loops.iter().any(|kind| match kind {
+            Token::Identifier(name) => String::from_utf8_lossy(name).eq_ignore_ascii_case(&label),
+            _ => false,
+        });
+
+        if is_known {
+            return;
+        }
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: format!("`{} {}` can only be used inside a loop labelled `{}`", keyword, label, label),
+        });
+    }
+
+    fn analyze_return(&mut self, data: &ReturnStatement) {
+        if let Some(value) = &data.value {
+            self.analyze_expression(value);
+        }
+
+        // NOTE: Returns can only be parsed inside a procedure's body.
+        let (name, returns_value) = match &self.procedure {
+            Some(procedure) => procedure.clone(),
+            None => return,
+        };
+
+        let message = match (&data.value, returns_value) {
+            (Some(_), false) => "can't return a value",
+            (None, true) => "must return a value",
+
+            (Some(value), true) => return self.analyze_returned_value(&name, value),
+            (None, false) => return,
+        };
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: format!("`{}` {}", String::from_utf8_lossy(&name.get_lexeme()), message),
+        });
+    }
+
+    // NOTE: Values are returned with `Set` when either the returned data type
+    // or the value are known to be objects (see `Transformer::transform_return`).
+    fn analyze_returned_value(&mut self, name: &Token, value: &Statement) {
+        let kind = match self.symbols.find_kind(name) {
+            Some(kind) => kind,
+            None => return,
+        };
+
+        if self.symbols.is_known_kind(&kind) || self.symbols.is_object(value) {
+            return;
+        }
+
+        // NOTE: Values of known data types (i.e. `Return 1`) can't be objects.
+        if let Some(value_kind) = self.symbols.infer_kind(value) {
+            if self.symbols.is_known_kind(&value_kind) && !self.symbols.is_variant_kind(&value_kind) {
+                return;
+            }
+        }
+
+        let name = String::from_utf8_lossy(&name.get_lexeme()).to_string();
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            message: format!(
+                "`{}` returns `{}`, which isn't known to be an object, so its value is returned without `Set` (use `Set {} = ...` if it's one)",
+                name,
+                String::from_utf8_lossy(&kind.get_lexeme()),
+                name,
+            ),
+        });
+    }
+
+    fn analyze_procedure_call(&mut self, data: &ProcedureCallStatement) {
+        for argument in &data.arguments { self.analyze_expression(argument); }
+
+        // NOTE: `Continue` and `Defer` alone are parsed as calls, since what
+        // follows them must be in the same line (see `Parser::parse_continue`).
+        let name = match &*data.procedure {
+            Statement::Value(ValueStatement { value: Token::Identifier(name) }) if data.arguments.is_empty() => name,
+            _ => return,
+        };
+
+        let message = match name.to_ascii_lowercase().as_slice() {
+            b"continue" => "`Continue` must be followed by `For`, `Do`, `While` or a loop's label in the same line",
+            b"defer" => "`Defer` must be followed by a statement in the same line",
+
+            _ => return,
+        };
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+        });
+    }
+
+    // Looks for the `Match` expressions inside an expression.
+    fn analyze_expression(&mut self, expression: &Statement) {
+        match expression {
+            Statement::Match(data) => {
+                self.analyze_match(data);
+
+                for arm in &data.arms {
+                    for value in &arm.body { self.analyze_expression(value); }
+                }
+            },
+
+            Statement::Unary(data) => self.analyze_expression(&data.value),
+
+            Statement::Binary(data) => {
+                self.analyze_expression(&data.left);
+                self.analyze_expression(&data.right);
+            },
+
+            Statement::Call(data) => {
+                self.analyze_expression(&data.callee);
+
+                for argument in &data.arguments { self.analyze_expression(argument); }
+            },
+
+            Statement::Member(data) => self.analyze_expression(&data.object),
+            Statement::TypeOf(data) => self.analyze_expression(&data.value),
+
+            Statement::Conditional(data) => {
+                self.analyze_expression(&data.condition);
+                self.analyze_expression(&data.when_true);
+                self.analyze_expression(&data.when_false);
+            },
+
+            Statement::InterpolatedString(data) => {
+                for part in &data.parts { self.analyze_expression(part); }
+            },
+
+            Statement::Interpolation(data) => self.analyze_expression(&data.value),
+
+            Statement::ArrayLiteral(data) => {
+                for element in &data.elements { self.analyze_expression(element); }
+            },
+
+            // No analysis aplicable.
+            _ => (),
+        }
+    }
+
+    fn analyze_match(&mut self, data: &MatchStatement) {
+        self.analyze_expression(&data.value);
+
+        for arm in &data.arms {
+            let patterns = arm.patterns.as_deref().unwrap_or(&[]);
+
+            for pattern in patterns {
+                match pattern {
+                    Pattern::Value(value) => self.analyze_expression(value),
+
+                    Pattern::Range(start, end) => {
+                        self.analyze_expression(start);
+                        self.analyze_expression(end);
+                    },
+
+                    // NOTE: See `MatchArm::get_binding`.
+                    Pattern::TypeOf(_, Some(name)) if patterns.len() > 1 => {
+                        self.diagnostics.push(Diagnostic {
+                            severity: Severity::Error,
+                            message: format!(
+                                "`{}` can only be bound by a `Case` with a single pattern",
+                                String::from_utf8_lossy(&name.get_lexeme()),
+                            ),
+                        });
+                    },
+
+                    _ => (),
+                }
+            }
+
+            if let Some(guard) = &arm.guard {
+                self.analyze_expression(guard);
+            }
+        }
+
+        self.analyze_match_exhaustiveness(data);
+    }
+
+    // Reports the members of an `Enum` which a `Match` on it doesn't handle,
+    // unless it has a `Case Else`.
+    //
+    // NOTE: Arms with a guard may not match, so they don't handle anything.
+    // Ranges only handle the members whose value is known.
+    fn analyze_match_exhaustiveness(&mut self, data: &MatchStatement) {
+        if data.arms.iter().any(|arm| arm.patterns.is_none()) {
+            return;
+        }
+
+        let kind = match self.symbols.infer_kind(&data.value) {
+            Some(kind) => kind,
+            None => return,
+        };
+
+        let members = match self.symbols.get_enum_members(&kind) {
+            Some(members) => members.clone(),
+            None => return,
+        };
+
+        let mut is_handled = vec!(false; members.len());
+
+        for arm in data.arms.iter().filter(|arm| arm.guard.is_none()) {
+            for pattern in arm.patterns.iter().flatten() {
+                let (start, end) = match pattern {
+                    Pattern::Value(value) => {
+                        if let Some(index) = Self::find_enum_member(&members, value) {
+                            is_handled[index] = true;
+                        }
+
+                        (value, value)
+                    },
+
+                    Pattern::Range(start, end) => (start, end),
+
+                    _ => continue,
+                };
+
+                let (start, end) = match (Self::get_enum_value(&members, start), Self::get_enum_value(&members, end)) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => continue,
+                };
+
+                for (index, (_, value)) in members.iter().enumerate() {
+                    if matches!(value, Some(value) if start <= *value && *value <= end) {
+                        is_handled[index] = true;
+                    }
+                }
+            }
+        }
+
+        let unhandled: Vec<String> = members.iter().zip(is_handled)
+            .filter(|(_, is_handled)| !is_handled)
+            .map(|((name, _), _)| format!("`{}`", String::from_utf8_lossy(&name.get_lexeme())))
+            .collect();
+
+        if unhandled.is_empty() {
+            return;
+        }
+
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: format!(
+                "`Match` on `{}` doesn't handle {} (nor has a `Case Else`)",
+                String::from_utf8_lossy(&kind.get_lexeme()), unhandled.join(", "),
+            ),
+        });
+    }
+
+    // NOTE: Members are written either alone (i.e. `Red`) or qualified by
+    // the `Enum`'s name (i.e. `Color.Red`).
+    fn find_enum_member(members: &Vec<(Token, Option<i64>)>, value: &Statement) -> Option<usize> {
+        let name = match value {
+            Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) => name,
+            Statement::Member(data) => &data.member,
+
+            _ => return None,
+        };
+
+        let name = name.get_lexeme();
+
+        return members.iter().position(|(member, _)| member.get_lexeme().eq_ignore_ascii_case(&name));
+    }
+
+    fn get_enum_value(members: &Vec<(Token, Option<i64>)>, value: &Statement) -> Option<i64> {
+        return match Self::find_enum_member(members, value) {
+            Some(index) => members[index].1,
+            None => ArrayBound::get_integer(value),
+        };
+    }
+}
+
+// NOTE: The `classes` are the ones declared by other modules (see
+// `Symbols::declare_module`).
+pub fn analyze(statements: &Vec<Statement>, classes: &Vec<String>) -> Vec<Diagnostic> {
+    let mut analyzer = Analyzer::new();
+
+    return analyzer.analyze(statements, classes);
+}
+
+#[cfg(test)]
+mod tests {
+    fn analyze(source: &str) -> Vec<String> {
+        let tokens = crate::lex(&source.as_bytes().to_vec());
+
+        return crate::analyze(&crate::parse(&tokens), &vec!()).into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+    }
+
+    #[test]
+    fn warns_about_objects_created_with_new_without_set() {
+        let source = "\
+Public Sub Main()
+    Dim items As Collection
+    items = New Collection
+    Set items = New Collection
+End Sub
+";
+
+        let diagnostics = analyze(source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0], "object variable `items` is assigned without `Set` (runtime error 91)");
+    }
+
+    #[test]
+    fn reports_misused_returns() {
+        let source = "\
+Public Sub Save()
+    Return 1
+End Sub
+
+Public Function Load() As Long
+    Return
+End Function
+";
+
+        assert_eq!(analyze(source), vec!("`Save` can't return a value", "`Load` must return a value"));
+    }
+
+    #[test]
+    fn warns_about_returns_of_data_types_which_arent_known() {
+        let source = "\
+Public Function Open() As Dictionary
+    Return CreateObject(\"Scripting.Dictionary\")
+End Function
+
+Public Function Reopen() As Dictionary
+    Dim opened As Variant
+    Set opened = CreateObject(\"Scripting.Dictionary\")
+    Return opened
+End Function
+
+Public Function Size() As Dictionary
+    Return 1
+End Function
+";
+
+        assert_eq!(analyze(source), vec!(
+            "`Open` returns `Dictionary`, which isn't known to be an object, so its value is returned without `Set` (use `Set Open = ...` if it's one)",
+        ));
+    }
+
+    #[test]
+    fn warns_about_variables_whose_data_type_cant_be_inferred() {
+        let source = "\
+Public Function Count() As Long
+    Return 1
+End Function
+
+Public Sub Main()
+    Dim dictionary = CreateObject(\"Scripting.Dictionary\")
+    Dim total = Count()
+    Dim name = \"Nabe\"
+End Sub
+";
+
+        assert_eq!(analyze(source), vec!(
+            "the data type of `dictionary` can't be inferred from its value, so it's a `Variant` assigned without `Set` (use `As` if it holds an object)",
+        ));
+    }
+
+    #[test]
+    fn knows_the_prefixed_values_of_enums() {
+        let source = "\
+Public Enum Level
+    Low = &H1
+    Middle
+    High = &H10
+End Enum
+
+Public Function Describe(ByVal value As Level) As String
+    Match value
+        Case Level.Low To Level.High
+            Describe = \"known\"
+    End Match
+End Function
+";
+
+        assert_eq!(analyze(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_unparsed_designer_lines() {
+        let source = "\
+VERSION 5.00
+Begin VB.Form Form1
+   Caption = \"Form1\"
+   Misplaced line
+End
+Attribute VB_Name = \"Form1\"
+";
+
+        assert_eq!(analyze(source), vec!("the designer line `Misplaced line` couldn't be parsed"));
+    }
+
+    #[test]
+    fn warns_about_objects_assigned_without_set() {
+        let source = "\
+Public Sub Main()
+    Dim answer As VbMsgBoxResult
+    answer = MsgBox(\"Continue?\", vbYesNo)
+
+    Dim area As RECT
+    area = GetArea()
+
+    Dim items As Collection
+    items = New Collection
+
+    Dim anything As Unknown
+    anything = Nothing
+End Sub
+";
+
+        assert_eq!(analyze(source), vec!(
+            "object variable `items` is assigned without `Set` (runtime error 91)",
+            "object variable `anything` is assigned without `Set` (runtime error 91)",
+        ));
+    }
+
+    #[test]
+    fn reports_continues_without_a_loop_in_the_same_line() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    For i = 1 To 2
+        Continue
+        Debug.Print i
+        Continue For
+    Next
+End Sub
+";
+
+        assert_eq!(analyze(source), vec!("`Continue` must be followed by `For`, `Do`, `While` or a loop's label in the same line"));
+    }
+
+    #[test]
+    fn reports_defers_without_a_statement_in_the_same_line() {
+        let source = "\
+Public Sub Main(conn As Object)
+    Defer
+    conn.Close
+    Defer conn.Close
+End Sub
+";
+
+        assert_eq!(analyze(source), vec!("`Defer` must be followed by a statement in the same line"));
+    }
+
+    #[test]
+    fn warns_about_variables_inside_loops_which_cant_be_reset() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    Dim outside As VbMsgBoxResult
+    For i = 1 To 2
+        Dim answer As VbMsgBoxResult
+        Dim items As Collection
+    Next
+End Sub
+";
+
+        assert_eq!(analyze(source), vec!("`answer` isn't reset at each iteration, since the default value of `VbMsgBoxResult` isn't known"));
+    }
+}
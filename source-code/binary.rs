@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::io::Write;
+
+const USAGE: &str = "\
+usage: nabe [<options>] <source> [<output>]
+       nabe [<options>] --project <project.vbp> <output directory>
+
+options:
+    --resolve               resolve conditional compilation directives
+    --define <name>[=<value>]
+                            define a conditional compilation constant
+                            (implies `--resolve`)";
+
+fn main() {
+    let mut defines: Option<HashMap<String, i64>> = None;
+    let mut is_project = false;
+    let mut paths = vec!();
+
+    let mut arguments = std::env::args().skip(1);
+
+    while let Some(argument) = arguments.next() {
+        match &argument as &str {
+            "--project" => is_project = true,
+            "--resolve" => { defines.get_or_insert_with(HashMap::new); },
+
+            // NOTE: Names without a value are defined as `True`, like in
+            // VB6 projects (see `nabe::parse_defines`).
+            "--define" => {
+                let definition = match arguments.next() {
+                    Some(definition) => definition,
+                    None => exit_with_usage(),
+                };
+
+                match nabe::parse_defines(&definition) {
+                    Some(definition) if !definition.is_empty() => defines.get_or_insert_with(HashMap::new).extend(definition),
+                    _ => exit_with_usage(),
+                }
+            },
+
+            _ => paths.push(argument),
+        }
+    }
+
+    let result = match (is_project, &paths[..]) {
+        (true, [project, output_directory]) => compile_project(project, output_directory, defines.as_ref()),
+        (false, [source]) => compile_file(source, None, defines.as_ref()),
+        (false, [source, output]) => compile_file(source, Some(output), defines.as_ref()),
+
+        _ => exit_with_usage(),
+    };
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+fn compile_file(source: &str, output: Option<&str>, defines: Option<&HashMap<String, i64>>) -> std::io::Result<()> {
+    let characters = std::fs::read(source)?;
+    let compilation = nabe::compile(&characters, defines, &vec!());
+
+    report(Path::new(source), &compilation.diagnostics);
+
+    let generated_code = match compilation.code {
+        Some(generated_code) => generated_code,
+        None => std::process::exit(1),
+    };
+
+    match output {
+        Some(output) => std::fs::write(output, nabe::to_crlf(&generated_code))?,
+        None => std::io::stdout().write_all(&generated_code)?,
+    }
+
+    return Ok(());
+}
+
+fn compile_project(project: &str, output_directory: &str, defines: Option<&HashMap<String, i64>>) -> std::io::Result<()> {
+    let compiled_files = nabe::compile_project(Path::new(project), Path::new(output_directory), defines)?;
+
+    for compiled_file in &compiled_files {
+        report(&compiled_file.source, &compiled_file.diagnostics);
+    }
+
+    if compiled_files.iter().any(|compiled_file| has_errors(&compiled_file.diagnostics)) {
+        std::process::exit(1);
+    }
+
+    return Ok(());
+}
+
+fn report(source: &Path, diagnostics: &Vec<nabe::Diagnostic>) {
+    for diagnostic in diagnostics {
+        let severity = match diagnostic.severity {
+            nabe::Severity::Warning => "warning",
+            nabe::Severity::Error => "error",
+        };
+
+        eprintln!("{}: {}: {}", source.display(), severity, diagnostic.message);
+    }
+}
+
+fn has_errors(diagnostics: &Vec<nabe::Diagnostic>) -> bool {
+    return diagnostics.iter().any(|diagnostic| diagnostic.severity == nabe::Severity::Error);
+}
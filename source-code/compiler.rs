@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+pub struct Compilation {
+    // NOTE: There's no code when there are errors, since it would (probably)
+    // be wrong. It's kept as bytes, since sources are written in the system's
+    // code page (not UTF-8).
+    pub code: Option<Vec<u8>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Compiles a single source file, be it a module, a class module or a form.
+//
+// NOTE: Conditional compilation directives are kept as they are unless some
+// `defines` are given. The `classes` are the ones declared by other modules
+// (i.e. the project's class modules).
+pub fn compile(characters: &Vec<u8>, defines: Option<&HashMap<String, i64>>, classes: &Vec<String>) -> Compilation {
+    let tokens = crate::lex(characters);
+    let statements = crate::parse(&tokens);
+
+    let statements = match defines {
+        Some(defines) => crate::preprocess(statements, defines),
+        None => statements,
+    };
+
+    let diagnostics = crate::analyze(&statements, classes);
+
+    if diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error) {
+        return Compilation {
+            code: None,
+            diagnostics: diagnostics,
+        };
+    }
+
+    let statements = crate::transform(statements, classes);
+
+    return Compilation {
+        code: Some(crate::generate(statements)),
+        diagnostics: diagnostics,
+    };
+}
+
+// Replaces the line endings of generated code by VB6's.
+//
+// NOTE: VB6 only loads files whose lines end with `\r\n`.
+pub fn to_crlf(code: &[u8]) -> Vec<u8> {
+    let mut converted_code = vec!();
+
+    for &character in code {
+        if character == b'\n' {
+            converted_code.push(b'\r');
+        }
+
+        converted_code.push(character);
+    }
+
+    return converted_code;
+}
@@ -1,3 +1,4 @@
+use crate::token::Token;
 use crate::statement::*;
 use crate::viewer::Viewer;
 
@@ -12,114 +13,151 @@ impl Generator {
         };
     }
 
-    pub fn generate(&mut self) -> String {
-        let mut generated_code = String::new();
+    pub fn generate(&mut self) -> Vec<u8> {
+        let mut generated_code = vec!();
 
         while let Some(statement) = self.statements.next() {
-            let statement_code = match statement {
-                Statement::Constant(data) => self.generate_constant(&data),
-                Statement::Subroutine(data) => self.generate_subroutine(&data),
-                Statement::Function(data) => self.generate_function(&data),
-                Statement::Type(data) => self.generate_type(&data),
-                Statement::Enum(data) => self.generate_enum(&data),
-                Statement::Variable(data) => self.generate_variable(&data),
-                Statement::Option(data) => self.generate_option(&data),
-                Statement::Attribute(data) => self.generate_attribute(&data),
-
-                // TODO: Handle all cases.
-                _ => String::from("__POLYFILL__\n"),
-            };
+            let statement_code = self.generate_module_statement(&statement);
 
-            generated_code.push_str(&statement_code);
+            generated_code.extend(statement_code);
         }
 
         return generated_code;
     }
 
-    fn generate_subroutine(&mut self, data: &SubroutineStatement) -> String {
+    // Used for the conditionally compiled blocks which stand outside of
+    // functions and subroutines.
+    fn generate_module_body(&mut self, body: &Vec<Statement>) -> Vec<u8> {
+        let mut generated_body = vec!();
+
+        for statement in body {
+            generated_body.extend(self.generate_module_statement(statement));
+        }
+
+        return generated_body;
+    }
+
+    fn generate_module_statement(&mut self, statement: &Statement) -> Vec<u8> {
+        return match statement {
+            Statement::Constant(data) => self.generate_constant(&data),
+            Statement::Subroutine(data) => self.generate_subroutine(&data),
+            Statement::Function(data) => self.generate_function(&data),
+            Statement::Property(data) => self.generate_property(&data),
+            Statement::Type(data) => self.generate_type(&data),
+            Statement::Enum(data) => self.generate_enum(&data),
+            Statement::Variable(data) => self.generate_variable(&data),
+            Statement::Option(data) => self.generate_option(&data),
+            Statement::Attribute(data) => self.generate_attribute(&data),
+            Statement::Version(data) => self.generate_version(&data),
+            Statement::Object(data) => self.generate_object(&data),
+            Statement::Begin(data) => self.generate_begin(&data, 0),
+            Statement::Event(data) => self.generate_event(&data),
+            Statement::Implements(data) => self.generate_implements(&data),
+            Statement::DirectiveIf(data) => self.generate_directive_if(&data, Generator::generate_module_body),
+            Statement::DirectiveConst(data) => self.generate_directive_const(&data),
+
+            // TODO: Handle all cases.
+            _ => b"__POLYFILL__\n".to_vec(),
+        };
+    }
+
+    fn generate_subroutine(&mut self, data: &SubroutineStatement) -> Vec<u8> {
         let generated_signature = self.generate_subroutine_signature(&data);
         let generated_body = self.generate_subroutine_body(&data);
-        let generated_end = "end sub\n";
+        let generated_end = b"end sub\n";
 
-        return generated_signature + &generated_body + generated_end;
+        return [generated_signature, generated_body, generated_end.to_vec()].concat();
     }
 
-    fn generate_subroutine_signature(&mut self, data: &SubroutineStatement) -> String {
-        let mut generated_signature = String::new();
+    fn generate_subroutine_signature(&mut self, data: &SubroutineStatement) -> Vec<u8> {
+        let mut generated_signature = vec!();
 
-        generated_signature.push_str(&String::from_utf8_lossy(&data.scope.get_lexeme()));
-        generated_signature.push_str(" sub ");
-        generated_signature.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
+        generated_signature.extend(data.scope.get_lexeme());
+        generated_signature.extend(b" sub ");
+        generated_signature.extend(data.name.get_lexeme());
 
-        generated_signature.push_str("(");
+        generated_signature.extend(self.generate_arguments(&data.arguments));
+        generated_signature.push(b'\n');
 
-        for statement in &data.arguments {
-            let argument_code = match statement {
-                Statement::Argument(argument) => self.generate_argument(argument),
+        return generated_signature;
+    }
 
-                // TODO: Is it correct to `panic`?
-                //
-                // TODO: Add a message?
-                _ => unreachable!(),
-            };
+    fn generate_subroutine_body(&mut self, data: &SubroutineStatement) -> Vec<u8> {
+        return self.generate_callable_body(&data.body);
+    }
 
-            generated_signature.push_str(&argument_code);
-        }
+    fn generate_function(&mut self, data: &FunctionStatement) -> Vec<u8> {
+        let generated_signature = self.generate_function_signature(&data);
+        let generated_body = self.generate_function_body(&data);
+        let generated_end = b"end function\n";
 
-        // Remove the last comma (and space) because it isn't followed by anything
-        // (and is also a Visual Basic 6 syntax error).
-        //
-        // TODO: This feels hardcoded.
-        if data.arguments.len() > 0 {
-            let _ = generated_signature.pop(); // Space.
-            let _ = generated_signature.pop(); // Comma.
+        return [generated_signature, generated_body, generated_end.to_vec()].concat();
+    }
+
+    fn generate_function_signature(&mut self, data: &FunctionStatement) -> Vec<u8> {
+        let mut generated_signature = vec!();
+
+        generated_signature.extend(data.scope.get_lexeme());
+        generated_signature.extend(b" function ");
+        generated_signature.extend(data.name.get_lexeme());
+
+        generated_signature.extend(self.generate_arguments(&data.arguments));
+
+        // TODO: This seems too imperative.
+        match &data.kind {
+            Some(kind) => {
+                generated_signature.extend(b" as ");
+                generated_signature.extend(kind.get_lexeme());
+            },
+
+            None => (),
         }
 
-        generated_signature.push_str(")");
-        generated_signature.push('\n');
+        generated_signature.push(b'\n');
 
         return generated_signature;
     }
 
-    fn generate_subroutine_body(&mut self, data: &SubroutineStatement) -> String {
-        let mut generated_body = String::new();
+    fn generate_function_body(&mut self, data: &FunctionStatement) -> Vec<u8> {
+        return self.generate_callable_body(&data.body);
+    }
 
-        for statement in &data.body {
-            let generated_statement = match statement {
-                Statement::Assignment(data) => self.generate_assignment(data),
-                Statement::Constant(data) => self.generate_constant(data),
-                Statement::Variable(data) => self.generate_variable(data),
-                Statement::Exit(data) => self.generate_exit(data),
-                Statement::Attribute(data) => self.generate_attribute(&data),
+    fn generate_property(&mut self, data: &PropertyStatement) -> Vec<u8> {
+        let generated_signature = self.generate_property_signature(&data);
+        let generated_body = self.generate_callable_body(&data.body);
+        let generated_end = b"end property\n";
 
-                // TODO: Handle all cases.
-                _ => String::from("__POLYFILL__\n"),
-            };
+        return [generated_signature, generated_body, generated_end.to_vec()].concat();
+    }
 
-            generated_body.push_str(&generated_statement);
-        }
+    fn generate_property_signature(&mut self, data: &PropertyStatement) -> Vec<u8> {
+        let mut generated_signature = vec!();
 
-        return generated_body;
-    }
+        generated_signature.extend(data.scope.get_lexeme());
+        generated_signature.extend(b" property ");
+        generated_signature.extend(data.accessor.get_lexeme());
+        generated_signature.push(b' ');
+        generated_signature.extend(data.name.get_lexeme());
+        generated_signature.extend(self.generate_arguments(&data.arguments));
 
-    fn generate_function(&mut self, data: &FunctionStatement) -> String {
-        let generated_signature = self.generate_function_signature(&data);
-        let generated_body = self.generate_function_body(&data);
-        let generated_end = "end function\n";
+        if let Some(kind) = &data.kind {
+            generated_signature.extend(b" as ");
+            generated_signature.extend(kind.get_lexeme());
+        }
 
-        return generated_signature + &generated_body + generated_end;
-    }
+        generated_signature.push(b'\n');
 
-    fn generate_function_signature(&mut self, data: &FunctionStatement) -> String {
-        let mut generated_signature = String::new();
+        return generated_signature;
+    }
 
-        generated_signature.push_str(&String::from_utf8_lossy(&data.scope.get_lexeme()));
-        generated_signature.push_str(" function ");
-        generated_signature.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
+    // Generates the (parenthesized) arguments of functions, subroutines,
+    // properties and events.
+    fn generate_arguments(&mut self, arguments: &Vec<Statement>) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_signature.push_str("(");
+        generated_code.extend(b"(");
 
-        for statement in &data.arguments {
+        for statement in arguments {
             let argument_code = match statement {
                 Statement::Argument(argument) => self.generate_argument(argument),
 
@@ -129,71 +167,74 @@ impl Generator {
                 _ => unreachable!(),
             };
 
-            generated_signature.push_str(&argument_code);
+            generated_code.extend(argument_code);
         }
 
         // Remove the last comma (and space) because it isn't followed by anything
         // (and is also a Visual Basic 6 syntax error).
         //
         // TODO: This feels hardcoded.
-        if data.arguments.len() > 0 {
-            let _ = generated_signature.pop(); // Space.
-            let _ = generated_signature.pop(); // Comma.
+        if arguments.len() > 0 {
+            let _ = generated_code.pop(); // Space.
+            let _ = generated_code.pop(); // Comma.
         }
 
-        generated_signature.push_str(")");
+        generated_code.extend(b")");
 
-        // TODO: This seems too imperative.
-        match &data.kind {
-            Some(kind) => {
-                generated_signature.push_str(" as ");
-                generated_signature.push_str(&String::from_utf8_lossy(&kind.get_lexeme()));
-            },
-
-            None => (),
-        }
-
-        generated_signature.push('\n');
-
-        return generated_signature;
+        return generated_code;
     }
 
-    fn generate_function_body(&mut self, data: &FunctionStatement) -> String {
-        let mut generated_body = String::new();
+    // Used for functions, subroutines and properties.
+    fn generate_callable_body(&mut self, body: &Vec<Statement>) -> Vec<u8> {
+        let mut generated_body = vec!();
 
-        for statement in &data.body {
+        for statement in body {
             let generated_statement = match statement {
                 Statement::Assignment(data) => self.generate_assignment(data),
                 Statement::Constant(data) => self.generate_constant(data),
                 Statement::Variable(data) => self.generate_variable(data),
+                Statement::ReDim(data) => self.generate_redim(data),
                 Statement::Exit(data) => self.generate_exit(data),
                 Statement::Attribute(data) => self.generate_attribute(&data),
+                Statement::RaiseEvent(data) => self.generate_raise_event(&data),
+                Statement::If(data) => self.generate_if(&data),
+                Statement::For(data) => self.generate_for(&data),
+                Statement::ForEach(data) => self.generate_for_each(&data),
+                Statement::Do(data) => self.generate_do(&data),
+                Statement::While(data) => self.generate_while(&data),
+                Statement::Label(data) => self.generate_label(&data),
+                Statement::GoTo(data) => self.generate_goto(&data),
+                Statement::ProcedureCall(data) => self.generate_procedure_call(&data),
+                Statement::OnError(data) => self.generate_on_error(&data),
+                Statement::Resume(data) => self.generate_resume(&data),
+                Statement::DirectiveIf(data) => self.generate_directive_if(&data, Generator::generate_callable_body),
+                Statement::DirectiveConst(data) => self.generate_directive_const(&data),
 
                 // TODO: Handle all cases.
-                _ => String::from("__POLYFILL__\n"),
+                _ => b"__POLYFILL__\n".to_vec(),
             };
 
-            generated_body.push_str(&generated_statement);
+            generated_body.extend(generated_statement);
         }
 
         return generated_body;
     }
 
-    fn generate_type(&mut self, data: &TypeStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_type(&mut self, data: &TypeStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_code.push_str("type ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-        generated_code.push('\n');
+        generated_code.extend(b"type ");
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.push(b'\n');
 
         for statement in &data.attributes {
             match statement {
                 // TODO: This seems too imperative.
                 Statement::TypeAttribute(data) => {
-                    generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-                    generated_code.push_str(" as ");
-                    generated_code.push_str(&String::from_utf8_lossy(&data.kind.get_lexeme()));
-                    generated_code.push('\n');
+                    generated_code.extend(data.name.get_lexeme());
+                    generated_code.extend(b" as ");
+                    generated_code.extend(data.kind.get_lexeme());
+                    generated_code.push(b'\n');
                 },
 
                 // TODO: Is it correct to `panic`?
@@ -203,35 +244,35 @@ impl Generator {
             }
         }
 
-        generated_code.push_str("end type\n");
+        generated_code.extend(b"end type\n");
 
         return generated_code;
     }
 
-    fn generate_enum(&mut self, data: &EnumStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_enum(&mut self, data: &EnumStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
         if let Some(scope) = &data.scope {
-            generated_code.push_str(&String::from_utf8_lossy(&scope.get_lexeme()));
-            generated_code.push(' ');
+            generated_code.extend(scope.get_lexeme());
+            generated_code.push(b' ');
         }
 
-        generated_code.push_str("enum ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-        generated_code.push('\n');
+        generated_code.extend(b"enum ");
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.push(b'\n');
 
         for statement in &data.attributes {
             match statement {
                 // TODO: This seems too imperative.
                 Statement::EnumAttribute(data) => {
-                    generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
+                    generated_code.extend(data.name.get_lexeme());
 
                     if let Some(value) = &data.value {
-                        generated_code.push_str(" = ");
-                        generated_code.push_str(&String::from_utf8_lossy(&value.get_lexeme()));
+                        generated_code.extend(b" = ");
+                        generated_code.extend(value.get_lexeme());
                     }
 
-                    generated_code.push('\n');
+                    generated_code.push(b'\n');
                 },
 
                 // TODO: Is it correct to `panic`?
@@ -241,50 +282,60 @@ impl Generator {
             }
         }
 
-        generated_code.push_str("end enum\n");
+        generated_code.extend(b"end enum\n");
 
         return generated_code;
     }
 
-    fn generate_argument(&mut self, data: &ArgumentStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_argument(&mut self, data: &ArgumentStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
         if let Some(modifier) = &data.modifier {
-            generated_code.push_str(&String::from_utf8_lossy(&modifier.get_lexeme()));
-            generated_code.push(' ');
+            generated_code.extend(modifier.get_lexeme());
+            generated_code.push(b' ');
         }
 
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-        generated_code.push_str(" as ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.kind.get_lexeme()));
-        generated_code.push_str(", ");
+        generated_code.extend(data.name.get_lexeme());
+
+        if let Some(bounds) = &data.bounds {
+            generated_code.extend(self.generate_array_bounds(bounds));
+        }
+
+        generated_code.extend(b" as ");
+        generated_code.extend(data.kind.get_lexeme());
+        generated_code.extend(b", ");
 
         return generated_code;
     }
 
-    fn generate_assignment(&mut self, data: &AssignmentStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_assignment(&mut self, data: &AssignmentStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        if let Some(modifier) = &data.modifier {
+            generated_code.extend(modifier.get_lexeme());
+            generated_code.push(b' ');
+        }
 
-        generated_code.push_str(&String::from_utf8_lossy(&data.left.get_lexeme()));
-        generated_code.push_str(" = ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.right.get_lexeme()));
-        generated_code.push('\n');
+        generated_code.extend(self.generate_expression(&data.left));
+        generated_code.extend(b" = ");
+        generated_code.extend(self.generate_expression(&data.right));
+        generated_code.push(b'\n');
 
         return generated_code;
     }
 
-    fn generate_constant(&mut self, data: &ConstantStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_constant(&mut self, data: &ConstantStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_code.push_str(&String::from_utf8_lossy(&data.scope.get_lexeme()));
-        generated_code.push_str(" const ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
+        generated_code.extend(data.scope.get_lexeme());
+        generated_code.extend(b" const ");
+        generated_code.extend(data.name.get_lexeme());
 
         // TODO: This seems too imperative.
         match &data.kind {
             Some(kind) => {
-                generated_code.push_str(" as ");
-                generated_code.push_str(&String::from_utf8_lossy(&kind.get_lexeme()));
+                generated_code.extend(b" as ");
+                generated_code.extend(kind.get_lexeme());
             },
 
             None => (),
@@ -293,74 +344,696 @@ impl Generator {
         // TODO: This seems too imperative.
         match &data.length {
             Some(length) => {
-                generated_code.push_str(" * ");
-                generated_code.push_str(&String::from_utf8_lossy(&length.get_lexeme()));
+                generated_code.extend(b" * ");
+                generated_code.extend(length.get_lexeme());
             },
 
             None => (),
         }
 
-        generated_code.push_str(" = ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.value.get_lexeme()));
+        generated_code.extend(b" = ");
+        generated_code.extend(data.value.get_lexeme());
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_variable(&mut self, data: &VariableStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(data.scope.get_lexeme());
+        generated_code.extend(b" ");
+        generated_code.extend(data.name.get_lexeme());
+
+        if let Some(bounds) = &data.bounds {
+            generated_code.extend(self.generate_array_bounds(bounds));
+        }
+
+        // NOTE: Values are moved to assignments by the transformer.
+        if let Some(kind) = &data.kind {
+            generated_code.extend(b" as ");
+
+            if data.new.is_some() {
+                generated_code.extend(b"new ");
+            }
+
+            generated_code.extend(kind.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_redim(&mut self, data: &ReDimStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"redim ");
+
+        if data.preserve.is_some() {
+            generated_code.extend(b"preserve ");
+        }
+
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.extend(self.generate_array_bounds(&data.bounds));
+
+        if let Some(kind) = &data.kind {
+            generated_code.extend(b" as ");
+            generated_code.extend(kind.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_array_bounds(&mut self, bounds: &Vec<ArrayBound>) -> Vec<u8> {
+        let mut generated_bounds = vec!();
+
+        for bound in bounds {
+            let generated_bound = match &bound.lower {
+                Some(lower) => [self.generate_expression(lower), b" to ".to_vec(), self.generate_expression(&bound.upper)].concat(),
+                None => self.generate_expression(&bound.upper),
+            };
+
+            generated_bounds.push(generated_bound);
+        }
+
+        return [&b"("[..], &generated_bounds.join(&b", "[..]), b")"].concat();
+    }
+
+    fn generate_exit(&mut self, data: &ExitStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"exit ");
+        generated_code.extend(data.block.get_lexeme());
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_label(&mut self, data: &LabelStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.extend(b":\n");
+
+        return generated_code;
+    }
+
+    fn generate_goto(&mut self, data: &GoToStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"goto ");
+        generated_code.extend(data.label.get_lexeme());
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_procedure_call(&mut self, data: &ProcedureCallStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(self.generate_expression(&data.procedure));
+
+        if data.arguments.len() > 0 {
+            let arguments: Vec<Vec<u8>> = data.arguments.iter()
+                .map(|argument| self.generate_expression(argument))
+                .collect();
+
+            generated_code.push(b' ');
+            generated_code.extend(arguments.join(&b", "[..]));
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_on_error(&mut self, data: &OnErrorStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        match &data.label {
+            Some(label) => {
+                generated_code.extend(b"on error goto ");
+                generated_code.extend(label.get_lexeme());
+            },
+
+            None => generated_code.extend(b"on error resume next"),
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_resume(&mut self, data: &ResumeStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"resume");
+
+        if let Some(target) = &data.target {
+            generated_code.push(b' ');
+            generated_code.extend(target.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    // NOTE: Single line `If`s are generated as blocks too.
+    fn generate_if(&mut self, data: &IfStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        for (index, statement) in data.branches.iter().enumerate() {
+            match statement {
+                // TODO: This seems too imperative.
+                Statement::IfBranch(branch) => {
+                    match (&branch.condition, index) {
+                        (Some(condition), 0) => {
+                            generated_code.extend(b"if ");
+                            generated_code.extend(self.generate_expression(condition));
+                            generated_code.extend(b" then\n");
+                        },
+
+                        (Some(condition), _) => {
+                            generated_code.extend(b"elseif ");
+                            generated_code.extend(self.generate_expression(condition));
+                            generated_code.extend(b" then\n");
+                        },
+
+                        (None, _) => generated_code.extend(b"else\n"),
+                    }
+
+                    generated_code.extend(self.generate_callable_body(&branch.body));
+                },
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            }
+        }
+
+        generated_code.extend(b"end if\n");
+
+        return generated_code;
+    }
+
+    fn generate_for(&mut self, data: &ForStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"for ");
+        generated_code.extend(data.counter.get_lexeme());
+        generated_code.extend(b" = ");
+        generated_code.extend(self.generate_expression(&data.start));
+        generated_code.extend(b" to ");
+        generated_code.extend(self.generate_expression(&data.end));
+
+        if let Some(step) = &data.step {
+            generated_code.extend(b" step ");
+            generated_code.extend(self.generate_expression(step));
+        }
+
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_callable_body(&data.body));
+        generated_code.extend(b"next\n");
+
+        return generated_code;
+    }
+
+    // NOTE: Typed elements are declared by the transformer.
+    fn generate_for_each(&mut self, data: &ForEachStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"for each ");
+        generated_code.extend(data.element.get_lexeme());
+        generated_code.extend(b" in ");
+        generated_code.extend(self.generate_expression(&data.collection));
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_callable_body(&data.body));
+        generated_code.extend(b"next\n");
+
+        return generated_code;
+    }
+
+    fn generate_do(&mut self, data: &DoStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"do");
+
+        if let Some(header) = &data.header {
+            generated_code.push(b' ');
+            generated_code.extend(self.generate_loop_condition(header));
+        }
+
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_callable_body(&data.body));
+        generated_code.extend(b"loop");
 
-        generated_code.push('\n');
+        if let Some(footer) = &data.footer {
+            generated_code.push(b' ');
+            generated_code.extend(self.generate_loop_condition(footer));
+        }
+
+        generated_code.push(b'\n');
 
         return generated_code;
     }
 
-    fn generate_variable(&mut self, data: &VariableStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_loop_condition(&mut self, data: &LoopCondition) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_code.push_str(&String::from_utf8_lossy(&data.scope.get_lexeme()));
-        generated_code.push_str(" ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-        generated_code.push_str(" as ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.kind.get_lexeme()));
-        generated_code.push('\n');
+        generated_code.extend(data.kind.get_lexeme());
+        generated_code.push(b' ');
+        generated_code.extend(self.generate_expression(&data.value));
 
         return generated_code;
     }
 
-    fn generate_exit(&mut self, data: &ExitStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_while(&mut self, data: &WhileStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_code.push_str("exit ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.block.get_lexeme()));
-        generated_code.push('\n');
+        generated_code.extend(b"while ");
+        generated_code.extend(self.generate_expression(&data.condition));
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_callable_body(&data.body));
+        generated_code.extend(b"wend\n");
 
         return generated_code;
     }
 
-    fn generate_option(&mut self, data: &OptionStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_option(&mut self, data: &OptionStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
 
-        generated_code.push_str("option ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.configuration.get_lexeme()));
+        generated_code.extend(b"option ");
+        generated_code.extend(data.configuration.get_lexeme());
 
         if let Some(value) = &data.value {
-            generated_code.push(' ');
-            generated_code.push_str(&String::from_utf8_lossy(&value.get_lexeme()));
+            generated_code.push(b' ');
+            generated_code.extend(value.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_attribute(&mut self, data: &AttributeStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"Attribute ");
+
+        if let Some(procedure) = &data.procedure {
+            generated_code.extend(procedure.get_lexeme());
+            generated_code.push(b'.');
+        }
+
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.extend(b" = ");
+        generated_code.extend(data.value.get_lexeme());
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_version(&mut self, data: &VersionStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"VERSION ");
+        generated_code.extend(data.number.get_lexeme());
+
+        if let Some(kind) = &data.kind {
+            generated_code.push(b' ');
+            generated_code.extend(kind.get_lexeme().to_ascii_uppercase());
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_object(&mut self, data: &ObjectStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"Object = ");
+        generated_code.extend(self.generate_tokens(&data.value));
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    // NOTE: The `depth` is only used to indent the nested blocks the same way
+    // VB6 does.
+    fn generate_begin(&mut self, data: &BeginStatement, depth: usize) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        // NOTE: VB6 writes the class modules' header in uppercase.
+        let (begin, end) = match &data.kind {
+            Some(_) => (&b"Begin"[..], &b"End\n"[..]),
+            None => (&b"BEGIN"[..], &b"END\n"[..]),
+        };
+
+        generated_code.extend(b"   ".repeat(depth));
+        generated_code.extend(begin);
+
+        if let Some(kind) = &data.kind {
+            generated_code.push(b' ');
+            generated_code.extend(kind.get_lexeme());
+        }
+
+        if let Some(name) = &data.name {
+            generated_code.push(b' ');
+            generated_code.extend(name.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_begin_body(&data.attributes, depth + 1));
+        generated_code.extend(b"   ".repeat(depth));
+        generated_code.extend(end);
+
+        return generated_code;
+    }
+
+    fn generate_begin_property(&mut self, data: &BeginPropertyStatement, depth: usize) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"   ".repeat(depth));
+        generated_code.extend(b"BeginProperty ");
+        generated_code.extend(data.name.get_lexeme());
+
+        if let Some(index) = &data.index {
+            generated_code.push(b'(');
+            generated_code.extend(index.get_lexeme());
+            generated_code.push(b')');
+        }
+
+        if let Some(class) = &data.class {
+            generated_code.push(b' ');
+            generated_code.extend(class.get_lexeme());
+        }
+
+        generated_code.push(b'\n');
+        generated_code.extend(self.generate_begin_body(&data.attributes, depth + 1));
+        generated_code.extend(b"   ".repeat(depth));
+        generated_code.extend(b"EndProperty\n");
+
+        return generated_code;
+    }
+
+    // Used for both `Begin` and `BeginProperty` blocks.
+    fn generate_begin_body(&mut self, attributes: &Vec<Statement>, depth: usize) -> Vec<u8> {
+        let mut generated_body = vec!();
+
+        for statement in attributes {
+            let generated_statement = match statement {
+                Statement::Begin(data) => self.generate_begin(data, depth),
+                Statement::BeginProperty(data) => self.generate_begin_property(data, depth),
+
+                // TODO: This seems too imperative.
+                Statement::BeginAttribute(data) => {
+                    let mut generated_code = vec!();
+
+                    generated_code.extend(b"   ".repeat(depth));
+                    generated_code.extend(data.name.get_lexeme());
+                    generated_code.extend(b" = ");
+                    generated_code.extend(self.generate_tokens(&data.value));
+                    generated_code.push(b'\n');
+
+                    generated_code
+                },
+
+                Statement::Unparsed(data) => {
+                    let mut generated_code = vec!();
+
+                    generated_code.extend(b"   ".repeat(depth));
+                    generated_code.extend(self.generate_tokens(&data.tokens));
+                    generated_code.push(b'\n');
+
+                    generated_code
+                },
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            };
+
+            generated_body.extend(generated_statement);
+        }
+
+        return generated_body;
+    }
+
+    // Writes back tokens which were kept as they were in the source code.
+    fn generate_tokens(&mut self, tokens: &Vec<Token>) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        for token in tokens {
+            generated_code.extend(token.get_lexeme());
+
+            // NOTE: Whitespace isn't saved by the lexer, but this one is needed
+            // to separate the file name in references to components (i.e.
+            // `Object = "{...}#2.0#0"; "MSCOMCTL.OCX"`).
+            if let Token::Semicolon = token {
+                generated_code.push(b' ');
+            }
+        }
+
+        return generated_code;
+    }
+
+    fn generate_event(&mut self, data: &EventStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        if let Some(scope) = &data.scope {
+            generated_code.extend(scope.get_lexeme());
+            generated_code.push(b' ');
+        }
+
+        generated_code.extend(b"event ");
+        generated_code.extend(data.name.get_lexeme());
+
+        generated_code.extend(self.generate_arguments(&data.arguments));
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_raise_event(&mut self, data: &RaiseEventStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"raiseevent ");
+        generated_code.extend(data.name.get_lexeme());
+
+        if data.arguments.len() > 0 {
+            let arguments: Vec<Vec<u8>> = data.arguments.iter()
+                .map(|argument| self.generate_expression(argument))
+                .collect();
+
+            generated_code.push(b'(');
+            generated_code.extend(arguments.join(&b", "[..]));
+            generated_code.push(b')');
+        }
+
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_implements(&mut self, data: &ImplementsStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"implements ");
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    // NOTE: See `Parser::parse_directive_if`.
+    fn generate_directive_if(
+        &mut self,
+        data: &DirectiveIfStatement,
+        generate_body: fn(&mut Self, &Vec<Statement>) -> Vec<u8>,
+    ) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        for (index, statement) in data.branches.iter().enumerate() {
+            match statement {
+                // TODO: This seems too imperative.
+                Statement::DirectiveBranch(branch) => {
+                    match (&branch.condition, index) {
+                        (Some(condition), 0) => {
+                            generated_code.extend(b"#if ");
+                            generated_code.extend(self.generate_expression(condition));
+                            generated_code.extend(b" then\n");
+                        },
+
+                        (Some(condition), _) => {
+                            generated_code.extend(b"#elseif ");
+                            generated_code.extend(self.generate_expression(condition));
+                            generated_code.extend(b" then\n");
+                        },
+
+                        (None, _) => generated_code.extend(b"#else\n"),
+                    }
+
+                    generated_code.extend(generate_body(self, &branch.body));
+                },
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            }
+        }
+
+        generated_code.extend(b"#end if\n");
+
+        return generated_code;
+    }
+
+    fn generate_directive_const(&mut self, data: &DirectiveConstStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"#const ");
+        generated_code.extend(data.name.get_lexeme());
+        generated_code.extend(b" = ");
+        generated_code.extend(self.generate_expression(&data.value));
+        generated_code.push(b'\n');
+
+        return generated_code;
+    }
+
+    fn generate_expression(&mut self, statement: &Statement) -> Vec<u8> {
+        return match statement {
+            Statement::Value(data) => self.generate_value(data),
+            Statement::New(data) => self.generate_new(data),
+            Statement::Unary(data) => self.generate_unary(data),
+            Statement::Binary(data) => self.generate_binary(data),
+            Statement::Call(data) => self.generate_call(data),
+            Statement::Member(data) => self.generate_member(data),
+            Statement::TypeOf(data) => self.generate_type_of(data),
+
+            // TODO: Is it correct to `panic`?
+            //
+            // TODO: Add a message?
+            _ => unreachable!(),
+        };
+    }
+
+    fn generate_value(&mut self, data: &ValueStatement) -> Vec<u8> {
+        return data.value.get_lexeme();
+    }
+
+    fn generate_new(&mut self, data: &NewStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(b"new ");
+        generated_code.extend(data.kind.get_lexeme());
+
+        return generated_code;
+    }
+
+    fn generate_unary(&mut self, data: &UnaryStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(data.operator.get_lexeme());
+
+        if let Token::Not = data.operator {
+            generated_code.push(b' ');
         }
 
-        generated_code.push('\n');
+        // NOTE: See `get_unary_precedence`, it can't fail for unary operators.
+        let precedence = data.operator.get_unary_precedence().unwrap();
+
+        generated_code.extend(self.generate_operand(&data.value, precedence));
 
         return generated_code;
     }
 
-    fn generate_attribute(&mut self, data: &AttributeStatement) -> String {
-        let mut generated_code = String::new();
+    fn generate_binary(&mut self, data: &BinaryStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        // NOTE: See `get_precedence`, it can't fail for binary operators.
+        let precedence = data.operator.get_precedence().unwrap();
 
-        generated_code.push_str("Attribute ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.name.get_lexeme()));
-        generated_code.push_str(" = ");
-        generated_code.push_str(&String::from_utf8_lossy(&data.value.get_lexeme()));
-        generated_code.push('\n');
+        // NOTE: Operators are left associative, so the right operand needs
+        // parentheses when its operator binds as tight as this one.
+        generated_code.extend(self.generate_operand(&data.left, precedence));
+        generated_code.push(b' ');
+        generated_code.extend(data.operator.get_lexeme());
+        generated_code.push(b' ');
+        generated_code.extend(self.generate_operand(&data.right, precedence + 1));
 
         return generated_code;
     }
+
+    fn generate_call(&mut self, data: &CallStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        let arguments: Vec<Vec<u8>> = data.arguments.iter()
+            .map(|argument| self.generate_expression(argument))
+            .collect();
+
+        generated_code.extend(self.generate_expression(&data.callee));
+        generated_code.push(b'(');
+        generated_code.extend(arguments.join(&b", "[..]));
+        generated_code.push(b')');
+
+        return generated_code;
+    }
+
+    fn generate_member(&mut self, data: &MemberStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        generated_code.extend(self.generate_expression(&data.object));
+        generated_code.push(b'.');
+        generated_code.extend(data.member.get_lexeme());
+
+        return generated_code;
+    }
+
+    fn generate_type_of(&mut self, data: &TypeOfStatement) -> Vec<u8> {
+        let mut generated_code = vec!();
+
+        // NOTE: See `get_precedence`, it can't fail for `Is`.
+        let precedence = Token::Is.get_precedence().unwrap();
+
+        generated_code.extend(b"typeof ");
+        generated_code.extend(self.generate_operand(&data.value, precedence + 1));
+        generated_code.extend(b" is ");
+        generated_code.extend(data.kind.get_lexeme());
+
+        return generated_code;
+    }
+
+    // Generates an operation's operand, surrounding it with parentheses if it
+    // binds looser than `precedence` (which is how the parser dropped them).
+    fn generate_operand(&mut self, statement: &Statement, precedence: u8) -> Vec<u8> {
+        let operand_precedence = match statement {
+            Statement::Unary(data) => data.operator.get_unary_precedence(),
+            Statement::Binary(data) => data.operator.get_precedence(),
+
+            // NOTE: `TypeOf value Is Kind` is a comparison.
+            Statement::TypeOf(_) => Token::Is.get_precedence(),
+
+            _ => None,
+        };
+
+        let generated_code = self.generate_expression(statement);
+
+        return match operand_precedence {
+            Some(operand_precedence) if operand_precedence < precedence => [&b"("[..], &generated_code, b")"].concat(),
+            _ => generated_code,
+        };
+    }
 }
 
-pub fn generate(statements: Vec<Statement>) -> String {
+pub fn generate(statements: Vec<Statement>) -> Vec<u8> {
     let mut generator = Generator::new(statements);
 
     return generator.generate();
@@ -9,9 +9,14 @@ pub fn lex(characters: &Vec<u8>) -> Vec<Token> {
         // NOTE: Order here is useful to prioritize, we want the lexeme to be as big as possible.
         if lex_comment(&characters, &mut position, &mut tokens) { continue; }
         if lex_whitespace(&characters, &mut position, &mut tokens) { continue; }
+        if lex_escaped_string(&characters, &mut position, &mut tokens) { continue; }
         if lex_identifier(&characters, &mut position, &mut tokens) { continue; }
         if lex_number(&characters, &mut position, &mut tokens) { continue; }
+        if lex_prefixed_number(&characters, &mut position, &mut tokens) { continue; }
+        if lex_interpolated_string(&characters, &mut position, &mut tokens) { continue; }
+        if lex_raw_string(&characters, &mut position, &mut tokens) { continue; }
         if lex_string(&characters, &mut position, &mut tokens) { continue; }
+        if lex_hidden_identifier(&characters, &mut position, &mut tokens) { continue; }
         if lex_symbol(&characters, &mut position, &mut tokens) { continue; }
 
         // TODO: Return an error containing the lexeme.
@@ -54,6 +59,8 @@ fn lex_whitespace(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
 
     if !(character.is_whitespace()) { return false; }
 
+    let mut is_new_line = character == '\n';
+
     // Count the first character.
     let mut length = 1usize;
 
@@ -64,11 +71,18 @@ fn lex_whitespace(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
         // Analyze the next character.
         if !(character.is_whitespace()) { break; }
 
+        is_new_line = is_new_line || character == '\n';
+
         // Count the previous character.
         length += 1;
     }
 
-    // NOTE: Whitespace tokens are not saved.
+    // NOTE: Whitespace tokens are not saved, but new lines are because some
+    // statements end at them (i.e. the properties of a form's controls).
+    if is_new_line {
+        tokens.push(Token::NewLine);
+    }
+
     *position += length;
 
     return true;
@@ -94,10 +108,17 @@ fn lex_identifier(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
         length += 1;
     }
 
+    // NOTE: The string type character is part of the identifier (i.e. the
+    // `Format$` function), unlike the others which may also be operators.
+    if characters.get(*position + length) == Some(&b'$') {
+        length += 1;
+    }
+
     let lexeme = characters[*position..*position + length].to_vec();
     let token = match &std::str::from_utf8(&lexeme).unwrap().to_lowercase() as &str {
         "public" => Token::Public,
         "private" => Token::Private,
+        "friend" => Token::Friend,
         "static" => Token::Static,
         "dim" => Token::Dim,
 
@@ -106,8 +127,14 @@ fn lex_identifier(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
 
         "as" => Token::As,
         "if" => Token::If,
+        "then" => Token::Then,
+        "else" => Token::Else,
+        "elseif" => Token::ElseIf,
+        "case" => Token::Case,
         "sub" => Token::Sub,
         "function" => Token::Function,
+        "property" => Token::Property,
+        "get" => Token::Get,
         "type" => Token::Type,
         "enum" => Token::Enum,
         "const" => Token::Const,
@@ -115,19 +142,47 @@ fn lex_identifier(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
 
         "exit" => Token::Exit,
         "return" => Token::Return,
+        "goto" => Token::GoTo,
+        "call" => Token::Call,
+
+        "on" => Token::On,
+        "resume" => Token::Resume,
+
+        "event" => Token::Event,
+        "raiseevent" => Token::RaiseEvent,
+        "implements" => Token::Implements,
+
+        "let" => Token::Let,
+        "set" => Token::Set,
+        "new" => Token::New,
+        "nothing" => Token::Nothing,
+
+        "redim" => Token::ReDim,
+        "preserve" => Token::Preserve,
 
         "do" => Token::Do,
         "loop" => Token::Loop,
+        "until" => Token::Until,
 
         "while" => Token::While,
         "wend" => Token::Wend,
 
         "for" => Token::For,
+        "to" => Token::To,
+        "step" => Token::Step,
         "next" => Token::Next,
 
+        "not" => Token::Not,
         "and" => Token::And,
         "or" => Token::Or,
         "xor" => Token::Xor,
+        "eqv" => Token::Eqv,
+        "imp" => Token::Imp,
+
+        "mod" => Token::Mod,
+        "like" => Token::Like,
+        "is" => Token::Is,
+        "typeof" => Token::TypeOf,
 
         "option" => Token::Option,
         "explicit" => Token::Explicit,
@@ -146,6 +201,41 @@ fn lex_identifier(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<T
     return true;
 }
 
+// NOTE: Only hidden names (i.e. `[_NewEnum]`) are lexed between brackets,
+// since anything else may be an array literal (i.e. `[a]`).
+fn lex_hidden_identifier(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
+    // The first characters must be a [ (bracket) and a _ (underscore).
+    if !characters[*position..].starts_with(b"[_") { return false; }
+
+    // Count the first characters.
+    let mut length = 2usize;
+
+    while (*position + length) < characters.len() {
+        // Peek the next character.
+        let character = characters[*position + length] as char;
+
+        // Analyze the next character.
+        if !(character.is_alphanumeric() || character == '_') { break; }
+
+        // Count the previous character.
+        length += 1;
+    }
+
+    // The last character must be a ] (bracket).
+    if characters.get(*position + length) != Some(&b']') { return false; }
+
+    // Count the closing bracket.
+    length += 1;
+
+    let lexeme = characters[*position..*position + length].to_vec();
+    let token = Token::Identifier(lexeme);
+
+    tokens.push(token);
+    *position += length;
+
+    return true;
+}
+
 fn lex_number(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
     let mut character = characters[*position] as char;
 
@@ -167,12 +257,10 @@ fn lex_number(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
     }
 
     // TODO: This is a workaround to also lex numbers that have a decimal part.
-    // We should add bounds checks here, and analyze if the construction of
-    // decimal numbers should happen at the lexing stage or at the parsing
-    // stage of the compiler.
-    if characters[*position + length] as char == '.' {
-        // TODO: Add bounds checks here!
-        if (characters[*position + length + 1] as char).is_numeric() {
+    // We should analyze if the construction of decimal numbers should happen
+    // at the lexing stage or at the parsing stage of the compiler.
+    if characters.get(*position + length) == Some(&b'.') {
+        if (*characters.get(*position + length + 1).unwrap_or(&b'\0') as char).is_numeric() {
             length += 2;
 
             while (*position + length) < characters.len() {
@@ -197,6 +285,51 @@ fn lex_number(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
     return true;
 }
 
+// Lexes hexadecimal (`&H`) and octal (`&O`) numbers, such as `&H8000000F&`.
+fn lex_prefixed_number(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
+    let character = characters[*position] as char;
+    let next_character = *characters.get(*position + 1).unwrap_or(&b'\0') as char;
+
+    // The first characters must be a & (ampersand) followed by the radix.
+    if !(character == '&') { return false; }
+
+    let is_digit = match next_character.to_ascii_lowercase() {
+        'h' => |c: char| c.is_ascii_hexdigit(),
+        'o' => |c: char| c.is_digit(8),
+        _ => return false,
+    };
+
+    // Count the ampersand and the radix.
+    let mut length = 2usize;
+
+    while (*position + length) < characters.len() {
+        // Peek the next character.
+        let character = characters[*position + length] as char;
+
+        // Analyze the next character.
+        if !(is_digit(character)) { break; }
+
+        // Count the previous character.
+        length += 1;
+    }
+
+    // There must be at least one digit after the radix.
+    if length == 2 { return false; }
+
+    // Count the (optional) `Long` type suffix.
+    if *characters.get(*position + length).unwrap_or(&b'\0') as char == '&' {
+        length += 1;
+    }
+
+    let lexeme = characters[*position..*position + length].to_vec();
+    let token = Token::Number(lexeme);
+
+    tokens.push(token);
+    *position += length;
+
+    return true;
+}
+
 fn lex_string(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
     let mut character = characters[*position] as char;
 
@@ -234,6 +367,172 @@ fn lex_string(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
     return true;
 }
 
+// NOTE: Embedded expressions may contain strings, whose quotes don't close
+// the interpolated string.
+fn lex_interpolated_string(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
+    // The first characters must be a $ (dollar) and a " (quote).
+    if !characters[*position..].starts_with(b"$\"") { return false; }
+
+    // Count the first characters.
+    let mut length = 2usize;
+    let mut depth = 0usize;
+    let mut is_closed = false;
+
+    while (*position + length) < characters.len() {
+        // Peek the next characters.
+        let character = characters[*position + length] as char;
+        let next_character = *characters.get(*position + length + 1).unwrap_or(&b'\0') as char;
+
+        match (character, next_character, depth) {
+            // NOTE: Doubled quotes and braces stand for themselves.
+            ('"', '"', 0) | ('{', '{', 0) | ('}', '}', 0) => length += 2,
+
+            ('"', _, 0) => {
+                // Count the closing quote.
+                length += 1;
+                is_closed = true;
+                break;
+            },
+
+            ('{', _, _) => { depth += 1; length += 1; },
+            ('}', _, _) => { depth = depth.saturating_sub(1); length += 1; },
+
+            // Skip a string inside an embedded expression.
+            ('"', _, _) => {
+                length += 1;
+
+                while (*position + length) < characters.len() && characters[*position + length] != b'"' {
+                    length += 1;
+                }
+
+                length += 1;
+            },
+
+            ('\n', _, _) => break,
+
+            _ => length += 1,
+        }
+    }
+
+    if !is_closed { return false; }
+
+    let lexeme = characters[*position + 2..*position + length - 1].to_vec();
+    let token = Token::InterpolatedString(lexeme);
+
+    tokens.push(token);
+    *position += length;
+
+    return true;
+}
+
+// NOTE: The escape sequences are kept as they are, the transformer is the one
+// which replaces them.
+fn lex_escaped_string(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
+    // The first characters must be an e and a " (quote).
+    if !(characters[*position..].starts_with(b"e\"") || characters[*position..].starts_with(b"E\"")) { return false; }
+
+    // Count the first characters.
+    let mut length = 2usize;
+    let mut is_closed = false;
+
+    while (*position + length) < characters.len() {
+        // Peek the next character.
+        let character = characters[*position + length] as char;
+
+        match character {
+            // Count the escaped character too.
+            '\\' => length += 2,
+
+            '"' => {
+                // Count the closing quote.
+                length += 1;
+                is_closed = true;
+                break;
+            },
+
+            '\r' | '\n' => break,
+
+            _ => length += 1,
+        }
+    }
+
+    if !is_closed { return false; }
+
+    let lexeme = characters[*position + 2..*position + length - 1].to_vec();
+    let token = Token::EscapedString(lexeme);
+
+    tokens.push(token);
+    *position += length;
+
+    return true;
+}
+
+// Lexes multi-line strings, which start with three quotes at the end of a
+// line and finish with three quotes at the beginning of one:
+//
+//     query = """
+//         SELECT *
+//         FROM customers
+//         """
+//
+// NOTE: The lines are unindented by the whitespace before the closing quotes,
+// and the line breaks around them aren't part of the string.
+fn lex_raw_string(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
+    // The first characters must be three " (quotes).
+    if !characters[*position..].starts_with(b"\"\"\"") { return false; }
+
+    // Count the first characters.
+    let mut length = 3usize;
+
+    // NOTE: Otherwise, these are VB6's strings with doubled quotes (i.e.
+    // `"""quoted"""`).
+    while characters.get(*position + length) == Some(&b' ') || characters.get(*position + length) == Some(&b'\t') {
+        length += 1;
+    }
+
+    if characters.get(*position + length) == Some(&b'\r') { length += 1; }
+    if characters.get(*position + length) != Some(&b'\n') { return false; }
+
+    length += 1;
+
+    let mut lines: Vec<&[u8]> = vec!();
+
+    loop {
+        if *position + length >= characters.len() { return false; }
+
+        let start = *position + length;
+        let end = characters[start..].iter().position(|character| *character == b'\n')
+            .map(|end| start + end)
+            .unwrap_or(characters.len());
+
+        let line = &characters[start..end];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        let indentation = line.iter().take_while(|character| **character == b' ' || **character == b'\t').count();
+
+        if line[indentation..].starts_with(b"\"\"\"") {
+            length += indentation + 3;
+
+            let lexeme = lines.iter()
+                .map(|line| {
+                    let whitespace = line.iter().take(indentation).take_while(|character| **character == b' ' || **character == b'\t').count();
+
+                    &line[whitespace..]
+                })
+                .collect::<Vec<&[u8]>>()
+                .join(&b'\n');
+
+            tokens.push(Token::RawString(lexeme));
+            *position += length;
+
+            return true;
+        }
+
+        lines.push(line);
+        length = end + 1 - *position;
+    }
+}
+
 fn lex_symbol(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token>) -> bool {
     let character = characters[*position] as char;
     let next_character = *characters.get(*position + 1).unwrap_or(&b'\0') as char;
@@ -245,11 +544,28 @@ fn lex_symbol(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
         ('[', _) => Some(Token::LeftBracket),
         (']', _) => Some(Token::RightBracket),
 
+        ('{', _) => Some(Token::LeftBrace),
+        ('}', _) => Some(Token::RightBrace),
+
+        ('+', '=') => Some(Token::PlusAssignment),
         ('+', _) => Some(Token::Plus),
+
+        ('-', '=') => Some(Token::MinusAssignment),
         ('-', _) => Some(Token::Minus),
+
+        ('*', '=') => Some(Token::TimesAssignment),
         ('*', _) => Some(Token::Times),
+
+        ('/', '=') => Some(Token::DivideAssignment),
         ('/', _) => Some(Token::Divide),
 
+        ('&', '=') => Some(Token::AmpersandAssignment),
+        ('&', _) => Some(Token::Ampersand),
+
+        ('\\', _) => Some(Token::IntegerDivide),
+        ('^', _) => Some(Token::Caret),
+
+        ('<', '>') => Some(Token::NotEqual),
         ('<', '=') => Some(Token::LessOrEqual),
         ('<', _) => Some(Token::Less),
 
@@ -257,7 +573,13 @@ fn lex_symbol(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
         ('>', _) => Some(Token::Greater),
 
         ('=', _) => Some(Token::Assignment),
+        ('.', '.') if characters.get(*position + 2) == Some(&b'<') => Some(Token::ExclusiveRange),
+        ('.', '.') => Some(Token::Range),
         ('.', _) => Some(Token::Dot),
+        (',', _) => Some(Token::Comma),
+        (':', _) => Some(Token::Colon),
+        (';', _) => Some(Token::Semicolon),
+        ('#', _) => Some(Token::Hash),
 
         (_, _) => None,
     };
@@ -268,7 +590,13 @@ fn lex_symbol(characters: &Vec<u8>, position: &mut usize, tokens: &mut Vec<Token
     };
 
     let length = match token {
-        Token::LessOrEqual | Token::GreaterOrEqual => 2,
+        Token::LessOrEqual | Token::GreaterOrEqual | Token::NotEqual => 2,
+
+        Token::PlusAssignment | Token::MinusAssignment | Token::TimesAssignment |
+        Token::DivideAssignment | Token::AmpersandAssignment => 2,
+
+        Token::Range => 2,
+        Token::ExclusiveRange => 3,
         _ => 1,
     };
 
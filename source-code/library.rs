@@ -1,12 +1,24 @@
 mod lexer;
 mod parser;
+mod preprocessor;
+mod analyzer;
+mod symbols;
+mod scoper;
 mod transformer;
 mod generator;
 mod viewer;
 mod token;
 mod statement;
+mod diagnostic;
+mod project;
+mod compiler;
 
 pub use lexer::lex;
 pub use parser::parse;
+pub use preprocessor::{preprocess, parse_defines};
+pub use analyzer::analyze;
 pub use transformer::transform;
 pub use generator::generate;
+pub use diagnostic::{Diagnostic, Severity};
+pub use project::{compile_project, CompiledFile};
+pub use compiler::{compile, to_crlf, Compilation};
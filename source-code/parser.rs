@@ -15,10 +15,19 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse(&mut self) -> Vec<Statement> {
+        return self.parse_module_body();
+    }
+
+    // Used for both the whole module and the conditionally compiled blocks
+    // which stand outside of functions and subroutines.
+    fn parse_module_body(&mut self) -> Vec<Statement> {
         let parsers = [
             Parser::parse_type, Parser::parse_variable, Parser::parse_constant,
-            Parser::parse_subroutine, Parser::parse_function, Parser::parse_enum,
-            Parser::parse_attribute, Parser::parse_option,
+            Parser::parse_subroutine, Parser::parse_function, Parser::parse_property,
+            Parser::parse_enum, Parser::parse_attribute, Parser::parse_option,
+            Parser::parse_version, Parser::parse_object, Parser::parse_begin,
+            Parser::parse_event, Parser::parse_implements,
+            Parser::parse_module_directive_if, Parser::parse_directive_const,
         ];
 
         let mut statements = vec!();
@@ -55,13 +64,40 @@ impl<'a> Parser<'a> {
         return left_discriminant == right_discriminant;
     }
 
-    fn consume(&mut self, expected_token: Token) -> Option<Token> {
+    // Returns the position of the next token, skipping new lines.
+    //
+    // NOTE: New lines are meaningful only to a few statements, so every other
+    // statement is parsed as if they didn't exist.
+    fn skip_new_lines(&self) -> usize {
+        let mut position = self.tokens_position;
+
+        while position < self.tokens.len() && self.tokens[position] == Token::NewLine {
+            position += 1;
+        }
+
+        return position;
+    }
+
+    fn is_line_end(&self) -> bool {
         if self.tokens_position >= self.tokens.len() {
+            return true;
+        }
+
+        return self.tokens[self.tokens_position] == Token::NewLine;
+    }
+
+    fn consume(&mut self, expected_token: Token) -> Option<Token> {
+        let position = match expected_token {
+            Token::NewLine => self.tokens_position,
+            _ => self.skip_new_lines(),
+        };
+
+        if position >= self.tokens.len() {
             return None;
         }
 
-        if Self::compare(&self.tokens[self.tokens_position], &expected_token) {
-            self.tokens_position += 1;
+        if Self::compare(&self.tokens[position], &expected_token) {
+            self.tokens_position = position + 1;
 
             // TODO: Cloning here!
             return Some(self.tokens[self.tokens_position - 1].clone());
@@ -70,6 +106,71 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // Consumes every token up to the end of the current line.
+    fn consume_line(&mut self) -> Vec<Token> {
+        let mut tokens = vec!();
+
+        while !self.is_line_end() {
+            // TODO: Cloning here!
+            tokens.push(self.tokens[self.tokens_position].clone());
+            self.tokens_position += 1;
+        }
+
+        return tokens;
+    }
+
+    // Consumes an identifier only if its lexeme matches the given one.
+    //
+    // NOTE: This is used for words which VB6 treats as keywords only in some
+    // places (i.e. `VERSION` or `BEGIN` in a class module's header), so they
+    // can still be used as identifiers everywhere else.
+    fn consume_contextual(&mut self, lexeme: &[u8]) -> Option<Token> {
+        let position = self.skip_new_lines();
+
+        if position >= self.tokens.len() {
+            return None;
+        }
+
+        if let Token::Identifier(identifier) = &self.tokens[position] {
+            if identifier.eq_ignore_ascii_case(lexeme) {
+                self.tokens_position = position + 1;
+
+                // TODO: Cloning here!
+                return Some(self.tokens[self.tokens_position - 1].clone());
+            }
+        }
+
+        return None;
+    }
+
+    // Consumes a dotted name (i.e. `VB.CommandButton`) as a single identifier.
+    fn consume_qualified_identifier(&mut self) -> Option<Token> {
+        // TODO: Remove `vec!`.
+        let mut lexeme = self.consume(Token::Identifier(vec!()))?.get_lexeme();
+
+        while self.consume(Token::Dot).is_some() {
+            lexeme.push(b'.');
+            lexeme.append(&mut self.consume(Token::Identifier(vec!()))?.get_lexeme());
+        }
+
+        return Some(Token::Identifier(lexeme));
+    }
+
+    // NOTE: The lexer has no notion of negative numbers (they are a `Minus`
+    // followed by a `Number`), so they are put back together here for the
+    // places where VB6 expects a literal instead of an expression.
+    fn consume_signed_number(&mut self) -> Option<Token> {
+        let sign = self.consume(Token::Minus);
+
+        // TODO: Remove `vec!`.
+        let number = self.consume(Token::Number(vec!()))?;
+
+        return match sign {
+            Some(_) => Some(Token::Number([b"-".to_vec(), number.get_lexeme()].concat())),
+            None => Some(number),
+        };
+    }
+
     fn parse_type(&mut self) -> Option<Statement> {
         // Assert there is a `Type` keyword and a identifier containing the
         // type's name.
@@ -163,12 +264,82 @@ impl<'a> Parser<'a> {
 
         // TODO: Remove `vec!`.
         let name = self.consume(Token::Identifier(vec!()))?;
-        let _ = self.consume(Token::As)?;
-        let kind = self.consume(Token::Identifier(vec!()))?;
+
+        let bounds = match self.consume(Token::LeftParentheses) {
+            Some(_) => Some(self.parse_array_bounds()?),
+            None => None,
+        };
+
+        let (new, kind) = match self.consume(Token::As) {
+            Some(_) => (self.consume(Token::New), Some(self.consume_qualified_identifier()?)),
+            None => (None, None),
+        };
+
+        // NOTE: Variables without a data type must have a value to infer it
+        // from, while the ones declared `As New` can't have any.
+        let value = match (&kind, &new, self.consume(Token::Assignment)) {
+            (_, None, Some(_)) => Some(Box::new(self.parse_expression()?)),
+            (Some(_), _, None) => None,
+            _ => return None,
+        };
 
         return Some(Statement::Variable(VariableStatement {
             scope: scope.clone(),
             name: name,
+            bounds: bounds,
+            kind: kind,
+            new: new,
+            value: value,
+        }));
+    }
+
+    // Parses the bounds of an array's dimensions up to the closing parentheses
+    // (i.e. `1 To 10, 5)`).
+    fn parse_array_bounds(&mut self) -> Option<Vec<ArrayBound>> {
+        let mut bounds = vec!();
+
+        while let Some(upper) = self.parse_expression() {
+            let bound = match self.consume(Token::To) {
+                Some(_) => ArrayBound {
+                    lower: Some(Box::new(upper)),
+                    upper: Box::new(self.parse_expression()?),
+                },
+
+                None => ArrayBound {
+                    lower: None,
+                    upper: Box::new(upper),
+                },
+            };
+
+            bounds.push(bound);
+
+            if self.consume(Token::Comma).is_none() { break; }
+        }
+
+        let _ = self.consume(Token::RightParentheses)?;
+
+        return Some(bounds);
+    }
+
+    fn parse_redim(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::ReDim)?;
+        let preserve = self.consume(Token::Preserve);
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+
+        let _ = self.consume(Token::LeftParentheses)?;
+        let bounds = self.parse_array_bounds()?;
+
+        let kind = match self.consume(Token::As) {
+            Some(_) => Some(self.consume(Token::Identifier(vec!()))?),
+            None => None,
+        };
+
+        return Some(Statement::ReDim(ReDimStatement {
+            preserve: preserve,
+            name: name,
+            bounds: bounds,
             kind: kind,
         }));
     }
@@ -214,7 +385,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_subroutine(&mut self) -> Option<Statement> {
-        let possible_scopes = [Token::Public, Token::Private, Token::Static];
+        let possible_scopes = [Token::Public, Token::Private, Token::Friend, Token::Static];
 
         // NOTE: See `parse_variable`.
         let scope = std::iter::IntoIterator::into_iter(possible_scopes).find_map(|t| self.consume(t))?;
@@ -229,6 +400,8 @@ impl<'a> Parser<'a> {
 
         while let Some(argument) = self.parse_callable_argument() {
             arguments.push(argument);
+
+            if self.consume(Token::Comma).is_none() { break; }
         }
 
         let _ = self.consume(Token::RightParentheses)?;
@@ -249,7 +422,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_function(&mut self) -> Option<Statement> {
-        let possible_scopes = [Token::Public, Token::Private, Token::Static];
+        let possible_scopes = [Token::Public, Token::Private, Token::Friend, Token::Static];
 
         // NOTE: See `parse_variable`.
         let scope = std::iter::IntoIterator::into_iter(possible_scopes).find_map(|t| self.consume(t))?;
@@ -264,12 +437,14 @@ impl<'a> Parser<'a> {
 
         while let Some(argument) = self.parse_callable_argument() {
             arguments.push(argument);
+
+            if self.consume(Token::Comma).is_none() { break; }
         }
 
         let _ = self.consume(Token::RightParentheses)?;
 
         let kind = match self.consume(Token::As) {
-            Some(_) => self.consume(Token::Identifier(vec!())),
+            Some(_) => self.consume_qualified_identifier(),
             None => None,
         };
 
@@ -289,7 +464,54 @@ impl<'a> Parser<'a> {
         }));
     }
 
-    // Used for both functions and subroutines.
+    fn parse_property(&mut self) -> Option<Statement> {
+        let possible_scopes = [Token::Public, Token::Private, Token::Friend, Token::Static];
+
+        // NOTE: See `parse_variable`.
+        let scope = std::iter::IntoIterator::into_iter(possible_scopes).find_map(|t| self.consume(t))?;
+
+        let _ = self.consume(Token::Property)?;
+
+        let possible_accessors = [Token::Get, Token::Let, Token::Set];
+
+        // NOTE: See `parse_variable`.
+        let accessor = std::iter::IntoIterator::into_iter(possible_accessors).find_map(|t| self.consume(t))?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::LeftParentheses)?;
+
+        let mut arguments = vec!();
+
+        while let Some(argument) = self.parse_callable_argument() {
+            arguments.push(argument);
+
+            if self.consume(Token::Comma).is_none() { break; }
+        }
+
+        let _ = self.consume(Token::RightParentheses)?;
+
+        let kind = match self.consume(Token::As) {
+            Some(_) => self.consume_qualified_identifier(),
+            None => None,
+        };
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume(Token::Property)?;
+
+        return Some(Statement::Property(PropertyStatement {
+            scope: scope,
+            accessor: accessor,
+            name: name,
+            arguments: arguments,
+            kind: kind,
+            body: body,
+        }));
+    }
+
+    // Used for functions, subroutines and properties.
     fn parse_callable_argument(&mut self) -> Option<Statement> {
         let possible_modifiers = [Token::ByVal, Token::ByRef,];
 
@@ -298,150 +520,1858 @@ impl<'a> Parser<'a> {
 
         // TODO: Remove `vec!`.
         let name = self.consume(Token::Identifier(vec!()))?;
+
+        let bounds = match self.consume(Token::LeftParentheses) {
+            Some(_) => Some(self.parse_array_bounds()?),
+            None => None,
+        };
+
         let _ = self.consume(Token::As)?;
         let kind = self.consume(Token::Identifier(vec!()))?;
 
         return Some(Statement::Argument(ArgumentStatement {
             modifier: modifier,
             name: name,
+            bounds: bounds,
             kind: kind,
         }));
     }
 
-    // Used for both functions and subroutines.
+    // Used for functions, subroutines and properties, along with the blocks
+    // inside them (i.e. loops).
     fn parse_callable_body(&mut self) -> Vec<Statement> {
-        let parsers = [
-            Parser::parse_variable, Parser::parse_constant, Parser::parse_assignment,
-            Parser::parse_exit, Parser::parse_return, Parser::parse_attribute,
-        ];
-
         let mut statements = vec!();
 
-        'parse_next_statement: while self.tokens_position < self.tokens.len() {
-            // Try each one of the specialized parsers to see if we can
-            // produce an statement.
-            for parser in &parsers {
-                let position_before_parsing = self.tokens_position;
+        while self.tokens_position < self.tokens.len() {
+            match self.parse_callable_statement() {
+                Some(statement) => statements.push(statement),
 
-                if let Some(statement) = parser(self) {
-                    statements.push(statement);
-                    continue 'parse_next_statement;
-                } else {
-                    self.tokens_position = position_before_parsing;
-                }
+                // None of the parsers could convert the remaining tokens into
+                // a statement.
+                //
+                // TODO: Return an error?
+                None => break,
             }
-
-            // None of the previous parsers could convert the remaining tokens
-            // into a statement.
-            //
-            // TODO: Return an error?
-            break;
         }
 
         return statements;
     }
 
+    // NOTE: Single line `If`s contain just a statement, so this is apart from
+    // `parse_callable_body`.
+    fn parse_callable_statement(&mut self) -> Option<Statement> {
+        let parsers = [
+            Parser::parse_variable, Parser::parse_redim, Parser::parse_constant,
+            Parser::parse_assignment, Parser::parse_compound_assignment, Parser::parse_exit, Parser::parse_return,
+            Parser::parse_attribute, Parser::parse_raise_event, Parser::parse_if,
+            Parser::parse_for, Parser::parse_for_each, Parser::parse_for_range, Parser::parse_do, Parser::parse_while, Parser::parse_continue, Parser::parse_defer,
+            Parser::parse_labelled_loop, Parser::parse_label, Parser::parse_goto, Parser::parse_try, Parser::parse_using, Parser::parse_match, Parser::parse_on_error,
+            Parser::parse_resume, Parser::parse_callable_directive_if,
+            Parser::parse_directive_const,
+
+            // NOTE: Anything starting with an identifier looks like a call, so
+            // this goes last.
+            Parser::parse_procedure_call,
+        ];
+
+        // Try each one of the specialized parsers to see if we can produce an
+        // statement.
+        for parser in &parsers {
+            let position_before_parsing = self.tokens_position;
+
+            if let Some(statement) = parser(self) {
+                return Some(statement);
+            } else {
+                self.tokens_position = position_before_parsing;
+            }
+        }
+
+        return None;
+    }
+
     fn parse_assignment(&mut self) -> Option<Statement> {
-        // TODO: Remove `vec!`.
-        let left = self.consume(Token::Identifier(vec!()))?;
+        let possible_modifiers = [Token::Let, Token::Set];
+
+        // NOTE: See `parse_variable`.
+        let modifier = std::iter::IntoIterator::into_iter(possible_modifiers).find_map(|t| self.consume(t));
+
+        let left = self.parse_target()?;
         let _ = self.consume(Token::Assignment)?;
+        let right = self.parse_expression()?;
 
-        // TODO: Use `parse_expression`.
-        // TODO: Remove `vec!`.
-        let possible_values = [
-            Token::Identifier(vec!()), Token::Number(vec!()), Token::String(vec!())
+        return Some(Statement::Assignment(AssignmentStatement {
+            modifier: modifier,
+            left: Box::new(left),
+            right: Box::new(right),
+        }));
+    }
+
+    fn parse_compound_assignment(&mut self) -> Option<Statement> {
+        let left = self.parse_target()?;
+
+        let possible_operators = [
+            Token::PlusAssignment, Token::MinusAssignment, Token::TimesAssignment,
+            Token::DivideAssignment, Token::AmpersandAssignment,
         ];
 
         // NOTE: See `parse_variable`.
-        let right = std::iter::IntoIterator::into_iter(possible_values).find_map(|t| self.consume(t))?;
+        let operator = std::iter::IntoIterator::into_iter(possible_operators).find_map(|t| self.consume(t))?;
+        let right = self.parse_expression()?;
 
-        return Some(Statement::Assignment(AssignmentStatement {
-            left: left,
+        return Some(Statement::CompoundAssignment(CompoundAssignmentStatement {
+            left: Box::new(left),
+            operator: operator,
             right: Box::new(right),
         }));
     }
 
+    // Parses what can be assigned to: a variable, an array's element or an
+    // object's member (i.e. `items(index).name`).
+    fn parse_target(&mut self) -> Option<Statement> {
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+
+        return self.parse_postfix(Statement::Value(ValueStatement {
+            value: name,
+        }));
+    }
+
     fn parse_exit(&mut self) -> Option<Statement> {
         let _ = self.consume(Token::Exit)?;
 
-        // TODO: Complete with `For`, `While`, etc.
+        // NOTE: What's exited must be in the same line.
+        if self.is_line_end() {
+            return None;
+        }
+
         let possible_blocks = [
-            Token::Sub, Token::Function,
+            Token::Sub, Token::Function, Token::Property, Token::For, Token::Do,
         ];
 
-        // NOTE: See `parse_variable`.
-        let block = std::iter::IntoIterator::into_iter(possible_blocks).find_map(|t| self.consume(t))?;
+        // NOTE: See `parse_variable`. Labelled loops are exited by their
+        // label.
+        //
+        // TODO: Remove `vec!`.
+        let block = match std::iter::IntoIterator::into_iter(possible_blocks).find_map(|t| self.consume(t)) {
+            Some(block) => block,
+            None => self.consume(Token::Identifier(vec!()))?,
+        };
 
         return Some(Statement::Exit(ExitStatement {
             block: block,
         }));
     }
 
-    fn parse_return(&mut self) -> Option<Statement> {
-        let _ = self.consume(Token::Return)?;
+    // NOTE: `Continue` isn't a VB6 keyword, so it's still allowed as an
+    // identifier.
+    fn parse_continue(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"continue")?;
+
+        // NOTE: Otherwise, it's a call to a procedure named `Continue` (see
+        // `Analyzer::analyze_procedure_call`), since what's continued must be
+        // in the same line.
+        if self.is_line_end() {
+            return None;
+        }
 
+        let possible_blocks = [Token::For, Token::Do, Token::While];
+
+        // NOTE: See `parse_exit`.
+        //
         // TODO: Remove `vec!`.
-        let possible_values = [
-            Token::Identifier(vec!()), Token::Number(vec!()), Token::String(vec!())
-        ];
+        let block = match std::iter::IntoIterator::into_iter(possible_blocks).find_map(|t| self.consume(t)) {
+            Some(block) => block,
+            None => self.consume(Token::Identifier(vec!()))?,
+        };
 
-        // NOTE: See `parse_variable`.
-        let value = std::iter::IntoIterator::into_iter(possible_values).find_map(|t| self.consume(t));
+        return Some(Statement::Continue(ContinueStatement {
+            block: block,
+        }));
+    }
 
-        return Some(Statement::Return(ReturnStatement {
-            value: value,
+    // NOTE: See `parse_continue`.
+    fn parse_defer(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"defer")?;
+
+        // NOTE: See `parse_continue`.
+        if self.is_line_end() {
+            return None;
+        }
+
+        let statement = self.parse_callable_statement()?;
+
+        return Some(Statement::Defer(DeferStatement {
+            body: vec!(statement),
         }));
     }
 
-    fn parse_attribute(&mut self) -> Option<Statement> {
-        let _ = self.consume(Token::Attribute)?;
+    fn parse_label(&mut self) -> Option<Statement> {
+        // TODO: Remove `vec!`.
         let name = self.consume(Token::Identifier(vec!()))?;
-        let _ = self.consume(Token::Assignment)?;
+        let _ = self.consume(Token::Colon)?;
 
-        // TODO: Use `parse_expression`?
+        return Some(Statement::Label(LabelStatement {
+            name: name,
+        }));
+    }
+
+    fn parse_labelled_loop(&mut self) -> Option<Statement> {
         // TODO: Remove `vec!`.
-        let possible_values = [
-            Token::Identifier(vec!()), Token::Number(vec!()), Token::String(vec!())
+        let label = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::Colon)?;
+
+        let parsers = [
+            Parser::parse_for, Parser::parse_for_each, Parser::parse_for_range, Parser::parse_do, Parser::parse_while,
         ];
 
-        // NOTE: See `parse_variable`.
-        let value = std::iter::IntoIterator::into_iter(possible_values).find_map(|t| self.consume(t))?;
+        // NOTE: See `parse_callable_statement`.
+        for parser in &parsers {
+            let position_before_parsing = self.tokens_position;
+
+            if let Some(statement) = parser(self) {
+                return Some(Statement::LabelledLoop(LabelledLoopStatement {
+                    label: label,
+                    body: vec!(statement),
+                }));
+            } else {
+                self.tokens_position = position_before_parsing;
+            }
+        }
 
-        return Some(Statement::Attribute(AttributeStatement {
-            name: name,
-            value: value,
+        return None;
+    }
+
+    fn parse_goto(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::GoTo)?;
+
+        // TODO: Remove `vec!`.
+        let label = self.consume(Token::Identifier(vec!()))?;
+
+        return Some(Statement::GoTo(GoToStatement {
+            label: label,
         }));
     }
 
-    fn parse_option(&mut self) -> Option<Statement> {
-        let _ = self.consume(Token::Option)?;
+    fn parse_procedure_call(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Call);
 
-        // TODO: Use `parse_expression`?
-        let possible_configurations = [
-            Token::Explicit, Token::Base, Token::Compare, Token::Private,
-        ];
+        let procedure = self.parse_target()?;
+        let mut arguments = vec!();
 
-        // NOTE: See `parse_variable`.
-        let configuration = std::iter::IntoIterator::into_iter(possible_configurations).find_map(|t| self.consume(t))?;
+        // NOTE: See `parse_binary`.
+        if !self.is_line_end() {
+            while let Some(argument) = self.parse_expression() {
+                arguments.push(argument);
 
-        let value = match configuration {
-            Token::Explicit => None,
-            Token::Base => Some(self.consume(Token::Number(vec!()))?),
-            Token::Compare => Some(self.consume(Token::Identifier(vec!()))?),
-            Token::Private => Some(self.consume(Token::Module)?),
+                if self.consume(Token::Comma).is_none() { break; }
+            }
+        }
 
-            _ => unreachable!(),
-        };
+        // NOTE: Arguments between parentheses are parsed as a call expression
+        // (i.e. `Call Name(a, b)`), so they're taken out of it.
+        if let (Statement::Call(call), true) = (&procedure, arguments.is_empty()) {
+            return Some(Statement::ProcedureCall(ProcedureCallStatement {
+                procedure: call.callee.clone(),
+                arguments: call.arguments.clone(),
+            }));
+        }
 
-        return Some(Statement::Option(OptionStatement {
-            configuration: configuration,
-            value: value,
+        return Some(Statement::ProcedureCall(ProcedureCallStatement {
+            procedure: Box::new(procedure),
+            arguments: arguments,
         }));
     }
-}
 
-pub fn parse(tokens: &Vec<Token>) -> Vec<Statement> {
-    let mut parser = Parser::new(tokens);
+    // NOTE: `Try`, `Catch` and `Finally` aren't keywords, see
+    // `consume_contextual`.
+    fn parse_try(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"try")?;
 
-    return parser.parse();
+        // NOTE: Otherwise, it's a call to a procedure named `Try`.
+        if !self.is_line_end() {
+            return None;
+        }
+
+        let body = self.parse_try_body();
+
+        let catch = match self.consume_contextual(b"catch") {
+            Some(_) => {
+                // NOTE: A name in the next line would be a statement.
+                let name = match self.is_line_end() {
+                    true => None,
+
+                    // TODO: Remove `vec!`.
+                    false => Some(self.consume(Token::Identifier(vec!()))?),
+                };
+
+                Some(CatchClause {
+                    name: name,
+                    body: self.parse_try_body(),
+                })
+            },
+
+            None => None,
+        };
+
+        let finally = match self.consume_contextual(b"finally") {
+            Some(_) => Some(self.parse_callable_body()),
+            None => None,
+        };
+
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume_contextual(b"try")?;
+
+        return Some(Statement::Try(TryStatement {
+            body: body,
+            catch: catch,
+            finally: finally,
+        }));
+    }
+
+    // Parses the statements of a `Try` (or its `Catch`) up to its next clause.
+    //
+    // NOTE: A line starting with `Catch` or `Finally` would otherwise be a
+    // call to a procedure (see `parse_try`).
+    fn parse_try_body(&mut self) -> Vec<Statement> {
+        let mut statements = vec!();
+
+        while self.tokens_position < self.tokens.len() {
+            let position = self.tokens_position;
+            let is_clause = self.consume_contextual(b"catch").is_some() || self.consume_contextual(b"finally").is_some();
+
+            self.tokens_position = position;
+
+            if is_clause {
+                break;
+            }
+
+            match self.parse_callable_statement() {
+                Some(statement) => statements.push(statement),
+
+                // NOTE: See `parse_callable_body`.
+                None => break,
+            }
+        }
+
+        return statements;
+    }
+
+    // NOTE: `Using` isn't a keyword, see `consume_contextual`.
+    fn parse_using(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"using")?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::As)?;
+
+        let (kind, value) = match self.consume(Token::New) {
+            Some(_) => {
+                let kind = self.consume_qualified_identifier()?;
+
+                (kind.clone(), Statement::New(NewStatement { kind: kind }))
+            },
+
+            None => {
+                let kind = self.consume_qualified_identifier()?;
+                let _ = self.consume(Token::Assignment)?;
+
+                (kind, self.parse_expression()?)
+            },
+        };
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume_contextual(b"using")?;
+
+        return Some(Statement::Using(UsingStatement {
+            name: name,
+            kind: kind,
+            value: Box::new(value),
+            body: body,
+        }));
+    }
+
+    // NOTE: `Error` isn't a keyword, see `consume_contextual`.
+    fn parse_on_error(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::On)?;
+        let _ = self.consume_contextual(b"error")?;
+
+        let label = match self.consume(Token::Resume) {
+            Some(_) => {
+                let _ = self.consume(Token::Next)?;
+
+                None
+            },
+
+            None => {
+                let _ = self.consume(Token::GoTo)?;
+
+                // TODO: Remove `vec!`.
+                let possible_labels = [Token::Identifier(vec!()), Token::Number(vec!())];
+
+                // NOTE: See `parse_variable`.
+                Some(std::iter::IntoIterator::into_iter(possible_labels).find_map(|t| self.consume(t))?)
+            },
+        };
+
+        return Some(Statement::OnError(OnErrorStatement {
+            label: label,
+        }));
+    }
+
+    fn parse_resume(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Resume)?;
+
+        // NOTE: A label in the next line would be another statement.
+        let target = match self.is_line_end() {
+            true => None,
+
+            false => {
+                // TODO: Remove `vec!`.
+                let possible_targets = [Token::Next, Token::Identifier(vec!()), Token::Number(vec!())];
+
+                // NOTE: See `parse_variable`.
+                Some(std::iter::IntoIterator::into_iter(possible_targets).find_map(|t| self.consume(t))?)
+            },
+        };
+
+        return Some(Statement::Resume(ResumeStatement {
+            target: target,
+        }));
+    }
+
+    fn parse_if(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::If)?;
+
+        let condition = self.parse_expression()?;
+        let _ = self.consume(Token::Then)?;
+
+        // NOTE: A statement in the same line as `Then` means it's a single
+        // line `If` (i.e. `If done Then Exit Sub Else count = count + 1`).
+        if !self.is_line_end() {
+            return self.parse_single_line_if(condition);
+        }
+
+        let mut branches = vec!(Statement::IfBranch(IfBranchStatement {
+            condition: Some(Box::new(condition)),
+            body: self.parse_callable_body(),
+        }));
+
+        // NOTE: See `parse_directive_if`.
+        loop {
+            let possible_keywords = [Token::ElseIf, Token::Else];
+
+            // NOTE: See `parse_variable`.
+            let keyword = std::iter::IntoIterator::into_iter(possible_keywords).find_map(|t| self.consume(t));
+
+            let condition = match keyword {
+                Some(Token::ElseIf) => {
+                    let condition = self.parse_expression()?;
+                    let _ = self.consume(Token::Then)?;
+
+                    Some(Box::new(condition))
+                },
+
+                Some(_) => None,
+
+                // This is the `End If`.
+                None => break,
+            };
+
+            let is_last_branch = condition.is_none();
+
+            branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: condition,
+                body: self.parse_callable_body(),
+            }));
+
+            if is_last_branch { break; }
+        }
+
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume(Token::If)?;
+
+        return Some(Statement::If(IfStatement {
+            branches: branches,
+        }));
+    }
+
+    fn parse_single_line_if(&mut self, condition: Statement) -> Option<Statement> {
+        let mut branches = vec!(Statement::IfBranch(IfBranchStatement {
+            condition: Some(Box::new(condition)),
+            body: vec!(self.parse_callable_statement()?),
+        }));
+
+        // NOTE: An `Else` in the next line would belong to another statement.
+        if !self.is_line_end() {
+            let _ = self.consume(Token::Else)?;
+
+            branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: None,
+                body: vec!(self.parse_callable_statement()?),
+            }));
+        }
+
+        return Some(Statement::If(IfStatement {
+            branches: branches,
+        }));
+    }
+
+    // NOTE: `Match` isn't a keyword, see `consume_contextual`.
+    fn parse_match(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"match")?;
+
+        let value = self.parse_expression()?;
+        let arms = self.parse_match_arms(|parser| Some(parser.parse_callable_body()))?;
+
+        return Some(Statement::Match(MatchStatement {
+            value: Box::new(value),
+            arms: arms,
+        }));
+    }
+
+    // NOTE: See `MatchStatement`.
+    fn parse_match_expression(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"match")?;
+
+        let value = self.parse_expression()?;
+        let arms = self.parse_match_arms(|parser| {
+            let _ = parser.consume(Token::Then)?;
+
+            Some(vec!(parser.parse_expression()?))
+        })?;
+
+        return Some(Statement::Match(MatchStatement {
+            value: Box::new(value),
+            arms: arms,
+        }));
+    }
+
+    fn parse_match_arms(&mut self, parse_body: fn(&mut Self) -> Option<Vec<Statement>>) -> Option<Vec<MatchArm>> {
+        // NOTE: The arms start in the next line.
+        if !self.is_line_end() {
+            return None;
+        }
+
+        let mut arms = vec!();
+
+        while self.consume(Token::Case).is_some() {
+            let patterns = match self.consume(Token::Else) {
+                Some(_) => None,
+
+                None => {
+                    let mut patterns = vec!(self.parse_pattern()?);
+
+                    while self.consume(Token::Comma).is_some() {
+                        patterns.push(self.parse_pattern()?);
+                    }
+
+                    Some(patterns)
+                },
+            };
+
+            // NOTE: `When` isn't a keyword, and it can't start the next line
+            // since it would be a statement.
+            let guard = match patterns.is_some() && !self.is_line_end() && self.consume_contextual(b"when").is_some() {
+                true => Some(Box::new(self.parse_expression()?)),
+                false => None,
+            };
+
+            let is_last_arm = patterns.is_none();
+
+            arms.push(MatchArm {
+                patterns: patterns,
+                guard: guard,
+                body: parse_body(self)?,
+            });
+
+            if is_last_arm { break; }
+        }
+
+        if arms.is_empty() {
+            return None;
+        }
+
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume_contextual(b"match")?;
+
+        return Some(arms);
+    }
+
+    fn parse_pattern(&mut self) -> Option<Pattern> {
+        if self.consume(Token::Nothing).is_some() {
+            return Some(Pattern::Nothing);
+        }
+
+        if self.consume(Token::TypeOf).is_some() {
+            let kind = self.consume_qualified_identifier()?;
+
+            let name = match self.consume(Token::As) {
+                // TODO: Remove `vec!`.
+                Some(_) => Some(self.consume(Token::Identifier(vec!()))?),
+                None => None,
+            };
+
+            return Some(Pattern::TypeOf(kind, name));
+        }
+
+        let value = self.parse_expression()?;
+
+        return match self.consume(Token::To) {
+            Some(_) => Some(Pattern::Range(value, self.parse_expression()?)),
+            None => Some(Pattern::Value(value)),
+        };
+    }
+
+    fn parse_for(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::For)?;
+
+        // TODO: Remove `vec!`.
+        let counter = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::Assignment)?;
+        let start = self.parse_expression()?;
+        let _ = self.consume(Token::To)?;
+        let end = self.parse_expression()?;
+
+        let step = match self.consume(Token::Step) {
+            Some(_) => Some(Box::new(self.parse_expression()?)),
+            None => None,
+        };
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::Next)?;
+
+        // NOTE: The counter may be repeated after `Next`, but it's optional.
+        if !self.is_line_end() {
+            // TODO: Remove `vec!`.
+            let _ = self.consume(Token::Identifier(vec!()));
+        }
+
+        return Some(Statement::For(ForStatement {
+            counter: counter,
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step,
+            body: body,
+        }));
+    }
+
+    fn parse_for_each(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::For)?;
+        let _ = self.consume_contextual(b"each")?;
+
+        // TODO: Remove `vec!`.
+        let element = self.consume(Token::Identifier(vec!()))?;
+
+        let kind = match self.consume(Token::As) {
+            Some(_) => Some(self.consume_qualified_identifier()?),
+            None => None,
+        };
+
+        let _ = self.consume_contextual(b"in")?;
+        let collection = self.parse_expression()?;
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::Next)?;
+
+        // NOTE: See `parse_for`.
+        if !self.is_line_end() {
+            // TODO: Remove `vec!`.
+            let _ = self.consume(Token::Identifier(vec!()));
+        }
+
+        return Some(Statement::ForEach(ForEachStatement {
+            element: element,
+            kind: kind,
+            collection: Box::new(collection),
+            body: body,
+        }));
+    }
+
+    fn parse_for_range(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::For)?;
+
+        // TODO: Remove `vec!`.
+        let counter = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume_contextual(b"in")?;
+        let start = self.parse_expression()?;
+
+        let operator = match self.consume(Token::Range) {
+            Some(token) => token,
+            None => self.consume(Token::ExclusiveRange)?,
+        };
+
+        let end = self.parse_expression()?;
+
+        let step = match self.consume(Token::Step) {
+            Some(_) => Some(Box::new(self.parse_expression()?)),
+            None => None,
+        };
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::Next)?;
+
+        // NOTE: See `parse_for`.
+        if !self.is_line_end() {
+            // TODO: Remove `vec!`.
+            let _ = self.consume(Token::Identifier(vec!()));
+        }
+
+        return Some(Statement::ForRange(ForRangeStatement {
+            counter: counter,
+            start: Box::new(start),
+            operator: operator,
+            end: Box::new(end),
+            step: step,
+            body: body,
+        }));
+    }
+
+    fn parse_do(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Do)?;
+
+        // NOTE: A `While` in the next line would be a loop inside this one.
+        let header = match self.is_line_end() {
+            true => None,
+            false => Some(self.parse_loop_condition()?),
+        };
+
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::Loop)?;
+
+        // NOTE: See above.
+        let footer = match self.is_line_end() {
+            true => None,
+            false => Some(self.parse_loop_condition()?),
+        };
+
+        return Some(Statement::Do(DoStatement {
+            header: header,
+            body: body,
+            footer: footer,
+        }));
+    }
+
+    fn parse_loop_condition(&mut self) -> Option<LoopCondition> {
+        let possible_kinds = [Token::While, Token::Until];
+
+        // NOTE: See `parse_variable`.
+        let kind = std::iter::IntoIterator::into_iter(possible_kinds).find_map(|t| self.consume(t))?;
+        let value = self.parse_expression()?;
+
+        return Some(LoopCondition {
+            kind: kind,
+            value: Box::new(value),
+        });
+    }
+
+    fn parse_while(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::While)?;
+
+        let condition = self.parse_expression()?;
+        let body = self.parse_callable_body();
+
+        let _ = self.consume(Token::Wend)?;
+
+        return Some(Statement::While(WhileStatement {
+            condition: Box::new(condition),
+            body: body,
+        }));
+    }
+
+    fn parse_return(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Return)?;
+
+        // NOTE: Empty returns are allowed here (even inside functions), it's
+        // up to the analyzer to report them.
+        let value = match self.is_line_end() {
+            true => None,
+            false => Some(Box::new(self.parse_expression()?)),
+        };
+
+        return Some(Statement::Return(ReturnStatement {
+            value: value,
+        }));
+    }
+
+    fn parse_attribute(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Attribute)?;
+
+        // NOTE: Nabe's attributes may be prefixed with a qualified data type
+        // (i.e. `Attribute ADODB.Connection.Nabe_Dispose = "Close"`).
+        let qualified_name = self.consume_qualified_identifier()?.get_lexeme();
+
+        let (procedure, name) = match qualified_name.iter().rposition(|character| *character == b'.') {
+            Some(dot) => (Some(Token::Identifier(qualified_name[..dot].to_vec())), Token::Identifier(qualified_name[dot + 1..].to_vec())),
+            None => (None, Token::Identifier(qualified_name)),
+        };
+
+        let _ = self.consume(Token::Assignment)?;
+
+        // TODO: Use `parse_expression`?
+        // TODO: Remove `vec!`.
+        let possible_values = [
+            Token::Identifier(vec!()), Token::String(vec!())
+        ];
+
+        // NOTE: See `parse_variable`.
+        let value = match std::iter::IntoIterator::into_iter(possible_values).find_map(|t| self.consume(t)) {
+            Some(value) => value,
+            None => self.consume_signed_number()?,
+        };
+
+        return Some(Statement::Attribute(AttributeStatement {
+            procedure: procedure,
+            name: name,
+            value: value,
+        }));
+    }
+
+    fn parse_option(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Option)?;
+
+        // TODO: Use `parse_expression`?
+        let possible_configurations = [
+            Token::Explicit, Token::Base, Token::Compare, Token::Private,
+        ];
+
+        // NOTE: See `parse_variable`.
+        let configuration = std::iter::IntoIterator::into_iter(possible_configurations).find_map(|t| self.consume(t))?;
+
+        let value = match configuration {
+            Token::Explicit => None,
+            Token::Base => Some(self.consume(Token::Number(vec!()))?),
+            Token::Compare => Some(self.consume(Token::Identifier(vec!()))?),
+            Token::Private => Some(self.consume(Token::Module)?),
+
+            _ => unreachable!(),
+        };
+
+        return Some(Statement::Option(OptionStatement {
+            configuration: configuration,
+            value: value,
+        }));
+    }
+
+    fn parse_version(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"version")?;
+
+        // TODO: Remove `vec!`.
+        let number = self.consume(Token::Number(vec!()))?;
+        let kind = self.consume_contextual(b"class");
+
+        return Some(Statement::Version(VersionStatement {
+            number: number,
+            kind: kind,
+        }));
+    }
+
+    fn parse_object(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"object")?;
+        let _ = self.consume(Token::Assignment)?;
+
+        let value = self.consume_line();
+
+        if value.is_empty() {
+            return None;
+        }
+
+        return Some(Statement::Object(ObjectStatement {
+            value: value,
+        }));
+    }
+
+    fn parse_begin(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"begin")?;
+
+        let mut kind = None;
+        let mut name = None;
+
+        // NOTE: Class modules' headers have neither a kind nor a name, so
+        // their attributes start right away in the next line.
+        if !self.is_line_end() {
+            kind = Some(self.consume_qualified_identifier()?);
+
+            // TODO: Remove `vec!`.
+            name = Some(self.consume(Token::Identifier(vec!()))?);
+        }
+
+        let attributes = self.parse_begin_body();
+
+        let _ = self.consume(Token::End)?;
+
+        return Some(Statement::Begin(BeginStatement {
+            kind: kind,
+            name: name,
+            attributes: attributes,
+        }));
+    }
+
+    fn parse_begin_property(&mut self) -> Option<Statement> {
+        let _ = self.consume_contextual(b"beginproperty")?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+
+        let index = match self.consume(Token::LeftParentheses) {
+            Some(_) => {
+                let index = self.consume(Token::Number(vec!()))?;
+                let _ = self.consume(Token::RightParentheses)?;
+
+                Some(index)
+            },
+
+            None => None,
+        };
+
+        // NOTE: The class identifier is written between braces and is kept as
+        // a single identifier since it's never looked into.
+        let class = match self.is_line_end() {
+            true => None,
+            false => Some(Token::Identifier(
+                self.consume_line().iter().flat_map(|t| t.get_lexeme()).collect()
+            )),
+        };
+
+        let attributes = self.parse_begin_body();
+
+        let _ = self.consume_contextual(b"endproperty")?;
+
+        return Some(Statement::BeginProperty(BeginPropertyStatement {
+            name: name,
+            index: index,
+            class: class,
+            attributes: attributes,
+        }));
+    }
+
+    // Used for both `Begin` and `BeginProperty` blocks.
+    fn parse_begin_body(&mut self) -> Vec<Statement> {
+        let parsers = [
+            Parser::parse_begin, Parser::parse_begin_property, Parser::parse_begin_attribute,
+        ];
+
+        let mut statements = vec!();
+
+        'parse_next_statement: while self.tokens_position < self.tokens.len() {
+            // Try each one of the specialized parsers to see if we can
+            // produce an statement.
+            for parser in &parsers {
+                let position_before_parsing = self.tokens_position;
+
+                if let Some(statement) = parser(self) {
+                    statements.push(statement);
+                    continue 'parse_next_statement;
+                } else {
+                    self.tokens_position = position_before_parsing;
+                }
+            }
+
+            // NOTE: Only `End` (or `EndProperty`) ends the block, any other
+            // line none of the previous parsers could convert is kept so it's
+            // reported by the analyzer, instead of silently dropping the rest
+            // of the file.
+            let position_before_parsing = self.tokens_position;
+            let is_end = self.consume(Token::End).is_some() || self.consume_contextual(b"endproperty").is_some();
+
+            self.tokens_position = self.skip_new_lines();
+
+            if is_end || self.tokens_position >= self.tokens.len() {
+                self.tokens_position = position_before_parsing;
+                break;
+            }
+
+            statements.push(Statement::Unparsed(UnparsedStatement {
+                tokens: self.consume_line(),
+            }));
+        }
+
+        return statements;
+    }
+
+    fn parse_begin_attribute(&mut self) -> Option<Statement> {
+        self.tokens_position = self.skip_new_lines();
+
+        // NOTE: Names aren't always identifiers, since some of them index (or
+        // are members of) another property (i.e. `TabCaption(0)` or
+        // `Tab(0).ControlCount`), so everything up to the `=` is kept as a
+        // single identifier.
+        let mut name = vec!();
+
+        while !self.is_line_end() && self.tokens[self.tokens_position] != Token::Assignment {
+            name.append(&mut self.tokens[self.tokens_position].get_lexeme());
+            self.tokens_position += 1;
+        }
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let name = Token::Identifier(name);
+        let _ = self.consume(Token::Assignment)?;
+
+        // NOTE: Values are kept as they were written because they can be
+        // almost anything (i.e. `&H8000000F&`, `"Form1.frx":0000` or `^{F2}`)
+        // and nobody but VB6 needs to understand them.
+        let value = self.consume_line();
+
+        if value.is_empty() {
+            return None;
+        }
+
+        return Some(Statement::BeginAttribute(BeginAttributeStatement {
+            name: name,
+            value: value,
+        }));
+    }
+
+    fn parse_event(&mut self) -> Option<Statement> {
+        let possible_scopes = [Token::Public];
+
+        // NOTE: See `parse_variable`.
+        let scope = std::iter::IntoIterator::into_iter(possible_scopes).find_map(|t| self.consume(t));
+
+        let _ = self.consume(Token::Event)?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::LeftParentheses)?;
+
+        let mut arguments = vec!();
+
+        while let Some(argument) = self.parse_callable_argument() {
+            arguments.push(argument);
+
+            if self.consume(Token::Comma).is_none() { break; }
+        }
+
+        let _ = self.consume(Token::RightParentheses)?;
+
+        return Some(Statement::Event(EventStatement {
+            scope: scope,
+            name: name,
+            arguments: arguments,
+        }));
+    }
+
+    fn parse_raise_event(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::RaiseEvent)?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+
+        let mut arguments = vec!();
+
+        // NOTE: Parentheses are optional when the event has no arguments.
+        if self.consume(Token::LeftParentheses).is_some() {
+            while let Some(argument) = self.parse_expression() {
+                arguments.push(argument);
+
+                if self.consume(Token::Comma).is_none() { break; }
+            }
+
+            let _ = self.consume(Token::RightParentheses)?;
+        }
+
+        return Some(Statement::RaiseEvent(RaiseEventStatement {
+            name: name,
+            arguments: arguments,
+        }));
+    }
+
+    fn parse_implements(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Implements)?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+
+        return Some(Statement::Implements(ImplementsStatement {
+            name: name,
+        }));
+    }
+
+    fn parse_module_directive_if(&mut self) -> Option<Statement> {
+        return self.parse_directive_if(Parser::parse_module_body);
+    }
+
+    fn parse_callable_directive_if(&mut self) -> Option<Statement> {
+        return self.parse_directive_if(Parser::parse_callable_body);
+    }
+
+    // NOTE: The branches contain the same statements that would be found
+    // where the `#If` is, so the parser for them is received as an argument.
+    fn parse_directive_if(&mut self, parse_body: fn(&mut Self) -> Vec<Statement>) -> Option<Statement> {
+        let _ = self.consume(Token::Hash)?;
+        let _ = self.consume(Token::If)?;
+
+        let condition = self.parse_expression()?;
+        let _ = self.consume(Token::Then)?;
+
+        let mut branches = vec!(Statement::DirectiveBranch(DirectiveBranchStatement {
+            condition: Some(Box::new(condition)),
+            body: parse_body(self),
+        }));
+
+        loop {
+            let position_before_parsing = self.tokens_position;
+
+            let _ = self.consume(Token::Hash)?;
+
+            let possible_keywords = [Token::ElseIf, Token::Else];
+
+            // NOTE: See `parse_variable`.
+            let keyword = std::iter::IntoIterator::into_iter(possible_keywords).find_map(|t| self.consume(t));
+
+            let condition = match keyword {
+                Some(Token::ElseIf) => {
+                    let condition = self.parse_expression()?;
+                    let _ = self.consume(Token::Then)?;
+
+                    Some(Box::new(condition))
+                },
+
+                Some(_) => None,
+
+                // This is the `#End If`.
+                None => {
+                    self.tokens_position = position_before_parsing;
+                    break;
+                },
+            };
+
+            let is_last_branch = condition.is_none();
+
+            branches.push(Statement::DirectiveBranch(DirectiveBranchStatement {
+                condition: condition,
+                body: parse_body(self),
+            }));
+
+            if is_last_branch { break; }
+        }
+
+        let _ = self.consume(Token::Hash)?;
+        let _ = self.consume(Token::End)?;
+        let _ = self.consume(Token::If)?;
+
+        return Some(Statement::DirectiveIf(DirectiveIfStatement {
+            branches: branches,
+        }));
+    }
+
+    fn parse_directive_const(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::Hash)?;
+        let _ = self.consume(Token::Const)?;
+
+        // TODO: Remove `vec!`.
+        let name = self.consume(Token::Identifier(vec!()))?;
+        let _ = self.consume(Token::Assignment)?;
+        let value = self.parse_expression()?;
+
+        return Some(Statement::DirectiveConst(DirectiveConstStatement {
+            name: name,
+            value: Box::new(value),
+        }));
+    }
+
+    fn parse_expression(&mut self) -> Option<Statement> {
+        return self.parse_binary(0);
+    }
+
+    // Parses the operations whose operators bind their operands at least as
+    // tight as `precedence` (see `Token::get_precedence`).
+    fn parse_binary(&mut self, precedence: u8) -> Option<Statement> {
+        let mut left = self.parse_unary()?;
+
+        // NOTE: An operator in the next line would belong to another statement.
+        while !self.is_line_end() {
+            let operator = match &self.tokens[self.tokens_position] {
+                // NOTE: `AndAlso` and `OrElse` aren't keywords, see
+                // `consume_contextual`.
+                Token::Identifier(lexeme) if lexeme.eq_ignore_ascii_case(b"andalso") => Token::AndAlso,
+                Token::Identifier(lexeme) if lexeme.eq_ignore_ascii_case(b"orelse") => Token::OrElse,
+
+                // TODO: Cloning here!
+                operator => operator.clone(),
+            };
+
+            let operator_precedence = match operator.get_precedence() {
+                Some(operator_precedence) if operator_precedence >= precedence => operator_precedence,
+                _ => break,
+            };
+
+            self.tokens_position += 1;
+
+            // NOTE: Operators are left associative, so the right operand can
+            // only contain operators which bind tighter.
+            let right = self.parse_binary(operator_precedence + 1)?;
+
+            left = Statement::Binary(BinaryStatement {
+                left: Box::new(left),
+                operator: operator,
+                right: Box::new(right),
+            });
+        }
+
+        return Some(left);
+    }
+
+    fn parse_unary(&mut self) -> Option<Statement> {
+        let possible_operators = [Token::Not, Token::Minus];
+
+        // NOTE: See `parse_variable`.
+        let operator = std::iter::IntoIterator::into_iter(possible_operators).find_map(|t| self.consume(t));
+
+        return match operator {
+            Some(operator) => {
+                // NOTE: `get_unary_precedence` can't fail for the possible operators.
+                let value = self.parse_binary(operator.get_unary_precedence().unwrap())?;
+
+                Some(Statement::Unary(UnaryStatement {
+                    operator: operator,
+                    value: Box::new(value),
+                }))
+            },
+
+            None => self.parse_primary(),
+        };
+    }
+
+    fn parse_primary(&mut self) -> Option<Statement> {
+        let parsers = [
+            // NOTE: `Match` isn't a keyword, so it goes before the values.
+            Parser::parse_match_expression,
+
+            Parser::parse_new, Parser::parse_value, Parser::parse_parenthesized,
+            Parser::parse_conditional, Parser::parse_type_of, Parser::parse_interpolated_string,
+            Parser::parse_array_literal,
+        ];
+
+        for parser in &parsers {
+            let position_before_parsing = self.tokens_position;
+
+            if let Some(statement) = parser(self) {
+                return self.parse_postfix(statement);
+            } else {
+                self.tokens_position = position_before_parsing;
+            }
+        }
+
+        return None;
+    }
+
+    // Parses the calls (or array indexes, see `CallStatement`) and member
+    // accesses which follow a value, such as `items(index).name`.
+    fn parse_postfix(&mut self, value: Statement) -> Option<Statement> {
+        let mut value = value;
+
+        // NOTE: See `parse_binary`.
+        while !self.is_line_end() {
+            if self.consume(Token::LeftParentheses).is_some() {
+                let mut arguments = vec!();
+
+                while let Some(argument) = self.parse_expression() {
+                    arguments.push(argument);
+
+                    if self.consume(Token::Comma).is_none() { break; }
+                }
+
+                let _ = self.consume(Token::RightParentheses)?;
+
+                value = Statement::Call(CallStatement {
+                    callee: Box::new(value),
+                    arguments: arguments,
+                });
+            } else if self.consume(Token::Dot).is_some() {
+                // TODO: Members named like keywords (i.e. `.Type`).
+                //
+                // TODO: Remove `vec!`.
+                let member = self.consume(Token::Identifier(vec!()))?;
+
+                value = Statement::Member(MemberStatement {
+                    object: Box::new(value),
+                    member: member,
+                });
+            } else {
+                break;
+            }
+        }
+
+        return Some(value);
+    }
+
+    // NOTE: Parentheses aren't kept, the generator puts them back wherever
+    // they are needed.
+    fn parse_parenthesized(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::LeftParentheses)?;
+        let value = self.parse_expression()?;
+        let _ = self.consume(Token::RightParentheses)?;
+
+        return Some(value);
+    }
+
+    fn parse_conditional(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::If)?;
+        let _ = self.consume(Token::LeftParentheses)?;
+
+        let condition = self.parse_expression()?;
+        let _ = self.consume(Token::Comma)?;
+        let when_true = self.parse_expression()?;
+        let _ = self.consume(Token::Comma)?;
+        let when_false = self.parse_expression()?;
+
+        let _ = self.consume(Token::RightParentheses)?;
+
+        return Some(Statement::Conditional(ConditionalStatement {
+            condition: Box::new(condition),
+            when_true: Box::new(when_true),
+            when_false: Box::new(when_false),
+        }));
+    }
+
+    fn parse_type_of(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::TypeOf)?;
+
+        let value = self.parse_primary()?;
+        let _ = self.consume(Token::Is)?;
+        let kind = self.consume_qualified_identifier()?;
+
+        return Some(Statement::TypeOf(TypeOfStatement {
+            value: Box::new(value),
+            kind: kind,
+        }));
+    }
+
+    // Splits an interpolated string into its literal parts and embedded
+    // expressions, which are lexed and parsed on their own.
+    fn parse_interpolated_string(&mut self) -> Option<Statement> {
+        // TODO: Remove `vec!`.
+        let lexeme = self.consume(Token::InterpolatedString(vec!()))?.get_lexeme();
+
+        // NOTE: `get_lexeme` adds back the dollar and the quotes.
+        let characters = &lexeme[2..lexeme.len() - 1];
+
+        let mut parts = vec!();
+        let mut literal = vec!();
+        let mut position = 0;
+
+        let string = |literal: &Vec<u8>| Statement::Value(ValueStatement {
+            value: Token::Identifier([b"\"", literal.as_slice(), b"\""].concat()),
+        });
+
+        while position < characters.len() {
+            let character = characters[position];
+            let next_character = *characters.get(position + 1).unwrap_or(&b'\0');
+
+            // NOTE: Doubled quotes are kept, since VB6's strings use them too.
+            if (character == b'{' || character == b'}') && next_character == character {
+                literal.push(character);
+                position += 2;
+                continue;
+            }
+
+            if character != b'{' {
+                literal.push(character);
+                position += 1;
+                continue;
+            }
+
+            if !literal.is_empty() {
+                parts.push(string(&literal));
+                literal.clear();
+            }
+
+            let (value, format, length) = Self::parse_interpolation(&characters[position + 1..])?;
+
+            parts.push(Statement::Interpolation(InterpolationStatement {
+                value: Box::new(value),
+                format: format,
+            }));
+
+            // NOTE: Skip the braces too.
+            position += length + 2;
+        }
+
+        if !literal.is_empty() {
+            parts.push(string(&literal));
+        }
+
+        return Some(Statement::InterpolatedString(InterpolatedStringStatement {
+            parts: parts,
+        }));
+    }
+
+    // Parses an embedded expression up to its closing brace, returning it
+    // along with its format and its length.
+    fn parse_interpolation(characters: &[u8]) -> Option<(Statement, Option<Token>, usize)> {
+        let mut length = 0;
+        let mut depth = 0;
+        let mut format_position = None;
+
+        while length < characters.len() {
+            match (characters[length], depth) {
+                (b'}', 0) => break,
+
+                (b'(', _) => depth += 1,
+                (b')', _) => depth -= 1,
+
+                // NOTE: `:=` passes arguments by name.
+                (b':', 0) if format_position.is_none() && characters.get(length + 1) != Some(&b'=') => {
+                    format_position = Some(length);
+                },
+
+                // Skip a string.
+                (b'"', _) => {
+                    length += 1;
+
+                    while length < characters.len() && characters[length] != b'"' {
+                        length += 1;
+                    }
+                },
+
+                _ => (),
+            }
+
+            length += 1;
+        }
+
+        // NOTE: The closing brace must be there.
+        if length >= characters.len() {
+            return None;
+        }
+
+        let (expression, format) = match format_position {
+            Some(format_position) => {
+                // NOTE: Quotes in the format are doubled, as VB6's strings
+                // need.
+                let format = characters[format_position + 1..length].iter()
+                    .flat_map(|character| match character {
+                        b'"' => vec!(b'"', b'"'),
+                        _ => vec!(*character),
+                    })
+                    .collect::<Vec<u8>>();
+
+                (&characters[..format_position], Some(Token::Identifier([b"\"", format.as_slice(), b"\""].concat())))
+            },
+
+            None => (&characters[..length], None),
+        };
+
+        let tokens = crate::lexer::lex(&expression.to_vec());
+        let mut parser = Parser::new(&tokens);
+        let value = parser.parse_expression()?;
+
+        // NOTE: The whole expression must be parsed.
+        if parser.skip_new_lines() < tokens.len() {
+            return None;
+        }
+
+        return Some((value, format, length));
+    }
+
+    fn parse_array_literal(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::LeftBrace)?;
+
+        let mut elements = vec!();
+
+        while let Some(element) = self.parse_expression() {
+            elements.push(element);
+
+            if self.consume(Token::Comma).is_none() { break; }
+        }
+
+        let _ = self.consume(Token::RightBrace)?;
+
+        return Some(Statement::ArrayLiteral(ArrayLiteralStatement {
+            elements: elements,
+        }));
+    }
+
+    fn parse_new(&mut self) -> Option<Statement> {
+        let _ = self.consume(Token::New)?;
+
+        // TODO: Remove `vec!`.
+        let kind = self.consume(Token::Identifier(vec!()))?;
+
+        return Some(Statement::New(NewStatement {
+            kind: kind,
+        }));
+    }
+
+    fn parse_value(&mut self) -> Option<Statement> {
+        // TODO: Remove `vec!`.
+        let possible_values = [
+            Token::Identifier(vec!()), Token::Number(vec!()), Token::String(vec!()),
+            Token::EscapedString(vec!()), Token::RawString(vec!()), Token::Nothing,
+        ];
+
+        // NOTE: See `parse_variable`.
+        let value = std::iter::IntoIterator::into_iter(possible_values).find_map(|t| self.consume(t))?;
+
+        return Some(Statement::Value(ValueStatement {
+            value: value,
+        }));
+    }
+}
+
+pub fn parse(tokens: &Vec<Token>) -> Vec<Statement> {
+    let mut parser = Parser::new(tokens);
+
+    return parser.parse();
+}
+
+#[cfg(test)]
+mod tests {
+    fn compile(source: &str) -> String {
+        let tokens = crate::lex(&source.as_bytes().to_vec());
+
+        return String::from_utf8(crate::generate(crate::transform(crate::parse(&tokens), &vec!()))).unwrap();
+    }
+
+    #[test]
+    fn parses_let_and_set_assignments_and_new() {
+        let source = "\
+Public Sub Main()
+    Dim items As Collection
+    Dim count As Long
+    Set items = New Collection
+    Let count = 0
+    Set items = Nothing
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim items as Collection
+dim count as Long
+set items = new Collection
+let count = 0
+set items = nothing
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn parses_class_modules_with_their_header_and_members() {
+        let source = "\
+VERSION 1.0 CLASS
+BEGIN
+  MultiUse = -1  'True
+END
+Attribute VB_Name = \"CCounter\"
+Implements ICounter
+Public Event Changed(ByVal value As Long)
+Private m_Value As Long
+
+Public Property Get Value() As Long
+    Value = m_Value
+End Property
+
+Public Property Let Value(ByVal newValue As Long)
+    m_Value = newValue
+    RaiseEvent Changed(m_Value)
+End Property
+
+Friend Sub Reset()
+    m_Value = 0
+End Sub
+";
+
+        let expected = "\
+VERSION 1.0 CLASS
+BEGIN
+   MultiUse = -1
+END
+Attribute VB_Name = \"CCounter\"
+implements ICounter
+public event Changed(byval value as Long)
+private m_Value as Long
+public property get Value() as Long
+Value = m_Value
+end property
+public property let Value(byval newValue as Long)
+m_Value = newValue
+raiseevent Changed(m_Value)
+end property
+friend sub Reset()
+m_Value = 0
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn keeps_the_designer_blocks_of_forms() {
+        let source = "\
+VERSION 5.00
+Begin VB.Form Form1
+   Caption         =   \"Orders\"
+   ClientHeight    =   3195
+   BeginProperty Font
+      Name            =   \"Tahoma\"
+      Size            =   8.25
+   EndProperty
+   Begin VB.CommandButton Command1
+      Caption         =   \"OK\"
+      Index           =   0
+   End
+End
+Attribute VB_Name = \"Form1\"
+Private Sub Command1_Click(Index As Integer)
+    Caption = \"Clicked\"
+End Sub
+";
+
+        let expected = "\
+VERSION 5.00
+Begin VB.Form Form1
+   Caption = \"Orders\"
+   ClientHeight = 3195
+   BeginProperty Font
+      Name = \"Tahoma\"
+      Size = 8.25
+   EndProperty
+   Begin VB.CommandButton Command1
+      Caption = \"OK\"
+      Index = 0
+   End
+End
+Attribute VB_Name = \"Form1\"
+private sub Command1_Click(Index as Integer)
+Caption = \"Clicked\"
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(code.find("Begin VB.CommandButton").unwrap() < code.find("Attribute VB_Name").unwrap());
+    }
+
+    #[test]
+    fn parses_functions_returning_qualified_data_types() {
+        let source = "\
+Public Function Find() As ADODB.Recordset
+End Function
+";
+
+        let expected = "\
+public function Find() as ADODB.Recordset
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn parses_properties_of_qualified_data_types() {
+        let source = "\
+Public Property Get Connection() As ADODB.Connection
+End Property
+";
+
+        let expected = "\
+public property get Connection() as ADODB.Connection
+end property
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn parses_typed_elements_of_qualified_data_types() {
+        let source = "\
+Public Sub Show(fields As Collection)
+    For Each field As ADODB.Field In fields
+        Debug.Print field.Name
+    Next
+End Sub
+";
+
+        let code = compile(source);
+
+        assert!(code.contains("dim field as ADODB.Field\n"));
+        assert!(code.contains("Debug.Print field.Name\n"));
+    }
+
+    #[test]
+    fn keeps_the_bytes_of_sources_in_other_code_pages() {
+        // NOTE: `é` is `\xE9` in Windows-1252, which isn't valid UTF-8.
+        let source = b"\
+Begin VB.Form Form1
+   Caption         =   \"Caf\xE9\"
+End
+Private Sub Form_Load()
+    Caption = \"Caf\xE9\"
+End Sub
+";
+
+        let expected = b"\
+Begin VB.Form Form1
+   Caption = \"Caf\xE9\"
+End
+private sub Form_Load()
+Caption = \"Caf\xE9\"
+end sub
+";
+
+        let tokens = crate::lex(&source.to_vec());
+        let code = crate::generate(crate::transform(crate::parse(&tokens), &vec!()));
+
+        assert_eq!(code, expected.to_vec());
+    }
+
+    #[test]
+    fn parses_variables_declared_as_new() {
+        let source = "\
+Private items As New Collection
+
+Public Sub Main()
+    Dim names As New Scripting.Dictionary
+    names.Add 1, 2
+End Sub
+
+Public Sub Other()
+    items.Add 3
+End Sub
+";
+
+        let expected = "\
+private items as new Collection
+public sub Main()
+dim names as new Scripting.Dictionary
+names.Add 1, 2
+end sub
+public sub Other()
+items.Add 3
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn parses_nabe_keywords_as_identifiers_elsewhere() {
+        let source = "\
+Public Sub Main()
+    Dim finally As Boolean
+    finally = try AndAlso catch
+    Catch finally
+    Try
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim finally as Boolean
+dim nabe_temporary_1 as Boolean
+if try then
+nabe_temporary_1 = catch
+else
+nabe_temporary_1 = False
+end if
+finally = nabe_temporary_1
+Catch finally
+Try
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn parses_designer_properties_with_indexes_and_members() {
+        let source = "\
+VERSION 5.00
+Begin VB.Form Form1
+   Caption = \"Form1\"
+   Begin TabDlg.SSTab SSTab1
+      TabCaption(0) = \"Tab 0\"
+      Tab(0).ControlCount = 1
+      Tab(0).Control(0) = \"Label1\"
+   End
+End
+Attribute VB_Name = \"Form1\"
+";
+
+        let expected = "\
+VERSION 5.00
+Begin VB.Form Form1
+   Caption = \"Form1\"
+   Begin TabDlg.SSTab SSTab1
+      TabCaption(0) = \"Tab 0\"
+      Tab(0).ControlCount = 1
+      Tab(0).Control(0) = \"Label1\"
+   End
+End
+Attribute VB_Name = \"Form1\"
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn keeps_the_line_after_a_continue_alone() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    For i = 1 To 2
+        Continue
+        Debug.Print i
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim i as Long
+for i = 1 to 2
+Continue
+Debug.Print i
+next
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn keeps_the_line_after_a_defer_alone() {
+        let source = "\
+Public Sub Main(conn As Object)
+    Defer
+    conn.Close
+End Sub
+";
+
+        let expected = "\
+public sub Main(conn as Object)
+Defer
+conn.Close
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn doesnt_take_the_label_of_an_exit_from_the_next_line() {
+        let source = "\
+Public Sub Main()
+    Exit
+    Cleanup
+End Sub
+";
+
+        let statements = crate::parse(&crate::lex(&source.as_bytes().to_vec()));
+
+        assert!(!format!("{:?}", statements).contains("ExitStatement"));
+    }
 }
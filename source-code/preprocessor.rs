@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+
+use crate::token::Token;
+use crate::statement::*;
+
+// NOTE: Every value is handled as a `Long`, where `True` is `-1` and `False`
+// is `0` (just like VB6 does).
+//
+// TODO: Handle strings.
+struct Preprocessor {
+    // NOTE: Names are stored in lowercase because VB6 is case insensitive.
+    constants: HashMap<String, i64>,
+}
+
+impl Preprocessor {
+    pub fn new(defines: &HashMap<String, i64>) -> Self {
+        let mut constants = HashMap::new();
+
+        // VB6's own constants.
+        constants.insert(String::from("win16"), 0);
+        constants.insert(String::from("win32"), -1);
+
+        for (name, value) in defines {
+            constants.insert(name.to_lowercase(), *value);
+        }
+
+        return Self {
+            constants: constants,
+        };
+    }
+
+    // Replaces each `#If` with the body of its branch whose condition holds,
+    // and removes the `#Const`s after evaluating them.
+    pub fn preprocess(&mut self, statements: Vec<Statement>) -> Vec<Statement> {
+        let mut preprocessed_statements = vec!();
+
+        for statement in statements {
+            match statement {
+                Statement::DirectiveIf(data) => {
+                    preprocessed_statements.append(&mut self.preprocess_directive_if(data));
+                },
+
+                Statement::DirectiveConst(data) => {
+                    let value = self.evaluate(&data.value);
+
+                    self.constants.insert(Self::normalize(&data.name), value);
+                },
+
+                Statement::Subroutine(data) => {
+                    preprocessed_statements.push(Statement::Subroutine(SubroutineStatement {
+                        scope: data.scope,
+                        name: data.name,
+                        arguments: data.arguments,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Function(data) => {
+                    preprocessed_statements.push(Statement::Function(FunctionStatement {
+                        scope: data.scope,
+                        name: data.name,
+                        arguments: data.arguments,
+                        kind: data.kind,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Property(data) => {
+                    preprocessed_statements.push(Statement::Property(PropertyStatement {
+                        scope: data.scope,
+                        accessor: data.accessor,
+                        name: data.name,
+                        arguments: data.arguments,
+                        kind: data.kind,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::If(data) => {
+                    let mut preprocessed_branches = vec!();
+
+                    for statement in data.branches {
+                        let preprocessed_branch = match statement {
+                            Statement::IfBranch(branch) => IfBranchStatement {
+                                condition: branch.condition,
+                                body: self.preprocess(branch.body),
+                            },
+
+                            // TODO: Is it correct to `panic`?
+                            //
+                            // TODO: Add a message?
+                            _ => unreachable!(),
+                        };
+
+                        preprocessed_branches.push(Statement::IfBranch(preprocessed_branch));
+                    }
+
+                    preprocessed_statements.push(Statement::If(IfStatement {
+                        branches: preprocessed_branches,
+                    }));
+                },
+
+                Statement::Match(data) => {
+                    let mut preprocessed_arms = vec!();
+
+                    for arm in data.arms {
+                        preprocessed_arms.push(MatchArm {
+                            patterns: arm.patterns,
+                            guard: arm.guard,
+                            body: self.preprocess(arm.body),
+                        });
+                    }
+
+                    preprocessed_statements.push(Statement::Match(MatchStatement {
+                        value: data.value,
+                        arms: preprocessed_arms,
+                    }));
+                },
+
+                Statement::For(data) => {
+                    preprocessed_statements.push(Statement::For(ForStatement {
+                        counter: data.counter,
+                        start: data.start,
+                        end: data.end,
+                        step: data.step,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::ForEach(data) => {
+                    preprocessed_statements.push(Statement::ForEach(ForEachStatement {
+                        element: data.element,
+                        kind: data.kind,
+                        collection: data.collection,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::ForRange(data) => {
+                    preprocessed_statements.push(Statement::ForRange(ForRangeStatement {
+                        counter: data.counter,
+                        start: data.start,
+                        operator: data.operator,
+                        end: data.end,
+                        step: data.step,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::LabelledLoop(data) => {
+                    preprocessed_statements.push(Statement::LabelledLoop(LabelledLoopStatement {
+                        label: data.label,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Defer(data) => {
+                    preprocessed_statements.push(Statement::Defer(DeferStatement {
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Using(data) => {
+                    preprocessed_statements.push(Statement::Using(UsingStatement {
+                        name: data.name,
+                        kind: data.kind,
+                        value: data.value,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Do(data) => {
+                    preprocessed_statements.push(Statement::Do(DoStatement {
+                        header: data.header,
+                        body: self.preprocess(data.body),
+                        footer: data.footer,
+                    }));
+                },
+
+                Statement::While(data) => {
+                    preprocessed_statements.push(Statement::While(WhileStatement {
+                        condition: data.condition,
+                        body: self.preprocess(data.body),
+                    }));
+                },
+
+                Statement::Try(data) => {
+                    let catch = match data.catch {
+                        Some(catch) => Some(CatchClause {
+                            name: catch.name,
+                            body: self.preprocess(catch.body),
+                        }),
+
+                        None => None,
+                    };
+
+                    let finally = match data.finally {
+                        Some(finally) => Some(self.preprocess(finally)),
+                        None => None,
+                    };
+
+                    preprocessed_statements.push(Statement::Try(TryStatement {
+                        body: self.preprocess(data.body),
+                        catch: catch,
+                        finally: finally,
+                    }));
+                },
+
+                // No preprocessing aplicable.
+                _ => preprocessed_statements.push(statement),
+            }
+        }
+
+        return preprocessed_statements;
+    }
+
+    fn preprocess_directive_if(&mut self, data: DirectiveIfStatement) -> Vec<Statement> {
+        for statement in data.branches {
+            let branch = match statement {
+                Statement::DirectiveBranch(branch) => branch,
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            };
+
+            let holds = match &branch.condition {
+                Some(condition) => self.evaluate(condition) != 0,
+                None => true,
+            };
+
+            // NOTE: Only the chosen branch is preprocessed, so the `#Const`s
+            // of the others are never evaluated.
+            if holds {
+                return self.preprocess(branch.body);
+            }
+        }
+
+        return vec!();
+    }
+
+    fn evaluate(&self, statement: &Statement) -> i64 {
+        return match statement {
+            Statement::Value(data) => self.evaluate_value(&data.value),
+            Statement::Unary(data) => self.evaluate_unary(data),
+            Statement::Binary(data) => self.evaluate_binary(data),
+
+            Statement::Conditional(data) => match self.evaluate(&data.condition) {
+                0 => self.evaluate(&data.when_false),
+                _ => self.evaluate(&data.when_true),
+            },
+
+            // NOTE: Anything else (i.e. `New`) can't be known at compile time.
+            _ => 0,
+        };
+    }
+
+    fn evaluate_value(&self, value: &Token) -> i64 {
+        let lexeme = Self::normalize(value);
+
+        return match value {
+            Token::Number(_) => parse_number(&lexeme).unwrap_or(0),
+
+            Token::Identifier(_) => match &lexeme as &str {
+                "true" => -1,
+                "false" => 0,
+
+                // NOTE: Undefined constants are `Empty`, which is the same as
+                // `0` when evaluated as a number.
+                _ => *self.constants.get(&lexeme).unwrap_or(&0),
+            },
+
+            _ => 0,
+        };
+    }
+
+    fn evaluate_unary(&self, data: &UnaryStatement) -> i64 {
+        let value = self.evaluate(&data.value);
+
+        return match data.operator {
+            Token::Not => !value,
+            Token::Minus => value.wrapping_neg(),
+
+            _ => 0,
+        };
+    }
+
+    fn evaluate_binary(&self, data: &BinaryStatement) -> i64 {
+        let left = self.evaluate(&data.left);
+        let right = self.evaluate(&data.right);
+
+        let from_bool = |value: bool| if value { -1 } else { 0 };
+
+        return match data.operator {
+            Token::Imp => !left | right,
+            Token::Eqv => !(left ^ right),
+            Token::Xor => left ^ right,
+            Token::Or => left | right,
+            Token::And => left & right,
+
+            // NOTE: Evaluating an operand has no side effects here, so these
+            // don't need to short circuit.
+            Token::OrElse => from_bool(left != 0 || right != 0),
+            Token::AndAlso => from_bool(left != 0 && right != 0),
+
+            Token::Assignment => from_bool(left == right),
+            Token::NotEqual => from_bool(left != right),
+            Token::Less => from_bool(left < right),
+            Token::LessOrEqual => from_bool(left <= right),
+            Token::Greater => from_bool(left > right),
+            Token::GreaterOrEqual => from_bool(left >= right),
+
+            Token::Plus => left.wrapping_add(right),
+            Token::Minus => left.wrapping_sub(right),
+            Token::Times => left.wrapping_mul(right),
+            Token::Mod => left.checked_rem(right).unwrap_or(0),
+            Token::IntegerDivide => left.checked_div(right).unwrap_or(0),
+            Token::Divide => (left as f64 / right as f64).round() as i64,
+            Token::Caret => (left as f64).powf(right as f64).round() as i64,
+
+            // NOTE: `&`, `Like` and `Is` work on strings and objects.
+            _ => 0,
+        };
+    }
+
+    fn normalize(token: &Token) -> String {
+        return String::from_utf8_lossy(&token.get_lexeme()).to_lowercase();
+    }
+}
+
+// Parses a (lowercase) number the way it would be written in VB6's code.
+fn parse_number(lexeme: &str) -> Option<i64> {
+    let lexeme = lexeme.trim_end_matches('&');
+
+    if let Some(digits) = lexeme.strip_prefix("&h") {
+        return i64::from_str_radix(digits, 16).ok();
+    }
+
+    if let Some(digits) = lexeme.strip_prefix("&o") {
+        return i64::from_str_radix(digits, 8).ok();
+    }
+
+    return lexeme.parse::<f64>().ok().map(|number| number.round() as i64);
+}
+
+// Parses conditional compilation arguments written the way VB6 projects do
+// (i.e. `DEBUG = 1 : TRACE = -1`), where omitted values mean `True`.
+pub fn parse_defines(text: &str) -> Option<HashMap<String, i64>> {
+    let mut defines = HashMap::new();
+
+    for definition in text.split(':').filter(|d| !d.trim().is_empty()) {
+        let (name, value) = match definition.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim().to_lowercase()),
+            None => (definition.trim(), String::from("true")),
+        };
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let value = match &value as &str {
+            "true" => -1,
+            "false" => 0,
+
+            _ => match value.strip_prefix('-') {
+                Some(digits) => -parse_number(digits)?,
+                None => parse_number(&value)?,
+            },
+        };
+
+        defines.insert(name.to_lowercase(), value);
+    }
+
+    return Some(defines);
+}
+
+pub fn preprocess(statements: Vec<Statement>, defines: &HashMap<String, i64>) -> Vec<Statement> {
+    let mut preprocessor = Preprocessor::new(defines);
+
+    return preprocessor.preprocess(statements);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compiles a module, resolving its directives with the given defines or
+    // keeping them when there are none.
+    fn compile(source: &str, defines: Option<&str>) -> String {
+        let tokens = crate::lex(&source.as_bytes().to_vec());
+        let statements = crate::parse(&tokens);
+        let statements = match defines {
+            Some(defines) => preprocess(statements, &parse_defines(defines).unwrap()),
+            None => statements,
+        };
+
+        return String::from_utf8(crate::generate(crate::transform(statements, &vec!()))).unwrap();
+    }
+
+    #[test]
+    fn resolves_directives_with_the_given_defines() {
+        let source = "\
+Public Sub Main()
+#If Win32 Then
+    target = \"windows\"
+#ElseIf DEBUG Then
+    target = \"debug\"
+#Else
+    target = \"other\"
+#End If
+#Const VERBOSE = DEBUG And LEVEL > 1
+#If VERBOSE Then
+    target = \"verbose\"
+#End If
+#If UNDEFINED Then
+    target = \"undefined\"
+#End If
+End Sub
+";
+
+        let code = compile(source, Some("DEBUG : LEVEL = 2"));
+
+        assert_eq!(code, "\
+public sub Main()
+target = \"windows\"
+target = \"verbose\"
+end sub
+");
+        assert!(!code.contains('#'));
+        assert!(!code.contains("\"undefined\""));
+
+        let code = compile(source, Some("Win32 = 0 : LEVEL = 1"));
+
+        assert_eq!(code, "\
+public sub Main()
+target = \"other\"
+end sub
+");
+        assert!(!code.contains("\"verbose\""));
+    }
+
+    #[test]
+    fn keeps_directives_without_defines() {
+        let source = "\
+Public Sub Main()
+#If Win32 Then
+    target = \"windows\"
+#ElseIf DEBUG Then
+    target = \"debug\"
+#Else
+    target = \"other\"
+#End If
+#Const VERBOSE = DEBUG And LEVEL > 1
+#If VERBOSE Then
+    target = \"verbose\"
+#End If
+#If UNDEFINED Then
+    target = \"undefined\"
+#End If
+End Sub
+";
+
+        let code = compile(source, None);
+
+        assert_eq!(code, "\
+public sub Main()
+#if Win32 then
+target = \"windows\"
+#elseif DEBUG then
+target = \"debug\"
+#else
+target = \"other\"
+#end if
+#const VERBOSE = DEBUG and LEVEL > 1
+#if VERBOSE then
+target = \"verbose\"
+#end if
+#if UNDEFINED then
+target = \"undefined\"
+#end if
+end sub
+");
+        assert_eq!(code.matches("target = ").count(), 5);
+    }
+
+    #[test]
+    fn defines_names_without_a_value_as_true() {
+        let defines = parse_defines("DEBUG : TRACE = 0 : LEVEL = &H10").unwrap();
+
+        assert_eq!(defines.get("debug"), Some(&-1));
+        assert_eq!(defines.get("trace"), Some(&0));
+        assert_eq!(defines.get("level"), Some(&16));
+    }
+
+    #[test]
+    fn rejects_definitions_without_a_name() {
+        assert_eq!(parse_defines("= 1"), None);
+    }
+}
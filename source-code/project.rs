@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+pub struct CompiledFile {
+    pub source: PathBuf,
+    pub output: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Compiles every module, class module, form, user control and property page
+// listed in a VB6 project (`.vbp`), writing them along with an updated project
+// (and copies of the other files it points to) to `output_directory`.
+//
+// NOTE: Conditional compilation directives are kept as they are unless some
+// `defines` are given, which take precedence over the project's own ones.
+pub fn compile_project(
+    path: &Path,
+    output_directory: &Path,
+    defines: Option<&HashMap<String, i64>>,
+) -> std::io::Result<Vec<CompiledFile>> {
+    let project = std::fs::read(path)?;
+    let project_directory = match path.parent() {
+        Some(directory) if directory != Path::new("") => directory,
+        _ => Path::new("."),
+    };
+
+    let mut compiled_files = vec!();
+    let mut updated_project = vec!();
+
+    std::fs::create_dir_all(output_directory)?;
+
+    // NOTE: Sources which already have VB6's extensions would be overwritten
+    // by their compiled counterparts.
+    if project_directory.canonicalize()? == output_directory.canonicalize()? {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the output directory must be different from the project's directory",
+        ));
+    }
+
+    let defines = match defines {
+        Some(defines) => {
+            let mut project_defines = project_defines(&project)?;
+
+            project_defines.extend(defines.clone());
+
+            Some(project_defines)
+        },
+
+        None => None,
+    };
+
+    // NOTE: Class modules are named by their entry, so they're known before
+    // compiling anything.
+    let classes = project.split(|character| *character == b'\n')
+        .filter_map(|line| parse_entry(&String::from_utf8_lossy(line)))
+        .filter(|entry| entry.key.eq_ignore_ascii_case("class"))
+        .filter_map(|entry| entry.name)
+        .collect::<Vec<String>>();
+
+    // NOTE: Projects are written in the system's code page (not UTF-8), so
+    // every line but the updated entries is written back byte for byte,
+    // along with its line ending.
+    for line in project.split_inclusive(|character| *character == b'\n') {
+        let (line, line_ending) = split_line_ending(line);
+
+        let updated_line = match parse_entry(&String::from_utf8_lossy(line)) {
+            Some(entry) => {
+                // NOTE: VB6 projects are written on Windows, so paths use
+                // backslashes as separators.
+                let relative_source = PathBuf::from(entry.path.replace('\\', "/"));
+                let relative_output = output_path(&relative_source, &entry.key);
+
+                let source = project_directory.join(&relative_source);
+                let output = output_directory.join(&relative_output);
+
+                match is_source_code(&entry.key) {
+                    true => compiled_files.push(compile_file(&source, &output, &entry.key, defines.as_ref(), &classes)?),
+                    false => copy_file(&source, &output)?,
+                }
+
+                entry.with_path(relative_output.to_string_lossy().replace('/', "\\")).into_bytes()
+            },
+
+            None => line.to_vec(),
+        };
+
+        updated_project.extend_from_slice(&updated_line);
+        updated_project.extend_from_slice(line_ending);
+    }
+
+    // NOTE: Files with errors aren't written, so neither is the project
+    // which would point to them.
+    let has_errors = compiled_files.iter().any(|compiled_file| compiled_file.diagnostics.iter().any(|diagnostic| {
+        diagnostic.severity == Severity::Error
+    }));
+
+    if !has_errors {
+        let project_name = path.file_name().unwrap_or(std::ffi::OsStr::new("project.vbp"));
+
+        std::fs::write(output_directory.join(project_name), updated_project)?;
+    }
+
+    return Ok(compiled_files);
+}
+
+// An entry of the project which points to a file, such as
+// `Module=Module1; Module1.bas`.
+struct Entry {
+    key: String,
+
+    // NOTE: Only modules and class modules are named by their entry.
+    name: Option<String>,
+    path: String,
+
+    // NOTE: Some paths are quoted, such as the resource file's one (i.e.
+    // `ResFile32="Project1.RES"`).
+    is_quoted: bool,
+}
+
+impl Entry {
+    fn with_path(&self, path: String) -> String {
+        let path = match self.is_quoted {
+            true => format!("\"{}\"", path),
+            false => path,
+        };
+
+        return match &self.name {
+            Some(name) => format!("{}={}; {}", self.key, name, path),
+            None => format!("{}={}", self.key, path),
+        };
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let (key, value) = line.split_once('=')?;
+
+    let (name, path) = match &key.to_lowercase() as &str {
+        "module" | "class" => {
+            let (name, path) = value.split_once(';')?;
+
+            (Some(name.trim().to_string()), path.trim())
+        },
+
+        "form" | "usercontrol" | "propertypage" | "resfile32" | "relateddoc" => (None, value.trim()),
+
+        _ => return None,
+    };
+
+    let unquoted_path = path.strip_prefix('"').and_then(|path| path.strip_suffix('"'));
+
+    return Some(Entry {
+        key: key.to_string(),
+        name: name,
+        path: unquoted_path.unwrap_or(path).to_string(),
+        is_quoted: unquoted_path.is_some(),
+    });
+}
+
+// Returns whether an entry points to source code, instead of a file which
+// is just copied (like resource files or related documents).
+fn is_source_code(key: &str) -> bool {
+    return matches!(&key.to_lowercase() as &str, "module" | "class" | "form" | "usercontrol" | "propertypage");
+}
+
+// Splits a line from its line ending (if any), which is either `\r\n` or
+// `\n`.
+fn split_line_ending(line: &[u8]) -> (&[u8], &[u8]) {
+    let length = match line {
+        [.., b'\r', b'\n'] => line.len() - 2,
+        [.., b'\n'] => line.len() - 1,
+        _ => line.len(),
+    };
+
+    return line.split_at(length);
+}
+
+// Returns the project's conditional compilation arguments, such as
+// `CondComp="DEBUG = 1 : TRACE = -1"`.
+fn project_defines(project: &[u8]) -> std::io::Result<HashMap<String, i64>> {
+    for line in String::from_utf8_lossy(project).lines() {
+        let (key, value) = match line.split_once('=') {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        if key.eq_ignore_ascii_case("condcomp") {
+            return match crate::parse_defines(value.trim_matches('"')) {
+                Some(defines) => Ok(defines),
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "the project's conditional compilation arguments are malformed",
+                )),
+            };
+        }
+    }
+
+    return Ok(HashMap::new());
+}
+
+// NOTE: Source code gets VB6's extensions, while any other file keeps its
+// own.
+fn output_path(source: &Path, key: &str) -> PathBuf {
+    let extension = match &key.to_lowercase() as &str {
+        "module" => Some("bas"),
+        "class" => Some("cls"),
+        "form" => Some("frm"),
+        "usercontrol" => Some("ctl"),
+        "propertypage" => Some("pag"),
+
+        _ => None,
+    };
+
+    // NOTE: Files outside of the project's directory are written directly
+    // into the output directory, since their relative path would escape it.
+    let is_nested = source.components().all(|c| matches!(c, Component::Normal(_)));
+
+    let output = match is_nested {
+        true => source.to_path_buf(),
+        false => PathBuf::from(source.file_name().unwrap_or_default()),
+    };
+
+    return match extension {
+        Some(extension) => output.with_extension(extension),
+        None => output,
+    };
+}
+
+// NOTE: Nothing is written when there are errors.
+fn compile_file(
+    source: &Path,
+    output: &Path,
+    key: &str,
+    defines: Option<&HashMap<String, i64>>,
+    classes: &Vec<String>,
+) -> std::io::Result<CompiledFile> {
+    let characters = std::fs::read(source)?;
+    let compilation = crate::compile(&characters, defines, classes);
+
+    if let Some(generated_code) = compilation.code {
+        if let Some(directory) = output.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        std::fs::write(output, crate::to_crlf(&generated_code))?;
+
+        // Forms, user controls and property pages keep their binary data
+        // (icons, pictures, etc.) in another file next to them, which is
+        // referenced by name from the designer blocks.
+        let binary_extension = match &key.to_lowercase() as &str {
+            "form" => Some("frx"),
+            "usercontrol" => Some("ctx"),
+            "propertypage" => Some("pgx"),
+
+            _ => None,
+        };
+
+        if let Some(binary_extension) = binary_extension {
+            let binary_data = source.with_extension(binary_extension);
+
+            if binary_data.is_file() {
+                copy_file(&binary_data, &output.with_extension(binary_extension))?;
+            }
+        }
+    }
+
+    return Ok(CompiledFile {
+        source: source.to_path_buf(),
+        output: output.to_path_buf(),
+        diagnostics: compilation.diagnostics,
+    });
+}
+
+fn copy_file(source: &Path, output: &Path) -> std::io::Result<()> {
+    if let Some(directory) = output.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    let _ = std::fs::copy(source, output)?;
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates an empty directory with the given files, for a test to compile.
+    fn create_directory(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("nabe-{}", name));
+
+        let _ = std::fs::remove_dir_all(&directory);
+
+        for (path, contents) in files {
+            let path = directory.join(path);
+
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        return directory;
+    }
+
+    #[test]
+    fn compiles_the_modules_classes_and_forms_of_a_project() {
+        let directory = create_directory("project-compile", &[
+            ("project/Project1.vbp", b"Type=Exe\r\nModule=Module1; Module1.nabe\r\nClass=CCounter; CCounter.nabe\r\nForm=Form1.frm\r\nStartup=\"Form1\"\r\n"),
+            ("project/Module1.nabe", b"Attribute VB_Name = \"Module1\"\r\nPublic Sub Main()\r\n    Dim total As Long\r\n    total = 1\r\nEnd Sub\r\n"),
+            ("project/CCounter.nabe", b"VERSION 1.0 CLASS\r\nBEGIN\r\n  MultiUse = -1\r\nEND\r\nAttribute VB_Name = \"CCounter\"\r\n"),
+            ("project/Form1.frm", b"VERSION 5.00\r\nBegin VB.Form Form1\r\n   Caption = \"Form1\"\r\nEnd\r\nAttribute VB_Name = \"Form1\"\r\n"),
+        ]);
+
+        let output = directory.join("output");
+        let compiled_files = compile_project(&directory.join("project/Project1.vbp"), &output, None).unwrap();
+        let read = |name: &str| String::from_utf8(std::fs::read(output.join(name)).unwrap()).unwrap();
+
+        assert_eq!(compiled_files.len(), 3);
+        assert!(compiled_files.iter().all(|compiled_file| compiled_file.diagnostics.is_empty()));
+        assert_eq!(read("Project1.vbp"), "\
+Type=Exe\r
+Module=Module1; Module1.bas\r
+Class=CCounter; CCounter.cls\r
+Form=Form1.frm\r
+Startup=\"Form1\"\r
+");
+        assert_eq!(read("Module1.bas"), "\
+Attribute VB_Name = \"Module1\"\r
+public sub Main()\r
+dim total as Long\r
+total = 1\r
+end sub\r
+");
+        assert_eq!(read("CCounter.cls"), "\
+VERSION 1.0 CLASS\r
+BEGIN\r
+   MultiUse = -1\r
+END\r
+Attribute VB_Name = \"CCounter\"\r
+");
+        assert!(output.join("Form1.frm").exists());
+    }
+
+    #[test]
+    fn updates_entries_and_keeps_other_lines_byte_for_byte() {
+        let directory = create_directory("project-entries", &[
+            ("project/Project1.vbp", b"Type=Exe\r\nModule=Module1; source\\Module1.nabe\r\nForm=Form1.frm\r\nResFile32=\"..\\shared\\Project1.RES\"\r\nRelatedDoc=readme.txt\r\nTitle=\"Caf\xe9\"\nName=\"Project1\""),
+            ("project/source/Module1.nabe", b"Attribute VB_Name = \"Module1\"\r\n"),
+            ("project/Form1.frm", b"VERSION 5.00\r\nBegin VB.Form Form1\r\n   Caption = \"Form1\"\r\nEnd\r\n"),
+            ("project/Form1.frx", b"binary"),
+            ("project/readme.txt", b"documentation"),
+            ("shared/Project1.RES", b"resources"),
+        ]);
+
+        let output = directory.join("output");
+        let compiled_files = compile_project(&directory.join("project/Project1.vbp"), &output, None).unwrap();
+
+        assert_eq!(compiled_files.len(), 2);
+        assert_eq!(
+            std::fs::read(output.join("Project1.vbp")).unwrap(),
+            b"Type=Exe\r\nModule=Module1; source\\Module1.bas\r\nForm=Form1.frm\r\nResFile32=\"Project1.RES\"\r\nRelatedDoc=readme.txt\r\nTitle=\"Caf\xe9\"\nName=\"Project1\"".to_vec(),
+        );
+
+        assert_eq!(std::fs::read(output.join("source/Module1.bas")).unwrap(), b"Attribute VB_Name = \"Module1\"\r\n".to_vec());
+        assert_eq!(std::fs::read(output.join("Form1.frx")).unwrap(), b"binary".to_vec());
+        assert_eq!(std::fs::read(output.join("Project1.RES")).unwrap(), b"resources".to_vec());
+        assert_eq!(std::fs::read(output.join("readme.txt")).unwrap(), b"documentation".to_vec());
+    }
+
+    #[test]
+    fn keeps_the_code_page_of_compiled_files() {
+        let directory = create_directory("project-code-page", &[
+            ("project/Project1.vbp", b"Module=Module1; Module1.nabe\r\n"),
+            ("project/Module1.nabe", b"Public Sub Main()\r\n    MsgBox \"Caf\xe9\"\r\nEnd Sub\r\n"),
+        ]);
+
+        let output = directory.join("output");
+        let _ = compile_project(&directory.join("project/Project1.vbp"), &output, None).unwrap();
+
+        assert_eq!(
+            std::fs::read(output.join("Module1.bas")).unwrap(),
+            b"public sub Main()\r\nMsgBox \"Caf\xe9\"\r\nend sub\r\n".to_vec(),
+        );
+    }
+
+    #[test]
+    fn writes_nothing_for_files_with_errors() {
+        let directory = create_directory("project-errors", &[
+            ("project/Project1.vbp", b"Form=Form1.frm\r\n"),
+            ("project/Form1.frm", b"VERSION 5.00\r\nBegin VB.Form Form1\r\n   Misplaced line\r\nEnd\r\n"),
+        ]);
+
+        let output = directory.join("output");
+        let compiled_files = compile_project(&directory.join("project/Project1.vbp"), &output, None).unwrap();
+
+        assert_eq!(compiled_files[0].diagnostics.len(), 1);
+        assert!(!output.join("Form1.frm").exists());
+        assert!(!output.join("Project1.vbp").exists());
+    }
+
+    #[test]
+    fn knows_the_classes_of_the_project() {
+        let directory = create_directory("project-classes", &[
+            ("project/Project1.vbp", b"Module=Module1; Module1.bas\r\nClass=CCustomer; CCustomer.cls\r\n"),
+            ("project/Module1.bas", b"Public Function Find(found As CCustomer) As CCustomer\r\n    Return found\r\nEnd Function\r\n"),
+            ("project/CCustomer.cls", b"VERSION 1.0 CLASS\r\nBEGIN\r\n  MultiUse = -1\r\nEND\r\n"),
+        ]);
+
+        let output = directory.join("output");
+        let _ = compile_project(&directory.join("project/Project1.vbp"), &output, None).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(output.join("Module1.bas")).unwrap(),
+            "public function Find(found as CCustomer) as CCustomer\r\nset Find = found\r\nexit function\r\nend function\r\n",
+        );
+    }
+}
@@ -0,0 +1,579 @@
+use std::collections::HashMap;
+
+use crate::token::Token;
+use crate::statement::*;
+use crate::symbols::Symbols;
+
+// Gives the variables declared inside a block (i.e. the body of an `If` or a
+// loop) a scope of their own, which VB6 lacks since its variables belong to
+// the whole procedure. Those whose name is already used by another variable
+// of the procedure are renamed:
+//
+//     If a Then                  If a Then
+//         Dim i As Long              Dim i As Long
+//         ...                        i = 0
+//     Else               =>          ...
+//         Dim i As String        Else
+//         ...                        Dim nabe_i_2 As String
+//     End If                         nabe_i_2 = vbNullString
+//                                    ...
+//                                End If
+//
+// NOTE: Variables declared without a value are reset to their default one,
+// so they're new each time the block is entered. The declarations themselves
+// are moved to the beginning of the procedure by the transformer (see
+// `Transformer::hoist_declarations`).
+pub struct Scoper<'a> {
+    symbols: &'a Symbols,
+
+    // NOTE: Names are stored in lowercase because VB6 is case insensitive.
+    //
+    // How many variables of the procedure have each name, which gives the
+    // renamed ones a unique suffix.
+    names: HashMap<String, usize>,
+
+    // The names given to the variables declared in each block around the
+    // statement being scoped, where the last one is the innermost block and
+    // the first one is the procedure itself.
+    scopes: Vec<HashMap<String, Token>>,
+}
+
+impl<'a> Scoper<'a> {
+    pub fn new(symbols: &'a Symbols) -> Self {
+        return Self {
+            symbols: symbols,
+            names: HashMap::new(),
+            scopes: vec!(),
+        };
+    }
+
+    // NOTE: The procedure's name is taken as a variable too, since functions
+    // return their value through it.
+    pub fn scope(&mut self, name: &Token, arguments: &Vec<Statement>, body: Vec<Statement>) -> Vec<Statement> {
+        self.names.clear();
+        self.scopes = vec!(HashMap::new());
+
+        self.add_procedure_name(name);
+
+        for statement in arguments {
+            if let Statement::Argument(data) = statement {
+                self.add_procedure_name(&data.name);
+            }
+        }
+
+        self.add_procedure_names(&body);
+
+        return self.scope_body(body);
+    }
+
+    // Takes the names of the variables which belong to the whole procedure,
+    // no matter whether they're declared before or after (in the source
+    // code) the blocks.
+    fn add_procedure_names(&mut self, body: &Vec<Statement>) {
+        for statement in body {
+            match statement {
+                Statement::Variable(data) => self.add_procedure_name(&data.name),
+                Statement::Constant(data) => self.add_procedure_name(&data.name),
+
+                // NOTE: See `Symbols::declare_variables`.
+                Statement::Assignment(data) if data.modifier == Some(Token::Let) => {
+                    if let Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) = &*data.left {
+                        if !self.is_visible(name) {
+                            self.add_procedure_name(name);
+                        }
+                    }
+                },
+
+                Statement::Try(data) => {
+                    if let Some(CatchClause { name: Some(name), .. }) = &data.catch {
+                        self.add_procedure_name(name);
+                    }
+
+                    for body in data.get_bodies() { self.add_procedure_names(body); }
+                },
+
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.add_procedure_names(body); }
+                },
+
+                _ => (),
+            }
+        }
+    }
+
+    fn add_procedure_name(&mut self, name: &Token) {
+        self.add_name(name);
+        self.scopes[0].insert(Self::normalize(name), name.clone());
+    }
+
+    fn add_name(&mut self, name: &Token) -> usize {
+        let count = self.names.entry(Self::normalize(name)).or_insert(0);
+
+        *count += 1;
+
+        return *count;
+    }
+
+    // Declares a variable in the innermost block, returning the name it's
+    // given.
+    fn declare(&mut self, name: &Token) -> Token {
+        let count = self.add_name(name);
+
+        // NOTE: Module's declarations would be shadowed in the whole
+        // procedure, not just inside the block.
+        let scoped_name = match count == 1 && !self.symbols.is_global(name) {
+            true => name.clone(),
+            false => Token::Identifier(format!("nabe_{}_{}", String::from_utf8_lossy(&name.get_lexeme()), count).into_bytes()),
+        };
+
+        // NOTE: There's always the procedure's scope.
+        self.scopes.last_mut().unwrap().insert(Self::normalize(name), scoped_name.clone());
+
+        return scoped_name;
+    }
+
+    fn is_visible(&self, name: &Token) -> bool {
+        let name = Self::normalize(name);
+
+        return self.scopes.iter().any(|scope| scope.contains_key(&name));
+    }
+
+    fn is_in_block(&self) -> bool {
+        return self.scopes.len() > 1;
+    }
+
+    fn scope_body(&mut self, body: Vec<Statement>) -> Vec<Statement> {
+        let mut scoped_statements = vec!();
+
+        for statement in body {
+            match statement {
+                Statement::Variable(mut data) if self.is_in_block() && data.scope != Token::Static => {
+                    // NOTE: The value is computed before the variable exists,
+                    // so it refers to the variables outside the block.
+                    self.rename_bounds(&mut data.bounds);
+
+                    if let Some(value) = &mut data.value {
+                        self.rename(value);
+                    }
+
+                    data.name = self.declare(&data.name);
+
+                    let reset = match data.value {
+                        Some(_) => None,
+                        None => self.reset(&data),
+                    };
+
+                    scoped_statements.push(Statement::Variable(data));
+                    scoped_statements.extend(reset);
+                },
+
+                // NOTE: Static variables keep their value, but not their name.
+                Statement::Variable(mut data) if self.is_in_block() => {
+                    self.rename_bounds(&mut data.bounds);
+
+                    if let Some(value) = &mut data.value {
+                        self.rename(value);
+                    }
+
+                    data.name = self.declare(&data.name);
+
+                    scoped_statements.push(Statement::Variable(data));
+                },
+
+                Statement::Assignment(mut data) if data.modifier == Some(Token::Let) && self.is_in_block() => {
+                    self.rename(&mut data.right);
+
+                    // NOTE: See `Symbols::declare_variables`.
+                    match &mut *data.left {
+                        Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) if !self.is_visible(name) && !self.symbols.is_global(name) => {
+                            *name = self.declare(name);
+                        },
+
+                        left => self.rename(left),
+                    }
+
+                    scoped_statements.push(Statement::Assignment(data));
+                },
+
+                Statement::If(mut data) => {
+                    for branch in &mut data.branches {
+                        if let Statement::IfBranch(branch) = branch {
+                            if let Some(condition) = &mut branch.condition {
+                                self.rename(condition);
+                            }
+
+                            branch.body = self.scope_block(std::mem::take(&mut branch.body));
+                        }
+                    }
+
+                    scoped_statements.push(Statement::If(data));
+                },
+
+                Statement::For(mut data) => {
+                    self.rename_name(&mut data.counter);
+                    self.rename(&mut data.start);
+                    self.rename(&mut data.end);
+
+                    if let Some(step) = &mut data.step {
+                        self.rename(step);
+                    }
+
+                    data.body = self.scope_block(data.body);
+
+                    scoped_statements.push(Statement::For(data));
+                },
+
+                // NOTE: Typed elements are declared by the loop, so they belong
+                // to its block.
+                Statement::ForEach(mut data) => {
+                    self.rename(&mut data.collection);
+
+                    self.scopes.push(HashMap::new());
+
+                    match data.kind {
+                        Some(_) => data.element = self.declare(&data.element),
+                        None => self.rename_name(&mut data.element),
+                    }
+
+                    data.body = self.scope_body(data.body);
+
+                    self.scopes.pop();
+
+                    scoped_statements.push(Statement::ForEach(data));
+                },
+
+                Statement::ForRange(mut data) => {
+                    self.rename_name(&mut data.counter);
+                    self.rename(&mut data.start);
+                    self.rename(&mut data.end);
+
+                    if let Some(step) = &mut data.step {
+                        self.rename(step);
+                    }
+
+                    data.body = self.scope_block(data.body);
+
+                    scoped_statements.push(Statement::ForRange(data));
+                },
+
+                // NOTE: The body only has the loop, which has the block.
+                Statement::LabelledLoop(mut data) => {
+                    data.body = self.scope_body(data.body);
+
+                    scoped_statements.push(Statement::LabelledLoop(data));
+                },
+
+                // NOTE: The conditions are outside of the block, even the
+                // one after `Loop`.
+                Statement::Do(mut data) => {
+                    if let Some(header) = &mut data.header {
+                        self.rename(&mut header.value);
+                    }
+
+                    if let Some(footer) = &mut data.footer {
+                        self.rename(&mut footer.value);
+                    }
+
+                    data.body = self.scope_block(data.body);
+
+                    scoped_statements.push(Statement::Do(data));
+                },
+
+                Statement::While(mut data) => {
+                    self.rename(&mut data.condition);
+
+                    data.body = self.scope_block(data.body);
+
+                    scoped_statements.push(Statement::While(data));
+                },
+
+                // NOTE: Bound values belong to the arm's block.
+                Statement::Match(mut data) => {
+                    self.rename(&mut data.value);
+
+                    for arm in &mut data.arms {
+                        self.rename_patterns(arm);
+                        self.scopes.push(HashMap::new());
+
+                        if let Some([Pattern::TypeOf(_, Some(name))]) = arm.patterns.as_deref_mut() {
+                            *name = self.declare(name);
+                        }
+
+                        if let Some(guard) = &mut arm.guard {
+                            self.rename(guard);
+                        }
+
+                        arm.body = self.scope_body(std::mem::take(&mut arm.body));
+
+                        self.scopes.pop();
+                    }
+
+                    scoped_statements.push(Statement::Match(data));
+                },
+
+                // NOTE: The object belongs to the block.
+                Statement::Using(mut data) => {
+                    self.rename(&mut data.value);
+
+                    self.scopes.push(HashMap::new());
+
+                    data.name = self.declare(&data.name);
+                    data.body = self.scope_body(data.body);
+
+                    self.scopes.pop();
+
+                    scoped_statements.push(Statement::Using(data));
+                },
+
+                Statement::Defer(mut data) => {
+                    data.body = self.scope_body(data.body);
+
+                    scoped_statements.push(Statement::Defer(data));
+                },
+
+                // NOTE: `Try`s (and `#If`s) aren't blocks of their own, so
+                // they're scoped like the statements around them.
+                Statement::Try(mut data) => {
+                    data.body = self.scope_body(data.body);
+
+                    if let Some(catch) = &mut data.catch {
+                        catch.body = self.scope_body(std::mem::take(&mut catch.body));
+                    }
+
+                    if let Some(finally) = data.finally {
+                        data.finally = Some(self.scope_body(finally));
+                    }
+
+                    scoped_statements.push(Statement::Try(data));
+                },
+
+                Statement::DirectiveIf(mut data) => {
+                    for branch in &mut data.branches {
+                        if let Statement::DirectiveBranch(branch) = branch {
+                            branch.body = self.scope_body(std::mem::take(&mut branch.body));
+                        }
+                    }
+
+                    scoped_statements.push(Statement::DirectiveIf(data));
+                },
+
+                mut statement => {
+                    self.rename(&mut statement);
+
+                    scoped_statements.push(statement);
+                },
+            }
+        }
+
+        return scoped_statements;
+    }
+
+    fn scope_block(&mut self, body: Vec<Statement>) -> Vec<Statement> {
+        self.scopes.push(HashMap::new());
+
+        let scoped_body = self.scope_body(body);
+
+        self.scopes.pop();
+
+        return scoped_body;
+    }
+
+    // Returns the assignment of the default value of a variable's data type.
+    //
+    // TODO: Reset `Type`s' variables too, which VB6 can only do by assigning
+    // them an empty one.
+    fn reset(&self, data: &VariableStatement) -> Option<Statement> {
+        let name = Statement::Value(ValueStatement {
+            value: data.name.clone(),
+        });
+
+        if data.bounds.is_some() {
+            return Some(Statement::ProcedureCall(ProcedureCallStatement {
+                procedure: Box::new(Statement::Value(ValueStatement {
+                    value: Token::Identifier(b"Erase".to_vec()),
+                })),
+                arguments: vec!(name),
+            }));
+        }
+
+        // NOTE: Variables without a data type have a value.
+        let value = self.symbols.get_default_value(data.kind.as_ref()?)?;
+
+        let modifier = match value {
+            Token::Nothing => Some(Token::Set),
+            _ => None,
+        };
+
+        return Some(Statement::Assignment(AssignmentStatement {
+            modifier: modifier,
+            left: Box::new(name),
+            right: Box::new(Statement::Value(ValueStatement {
+                value: value,
+            })),
+        }));
+    }
+
+    fn rename_name(&self, name: &mut Token) {
+        let normalized_name = Self::normalize(name);
+
+        // NOTE: Inner blocks' declarations shadow the outer ones.
+        if let Some(scoped_name) = self.scopes.iter().rev().find_map(|scope| scope.get(&normalized_name)) {
+            *name = scoped_name.clone();
+        }
+    }
+
+    fn rename_bounds(&mut self, bounds: &mut Option<Vec<ArrayBound>>) {
+        for bound in bounds.iter_mut().flatten() {
+            if let Some(lower) = &mut bound.lower {
+                self.rename(lower);
+            }
+
+            self.rename(&mut bound.upper);
+        }
+    }
+
+    // Replaces a variable with another one in an expression (see
+    // `Transformer::transform_match`).
+    pub fn replace(&mut self, statement: &mut Statement, name: &Token, replacement: &Token) {
+        self.scopes.push(HashMap::from([(Self::normalize(name), replacement.clone())]));
+        self.rename(statement);
+        self.scopes.pop();
+    }
+
+    fn rename_patterns(&mut self, arm: &mut MatchArm) {
+        for pattern in arm.patterns.iter_mut().flatten() {
+            match pattern {
+                Pattern::Value(value) => self.rename(value),
+
+                Pattern::Range(start, end) => {
+                    self.rename(start);
+                    self.rename(end);
+                },
+
+                _ => (),
+            }
+        }
+    }
+
+    // Renames the variables used by a statement which isn't a block.
+    fn rename(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Value(data) => {
+                if let Token::Identifier(_) = data.value {
+                    self.rename_name(&mut data.value);
+                }
+            },
+
+            Statement::ReDim(data) => {
+                self.rename_name(&mut data.name);
+
+                for bound in &mut data.bounds {
+                    if let Some(lower) = &mut bound.lower {
+                        self.rename(lower);
+                    }
+
+                    self.rename(&mut bound.upper);
+                }
+            },
+
+            Statement::Variable(data) => {
+                self.rename_bounds(&mut data.bounds);
+
+                if let Some(value) = &mut data.value {
+                    self.rename(value);
+                }
+            },
+
+            Statement::Assignment(data) => {
+                self.rename(&mut data.left);
+                self.rename(&mut data.right);
+            },
+
+            Statement::CompoundAssignment(data) => {
+                self.rename(&mut data.left);
+                self.rename(&mut data.right);
+            },
+
+            Statement::Return(data) => {
+                if let Some(value) = &mut data.value {
+                    self.rename(value);
+                }
+            },
+
+            Statement::ProcedureCall(data) => {
+                self.rename(&mut data.procedure);
+
+                for argument in &mut data.arguments { self.rename(argument); }
+            },
+
+            Statement::RaiseEvent(data) => {
+                for argument in &mut data.arguments { self.rename(argument); }
+            },
+
+            Statement::Unary(data) => self.rename(&mut data.value),
+
+            Statement::Binary(data) => {
+                self.rename(&mut data.left);
+                self.rename(&mut data.right);
+            },
+
+            Statement::Call(data) => {
+                self.rename(&mut data.callee);
+
+                for argument in &mut data.arguments { self.rename(argument); }
+            },
+
+            // NOTE: Members belong to the object, so they're kept.
+            Statement::Member(data) => self.rename(&mut data.object),
+
+            Statement::Conditional(data) => {
+                self.rename(&mut data.condition);
+                self.rename(&mut data.when_true);
+                self.rename(&mut data.when_false);
+            },
+
+            Statement::InterpolatedString(data) => {
+                for part in &mut data.parts { self.rename(part); }
+            },
+
+            Statement::Interpolation(data) => self.rename(&mut data.value),
+            Statement::TypeOf(data) => self.rename(&mut data.value),
+
+            // NOTE: Bound values are replaced by the transformer, so they're
+            // kept.
+            Statement::Match(data) => {
+                self.rename(&mut data.value);
+
+                for arm in &mut data.arms {
+                    self.rename_patterns(arm);
+                    self.scopes.push(HashMap::new());
+
+                    if let Some((_, name)) = arm.get_binding() {
+                        let name = name.clone();
+
+                        self.scopes.last_mut().unwrap().insert(Self::normalize(&name), name);
+                    }
+
+                    if let Some(guard) = &mut arm.guard {
+                        self.rename(guard);
+                    }
+
+                    for statement in &mut arm.body { self.rename(statement); }
+
+                    self.scopes.pop();
+                }
+            },
+
+            Statement::ArrayLiteral(data) => {
+                for element in &mut data.elements { self.rename(element); }
+            },
+
+            // No renaming aplicable.
+            _ => (),
+        }
+    }
+
+    fn normalize(token: &Token) -> String {
+        return String::from_utf8_lossy(&token.get_lexeme()).to_lowercase();
+    }
+}
@@ -17,15 +17,69 @@ pub enum Statement {
     Enum(EnumStatement),
     EnumAttribute(EnumAttributeStatement),
     Variable(VariableStatement),
+    ReDim(ReDimStatement),
     Constant(ConstantStatement),
     Subroutine(SubroutineStatement),
     Function(FunctionStatement),
+    Property(PropertyStatement),
     Exit(ExitStatement),
     Argument(ArgumentStatement),
     Assignment(AssignmentStatement),
+    CompoundAssignment(CompoundAssignmentStatement),
     Return(ReturnStatement),
     Option(OptionStatement),
     Attribute(AttributeStatement),
+
+    // Class modules and forms.
+    Version(VersionStatement),
+    Object(ObjectStatement),
+    Begin(BeginStatement),
+    BeginProperty(BeginPropertyStatement),
+    BeginAttribute(BeginAttributeStatement),
+    Unparsed(UnparsedStatement),
+    Event(EventStatement),
+    RaiseEvent(RaiseEventStatement),
+    Implements(ImplementsStatement),
+
+    // Control flow.
+    If(IfStatement),
+    IfBranch(IfBranchStatement),
+    Match(MatchStatement),
+    For(ForStatement),
+    ForEach(ForEachStatement),
+    ForRange(ForRangeStatement),
+    LabelledLoop(LabelledLoopStatement),
+    Do(DoStatement),
+    While(WhileStatement),
+    Continue(ContinueStatement),
+    Defer(DeferStatement),
+    Label(LabelStatement),
+    GoTo(GoToStatement),
+    ProcedureCall(ProcedureCallStatement),
+
+    // Error handling.
+    Try(TryStatement),
+    Using(UsingStatement),
+    OnError(OnErrorStatement),
+    Resume(ResumeStatement),
+
+    // Conditional compilation.
+    DirectiveIf(DirectiveIfStatement),
+    DirectiveBranch(DirectiveBranchStatement),
+    DirectiveConst(DirectiveConstStatement),
+
+    // Expressions.
+    Value(ValueStatement),
+    New(NewStatement),
+    Unary(UnaryStatement),
+    Binary(BinaryStatement),
+    Call(CallStatement),
+    Member(MemberStatement),
+    TypeOf(TypeOfStatement),
+    Conditional(ConditionalStatement),
+    InterpolatedString(InterpolatedStringStatement),
+    ArrayLiteral(ArrayLiteralStatement),
+    Interpolation(InterpolationStatement),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -57,7 +111,63 @@ pub struct EnumAttributeStatement {
 pub struct VariableStatement {
     pub scope: Token,
     pub name: Token,
-    pub kind: Token,
+
+    // NOTE: Only arrays have bounds, which are empty for the dynamic ones
+    // (i.e. `Dim items() As Long`).
+    pub bounds: Option<Vec<ArrayBound>>,
+
+    // NOTE: When there's no data type, it's inferred from the value.
+    pub kind: Option<Token>,
+
+    // NOTE: Variables declared `As New` create their object the first time
+    // they're used (i.e. `Dim items As New Collection`), and so can't have
+    // a value.
+    pub new: Option<Token>,
+    pub value: Option<Box<Statement>>,
+}
+
+// NOTE: Written as `1 To 10`, or just `10` when the lower bound is the one
+// given by `Option Base`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayBound {
+    pub lower: Option<Box<Statement>>,
+    pub upper: Box<Statement>,
+}
+
+impl ArrayBound {
+    // Returns the lower bound, if it's known at compile time.
+    pub fn get_lower(&self, base: i64) -> Option<i64> {
+        return match &self.lower {
+            Some(lower) => Self::get_integer(lower),
+            None => Some(base),
+        };
+    }
+
+    // Returns the upper bound, if it's known at compile time.
+    pub fn get_upper(&self) -> Option<i64> {
+        return Self::get_integer(&self.upper);
+    }
+
+    // NOTE: Only (maybe negative) literals are taken into account.
+    pub fn get_integer(statement: &Statement) -> Option<i64> {
+        return match statement {
+            Statement::Value(ValueStatement { value: Token::Number(lexeme) }) => {
+                String::from_utf8_lossy(lexeme).parse().ok()
+            },
+
+            Statement::Unary(UnaryStatement { operator: Token::Minus, value }) => Some(-Self::get_integer(value)?),
+
+            _ => None,
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReDimStatement {
+    pub preserve: Option<Token>,
+    pub name: Token,
+    pub bounds: Vec<ArrayBound>,
+    pub kind: Option<Token>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -86,27 +196,267 @@ pub struct FunctionStatement {
     pub body: Vec<Statement>,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyStatement {
+    pub scope: Token,
+
+    // NOTE: Either `Get`, `Let` or `Set`.
+    pub accessor: Token,
+    pub name: Token,
+    pub arguments: Vec<Statement>,
+    pub kind: Option<Token>,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: The block is the label of the loop when it's labelled (i.e. `Exit
+// outer`), which is lowered by the transformer.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ExitStatement {
     pub block: Token,
 }
 
+// NOTE: Lowered by the transformer, see `Transformer::transform_continue`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContinueStatement {
+    pub block: Token,
+}
+
+// NOTE: Lowered by the transformer, see `Transformer::transform_defer`. The
+// body only has the deferred statement.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeferStatement {
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelStatement {
+    pub name: Token,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoToStatement {
+    pub label: Token,
+}
+
+// NOTE: Written either as `Call Name(a, b)` or as `Name a, b`, but it's
+// always generated as the latter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcedureCallStatement {
+    pub procedure: Box<Statement>,
+    pub arguments: Vec<Statement>,
+}
+
+// NOTE: Lowered by the transformer, see `Transformer::transform_try`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TryStatement {
+    pub body: Vec<Statement>,
+    pub catch: Option<CatchClause>,
+    pub finally: Option<Vec<Statement>>,
+}
+
+impl TryStatement {
+    pub fn get_bodies(&self) -> Vec<&Vec<Statement>> {
+        let mut bodies = vec!(&self.body);
+
+        if let Some(catch) = &self.catch {
+            bodies.push(&catch.body);
+        }
+
+        if let Some(finally) = &self.finally {
+            bodies.push(finally);
+        }
+
+        return bodies;
+    }
+}
+
+// NOTE: Lowered by the transformer, see `Transformer::transform_using`. The
+// value is either `New` (i.e. `Using conn As New ADODB.Connection`) or given
+// after the data type (i.e. `Using rs As ADODB.Recordset = conn.Execute(sql)`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsingStatement {
+    pub name: Token,
+    pub kind: Token,
+    pub value: Box<Statement>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatchClause {
+    // NOTE: The variable which refers to the caught error (VB6's `Err`).
+    pub name: Option<Token>,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: The label is `None` for `On Error Resume Next`, and `0` for
+// `On Error GoTo 0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OnErrorStatement {
+    pub label: Option<Token>,
+}
+
+// NOTE: The target is either `None` (a plain `Resume`), `Next` or a label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResumeStatement {
+    pub target: Option<Token>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfStatement {
+    pub branches: Vec<Statement>,
+}
+
+impl IfStatement {
+    pub fn get_bodies(&self) -> Vec<&Vec<Statement>> {
+        return self.branches.iter().filter_map(|statement| match statement {
+            Statement::IfBranch(branch) => Some(&branch.body),
+            _ => None,
+        }).collect();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfBranchStatement {
+    // NOTE: The `Else` branch has no condition.
+    pub condition: Option<Box<Statement>>,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: Lowered by the transformer, see `Transformer::transform_match`. It's
+// an expression too, whose arms have their value as their only statement
+// (i.e. `Case Red Then "Stop"`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchStatement {
+    pub value: Box<Statement>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchArm {
+    // NOTE: `Case Else` has no patterns.
+    pub patterns: Option<Vec<Pattern>>,
+    pub guard: Option<Box<Statement>>,
+    pub body: Vec<Statement>,
+}
+
+impl MatchArm {
+    // Returns the data type and the variable given by `Case TypeOf Kind As
+    // name`, which can only bind the value when it's the arm's only pattern.
+    pub fn get_binding(&self) -> Option<(&Token, &Token)> {
+        return match self.patterns.as_deref() {
+            Some([Pattern::TypeOf(kind, Some(name))]) => Some((kind, name)),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    Value(Statement),
+
+    // NOTE: Both ends are included (i.e. `Case 1 To 9`).
+    Range(Statement, Statement),
+
+    // NOTE: The variable is `Set` to the value when it matches.
+    TypeOf(Token, Option<Token>),
+
+    Nothing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForStatement {
+    pub counter: Token,
+    pub start: Box<Statement>,
+    pub end: Box<Statement>,
+    pub step: Option<Box<Statement>>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForEachStatement {
+    pub element: Token,
+
+    // NOTE: Nabe's typed elements (i.e. `For Each item As Customer In items`)
+    // are declared by the loop.
+    pub kind: Option<Token>,
+    pub collection: Box<Statement>,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: The operator is either `..` (the end is included) or `..<` (it isn't).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForRangeStatement {
+    pub counter: Token,
+    pub start: Box<Statement>,
+    pub operator: Token,
+    pub end: Box<Statement>,
+    pub step: Option<Box<Statement>>,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: Nabe's labelled loops (i.e. `outer: For ...`), which can be exited and
+// continued from inner loops (i.e. `Exit outer` and `Continue outer`). The
+// body only has the loop itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelledLoopStatement {
+    pub label: Token,
+    pub body: Vec<Statement>,
+}
+
+// NOTE: The condition is checked before each iteration when it's written
+// next to `Do` (the header), and after it when it's next to `Loop` (the
+// footer).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DoStatement {
+    pub header: Option<LoopCondition>,
+    pub body: Vec<Statement>,
+    pub footer: Option<LoopCondition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoopCondition {
+    // NOTE: Either `While` or `Until`.
+    pub kind: Token,
+    pub value: Box<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WhileStatement {
+    pub condition: Box<Statement>,
+    pub body: Vec<Statement>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ArgumentStatement {
     pub modifier: Option<Token>,
     pub name: Token,
+
+    // NOTE: Arrays are passed without bounds (i.e. `items() As Long`).
+    pub bounds: Option<Vec<ArrayBound>>,
     pub kind: Token,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct AssignmentStatement {
-    pub left: Token,
-    pub right: Box<Token>, // TODO: This should be `Box<Statement>`.
+    // NOTE: Either `Let`, `Set` or `None`. The latter is an implicit `Let`
+    // and is kept apart so the generated code looks like the original one.
+    pub modifier: Option<Token>,
+    pub left: Box<Statement>,
+    pub right: Box<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompoundAssignmentStatement {
+    pub left: Box<Statement>,
+    // NOTE: The compound operator itself (i.e. `+=`), see
+    // `Token::get_compound_operator`.
+    pub operator: Token,
+    pub right: Box<Statement>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ReturnStatement {
-    pub value: Option<Token>, // TODO: This should be `Option<Box<Statement>>`.
+    pub value: Option<Box<Statement>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -117,8 +467,205 @@ pub struct OptionStatement {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct AttributeStatement {
-    // TODO: This should be a `Box<Statement>` since `name` could be a field
-    // (i.e. `something.like.this`).
+    // NOTE: Procedure attributes are prefixed with the procedure's name
+    // (i.e. `Attribute Item.VB_UserMemId = 0`).
+    pub procedure: Option<Token>,
+    pub name: Token,
+    pub value: Token,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionStatement {
+    pub number: Token,
+
+    // NOTE: Only class modules have it (i.e. `VERSION 1.0 CLASS`).
+    pub kind: Option<Token>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectStatement {
+    pub value: Vec<Token>,
+}
+
+// NOTE: Forms' designer blocks are trees, where the attributes of each
+// `Begin` may contain the (nested) controls of a container and groups of
+// properties (`BeginProperty`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeginStatement {
+    // NOTE: Class modules' headers have neither a kind nor a name, while
+    // forms' controls have both (i.e. `Begin VB.CommandButton Command1`).
+    pub kind: Option<Token>,
+    pub name: Option<Token>,
+    pub attributes: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeginPropertyStatement {
+    pub name: Token,
+    pub index: Option<Token>,
+    pub class: Option<Token>,
+    pub attributes: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeginAttributeStatement {
+    pub name: Token,
+    pub value: Vec<Token>,
+}
+
+// NOTE: A line of a designer block which couldn't be parsed, which is kept
+// as it was so it's reported by the analyzer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnparsedStatement {
+    pub tokens: Vec<Token>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventStatement {
+    pub scope: Option<Token>,
+    pub name: Token,
+    pub arguments: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaiseEventStatement {
+    pub name: Token,
+    pub arguments: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImplementsStatement {
+    pub name: Token,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectiveIfStatement {
+    pub branches: Vec<Statement>,
+}
+
+impl DirectiveIfStatement {
+    pub fn get_bodies(&self) -> Vec<&Vec<Statement>> {
+        return self.branches.iter().filter_map(|statement| match statement {
+            Statement::DirectiveBranch(branch) => Some(&branch.body),
+            _ => None,
+        }).collect();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectiveBranchStatement {
+    // NOTE: The `#Else` branch has no condition.
+    pub condition: Option<Box<Statement>>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectiveConstStatement {
     pub name: Token,
+    pub value: Box<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueStatement {
+    // NOTE: Either an identifier, a number, a string or `Nothing`.
     pub value: Token,
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewStatement {
+    pub kind: Token,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnaryStatement {
+    pub operator: Token,
+    pub value: Box<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinaryStatement {
+    pub left: Box<Statement>,
+    pub operator: Token,
+    pub right: Box<Statement>,
+}
+
+// NOTE: Array indexes are written just like calls, so both are parsed as
+// calls since they can't be told apart without knowing the declarations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallStatement {
+    pub callee: Box<Statement>,
+    pub arguments: Vec<Statement>,
+}
+
+// NOTE: Written as `If(condition, a, b)`, which (unlike VB6's `IIf`) only
+// evaluates the chosen value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalStatement {
+    pub condition: Box<Statement>,
+    pub when_true: Box<Statement>,
+    pub when_false: Box<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberStatement {
+    pub object: Box<Statement>,
+    pub member: Token,
+}
+
+// NOTE: Written as `TypeOf value Is Kind`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeOfStatement {
+    pub value: Box<Statement>,
+    pub kind: Token,
+}
+
+// NOTE: Written as `$"Hello {name}"`, where the literal parts are strings
+// (i.e. `"Hello "`) and the embedded expressions are `Interpolation`s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterpolatedStringStatement {
+    pub parts: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterpolationStatement {
+    pub value: Box<Statement>,
+
+    // NOTE: A string with the format given after a colon (i.e. `"0.00"` for
+    // `{amount:0.00}`), as expected by VB6's `Format$`.
+    pub format: Option<Token>,
+}
+
+// NOTE: Written as `{1, 2, 3}`, where multidimensional arrays nest them (i.e.
+// `{{1, 2}, {3, 4}}`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayLiteralStatement {
+    pub elements: Vec<Statement>,
+}
+
+impl ArrayLiteralStatement {
+    // Returns the length of each dimension, if every nested array of the same
+    // dimension has the same length.
+    pub fn get_shape(&self) -> Option<Vec<usize>> {
+        let mut shape = vec!(self.elements.len());
+        let mut inner_shape = None;
+
+        for element in &self.elements {
+            let element_shape = match element {
+                Statement::ArrayLiteral(data) => Some(data.get_shape()?),
+                _ => None,
+            };
+
+            match &inner_shape {
+                None => inner_shape = Some(element_shape),
+                Some(inner_shape) if *inner_shape != element_shape => return None,
+                Some(_) => (),
+            }
+        }
+
+        if let Some(Some(mut inner_shape)) = inner_shape {
+            shape.append(&mut inner_shape);
+        }
+
+        return Some(shape);
+    }
+}
@@ -0,0 +1,856 @@
+use std::collections::HashMap;
+
+use crate::token::Token;
+use crate::statement::*;
+
+// Data types whose variables hold a value instead of an object reference.
+//
+// NOTE: `Variant` is listed too because it can hold either of them, so
+// nothing can be told about it.
+const VALUE_KINDS: [&str; 10] = [
+    "boolean", "byte", "integer", "long", "single", "double", "currency", "date",
+    "string", "variant",
+];
+
+// VB6's own classes, whose variables hold an object reference.
+const OBJECT_KINDS: [&str; 5] = ["object", "collection", "errobject", "form", "control"];
+
+// NOTE: Sorted from the narrowest to the widest, see `widen_kinds`.
+const NUMERIC_KINDS: [&str; 6] = ["byte", "integer", "long", "currency", "single", "double"];
+
+// Keeps track of the data types of the module's declarations, and of the
+// declarations of the procedure being looked into.
+pub struct Symbols {
+    // NOTE: Names are stored in lowercase because VB6 is case insensitive.
+    kinds: Vec<String>,
+    globals: HashMap<String, Token>,
+    locals: HashMap<String, Token>,
+
+    // NOTE: The data type returned by functions and `Property Get`s.
+    procedures: HashMap<String, Token>,
+
+    // NOTE: The variables named by `Catch`es or assigned through `Let`
+    // which weren't declared, and so have to be.
+    undeclared: Vec<String>,
+
+    // NOTE: The variables the procedure assigns with `Set`, which hold
+    // objects no matter their data type (see `is_object`).
+    objects: Vec<String>,
+
+    // NOTE: The lower bound of arrays declared without one, which is given
+    // by `Option Base`.
+    base: i64,
+
+    // NOTE: The method which disposes of the objects of a data type, given
+    // by the `Nabe_Dispose` attribute (see `get_dispose_method`).
+    dispose_methods: HashMap<String, Option<Token>>,
+
+    // NOTE: The members of `Enum`s, along with their values when they're
+    // known (see `get_enum_members`).
+    enums: HashMap<String, Vec<(Token, Option<i64>)>>,
+
+    // NOTE: The `Enum`s given the `Nabe_Helpers` attribute (see `has_helpers`).
+    helpers: Vec<String>,
+
+    // NOTE: The data types known to be classes (see `is_object_kind`).
+    classes: Vec<String>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        return Self {
+            kinds: vec!(),
+            globals: HashMap::new(),
+            locals: HashMap::new(),
+            procedures: HashMap::new(),
+            undeclared: vec!(),
+            objects: vec!(),
+            base: 0,
+            dispose_methods: HashMap::new(),
+            enums: HashMap::new(),
+            helpers: vec!(),
+            classes: vec!(),
+        };
+    }
+
+    // NOTE: Declarations must be collected before looking into procedures
+    // because VB6 allows using them before (in the source code) they are
+    // declared. The `classes` are the ones declared by other modules (i.e.
+    // the project's class modules).
+    pub fn declare_module(&mut self, statements: &Vec<Statement>, classes: &Vec<String>) {
+        let mut untyped = vec!();
+
+        self.classes.extend(classes.iter().map(|class| class.to_lowercase()));
+
+        self.declare_globals(statements, &mut untyped);
+
+        // NOTE: See `Transformer::transform_enum`.
+        for kind in &self.helpers {
+            self.procedures.insert(format!("{}_tostring", kind), Token::Identifier(b"String".to_vec()));
+            self.procedures.insert(format!("{}_tryparse", kind), Token::Identifier(b"Boolean".to_vec()));
+        }
+
+        // NOTE: The data types of initializers are inferred once everything
+        // else is declared, since they may use functions declared after them.
+        for (name, value) in untyped {
+            let kind = self.infer_kind(value).unwrap_or(Token::Identifier(b"Variant".to_vec()));
+
+            self.globals.insert(name, kind);
+        }
+    }
+
+    fn declare_globals<'a>(&mut self, statements: &'a Vec<Statement>, untyped: &mut Vec<(String, &'a Statement)>) {
+        for statement in statements {
+            match statement {
+                Statement::Type(data) => self.kinds.push(Self::normalize(&data.name)),
+                Statement::Enum(data) => {
+                    self.kinds.push(Self::normalize(&data.name));
+                    self.enums.insert(Self::normalize(&data.name), Self::get_enum_values(data));
+                },
+
+                Statement::Variable(data) => {
+                    self.declare_new_class(data);
+
+                    match (&data.kind, &data.value) {
+                        (None, Some(value)) => untyped.push((Self::normalize(&data.name), value)),
+                        _ => { self.globals.insert(Self::normalize(&data.name), Self::get_declared_kind(data)); },
+                    }
+                },
+
+                Statement::Implements(data) => self.classes.push(Self::normalize(&data.name)),
+
+                Statement::Option(OptionStatement { configuration: Token::Base, value: Some(value) }) => {
+                    self.base = String::from_utf8_lossy(&value.get_lexeme()).parse().unwrap_or(0);
+                },
+
+                // NOTE: An empty method means there's none.
+                Statement::Attribute(AttributeStatement { procedure: Some(kind), name, value }) if Self::is_dispose_attribute(name) => {
+                    let method = value.get_lexeme().iter().filter(|character| **character != b'"').cloned().collect::<Vec<u8>>();
+
+                    let method = match method.is_empty() {
+                        true => None,
+                        false => Some(Token::Identifier(method)),
+                    };
+
+                    self.dispose_methods.insert(Self::normalize(kind), method);
+                    self.classes.push(Self::normalize(kind));
+                },
+
+                Statement::Attribute(AttributeStatement { procedure: Some(kind), name, value }) if Self::is_helpers_attribute(name) => {
+                    if !matches!(Self::normalize(value).as_str(), "false" | "0") {
+                        self.helpers.push(Self::normalize(kind));
+                    }
+                },
+
+                Statement::Constant(data) => {
+                    let kind = match &data.kind {
+                        Some(kind) => kind.clone(),
+                        None => self.infer_value_kind(&data.value).unwrap_or(Token::Identifier(b"Variant".to_vec())),
+                    };
+
+                    self.globals.insert(Self::normalize(&data.name), kind);
+                },
+
+                Statement::Subroutine(data) => self.declare_classes(&data.body),
+
+                Statement::Function(data) => {
+                    self.procedures.insert(Self::normalize(&data.name), Self::kind_or_variant(&data.kind));
+                    self.declare_classes(&data.body);
+                },
+
+                Statement::Property(data) => {
+                    if data.accessor == Token::Get {
+                        self.procedures.insert(Self::normalize(&data.name), Self::kind_or_variant(&data.kind));
+                    }
+
+                    self.declare_classes(&data.body);
+                },
+
+                // NOTE: Both branches' declarations are taken into account
+                // since it's unknown which one will be compiled.
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.declare_globals(body, untyped); }
+                },
+
+                _ => (),
+            }
+        }
+    }
+
+    // Collects the classes of the objects created inside a procedure, either
+    // by variables declared `As New`, by assigning `New` objects or by `Using`
+    // blocks.
+    fn declare_classes(&mut self, body: &Vec<Statement>) {
+        for statement in body {
+            match statement {
+                Statement::Variable(data) => self.declare_new_class(data),
+
+                Statement::Assignment(data) => {
+                    if let Statement::New(value) = &*data.right {
+                        self.classes.push(Self::normalize(&value.kind));
+                    }
+                },
+
+                Statement::If(data) => {
+                    for body in data.get_bodies() { self.declare_classes(body); }
+                },
+
+                Statement::For(data) => self.declare_classes(&data.body),
+                Statement::ForEach(data) => self.declare_classes(&data.body),
+                Statement::ForRange(data) => self.declare_classes(&data.body),
+                Statement::LabelledLoop(data) => self.declare_classes(&data.body),
+                Statement::Do(data) => self.declare_classes(&data.body),
+                Statement::While(data) => self.declare_classes(&data.body),
+                Statement::Defer(data) => self.declare_classes(&data.body),
+
+                Statement::Using(data) => {
+                    self.classes.push(Self::normalize(&data.kind));
+                    self.declare_classes(&data.body);
+                },
+
+                Statement::Match(data) => {
+                    for arm in &data.arms { self.declare_classes(&arm.body); }
+                },
+
+                Statement::Try(data) => {
+                    for body in data.get_bodies() { self.declare_classes(body); }
+                },
+
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.declare_classes(body); }
+                },
+
+                _ => (),
+            }
+        }
+    }
+
+    fn declare_new_class(&mut self, data: &VariableStatement) {
+        if let (Some(_), Some(kind)) = (&data.new, &data.kind) {
+            self.classes.push(Self::normalize(kind));
+        }
+    }
+
+    // Replaces the previous procedure's declarations. The `kind` is the one
+    // of the returned value, if any.
+    pub fn declare_procedure(
+        &mut self,
+        name: &Token,
+        kind: Option<&Token>,
+        arguments: &Vec<Statement>,
+        body: &Vec<Statement>,
+    ) {
+        self.locals.clear();
+        self.undeclared.clear();
+        self.objects.clear();
+
+        // NOTE: Inside a function, its name behaves like a variable which
+        // holds the returned value.
+        if let Some(kind) = kind {
+            self.locals.insert(Self::normalize(name), kind.clone());
+        }
+
+        for statement in arguments {
+            if let Statement::Argument(data) = statement {
+                let kind = match data.bounds {
+                    Some(_) => Self::get_array_kind(&data.kind),
+                    None => data.kind.clone(),
+                };
+
+                self.locals.insert(Self::normalize(&data.name), kind);
+            }
+        }
+
+        let mut implicit = vec!();
+
+        self.declare_variables(body, &mut implicit);
+
+        // NOTE: Variables may be declared after (in the source code) the
+        // statements using them, so these are declared once all the others.
+        for (name, value) in implicit {
+            let normalized_name = Self::normalize(&name);
+
+            if self.is_declared(&name) || self.undeclared.contains(&normalized_name) {
+                continue;
+            }
+
+            // NOTE: `Catch`es have no value, their variable is VB6's `Err`.
+            let kind = match value {
+                Some(value) => self.infer_kind(value).unwrap_or(Token::Identifier(b"Variant".to_vec())),
+                None => Token::Identifier(b"ErrObject".to_vec()),
+            };
+
+            self.locals.insert(normalized_name.clone(), kind);
+            self.undeclared.push(normalized_name);
+        }
+    }
+
+    // Returns whether an implicitly declared variable (see `undeclared`) has
+    // to be declared, which is only true the first time.
+    pub fn take_undeclared(&mut self, name: &Token) -> bool {
+        let name = Self::normalize(name);
+        let is_undeclared = self.undeclared.contains(&name);
+
+        self.undeclared.retain(|undeclared| *undeclared != name);
+
+        return is_undeclared;
+    }
+
+    // Used for the variables generated while transforming the procedure.
+    pub fn declare_local(&mut self, name: &Token, kind: &Token) {
+        self.locals.insert(Self::normalize(name), kind.clone());
+    }
+
+    // NOTE: The variables which may be implicitly declared are pushed to
+    // `implicit`, along with their value.
+    fn declare_variables<'a>(&mut self, body: &'a Vec<Statement>, implicit: &mut Vec<(Token, Option<&'a Statement>)>) {
+        for statement in body {
+            match statement {
+                Statement::Variable(data) => {
+                    let kind = match (&data.kind, &data.value) {
+                        (None, Some(value)) => self.infer_kind(value).unwrap_or(Token::Identifier(b"Variant".to_vec())),
+                        _ => Self::get_declared_kind(data),
+                    };
+
+                    self.locals.insert(Self::normalize(&data.name), kind);
+                },
+
+                // NOTE: `Let x = value` declares `x` when it isn't declared
+                // anywhere else.
+                Statement::Assignment(data) if data.modifier == Some(Token::Let) => {
+                    if let Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) = &*data.left {
+                        implicit.push((name.clone(), Some(&data.right)));
+                    }
+                },
+
+                Statement::Assignment(data) if data.modifier == Some(Token::Set) => {
+                    if let Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) = &*data.left {
+                        self.objects.push(Self::normalize(name));
+                    }
+                },
+
+                // NOTE: VB6's variables belong to the whole procedure, even
+                // when they're declared inside a block.
+                Statement::If(data) => {
+                    for body in data.get_bodies() { self.declare_variables(body, implicit); }
+                },
+
+                Statement::For(data) => self.declare_variables(&data.body, implicit),
+
+                // NOTE: Typed elements are declared by the loop.
+                Statement::ForEach(data) => {
+                    if let Some(kind) = &data.kind {
+                        self.locals.insert(Self::normalize(&data.element), kind.clone());
+                    }
+
+                    self.declare_variables(&data.body, implicit);
+                },
+
+                Statement::ForRange(data) => self.declare_variables(&data.body, implicit),
+                Statement::LabelledLoop(data) => self.declare_variables(&data.body, implicit),
+                Statement::Do(data) => self.declare_variables(&data.body, implicit),
+                Statement::While(data) => self.declare_variables(&data.body, implicit),
+
+                Statement::Using(data) => {
+                    self.locals.insert(Self::normalize(&data.name), data.kind.clone());
+                    self.declare_variables(&data.body, implicit);
+                },
+
+                // NOTE: Bound values are declared by the arm.
+                Statement::Match(data) => {
+                    for arm in &data.arms {
+                        if let Some((kind, name)) = arm.get_binding() {
+                            self.locals.insert(Self::normalize(name), kind.clone());
+                        }
+
+                        self.declare_variables(&arm.body, implicit);
+                    }
+                },
+
+                Statement::Try(data) => {
+                    if let Some(CatchClause { name: Some(name), .. }) = &data.catch {
+                        implicit.push((name.clone(), None));
+                    }
+
+                    for body in data.get_bodies() { self.declare_variables(body, implicit); }
+                },
+
+                // NOTE: See `declare_module`.
+                Statement::DirectiveIf(data) => {
+                    for body in data.get_bodies() { self.declare_variables(body, implicit); }
+                },
+
+                _ => (),
+            }
+        }
+    }
+
+    fn is_declared(&self, name: &Token) -> bool {
+        let name = Self::normalize(name);
+
+        return self.locals.contains_key(&name) || self.globals.contains_key(&name) ||
+            self.procedures.contains_key(&name) || self.kinds.contains(&name);
+    }
+
+    // Returns whether a variable or a function is declared by the module,
+    // no matter the procedure.
+    pub fn is_global(&self, name: &Token) -> bool {
+        let name = Self::normalize(name);
+
+        return self.globals.contains_key(&name) || self.procedures.contains_key(&name);
+    }
+
+    pub fn find_kind(&self, name: &Token) -> Option<Token> {
+        let name = Self::normalize(name);
+
+        // NOTE: Local declarations shadow global ones.
+        return match self.locals.get(&name) {
+            Some(kind) => Some(kind.clone()),
+            None => self.globals.get(&name).cloned(),
+        };
+    }
+
+    // Returns whether the variables of a data type are known to hold object
+    // references, which is only true for classes.
+    //
+    // NOTE: Data types which are unknown (i.e. the ones of libraries, like
+    // `VbMsgBoxResult`) aren't, since they may be `Enum`s or `Type`s, which
+    // can't be assigned with `Set`.
+    pub fn is_object_kind(&self, kind: &Token) -> bool {
+        let kind = Self::normalize(kind);
+
+        return OBJECT_KINDS.contains(&kind.as_str()) || self.classes.contains(&kind);
+    }
+
+    // Returns whether it's known if the variables of a data type hold object
+    // references or values.
+    pub fn is_known_kind(&self, kind: &Token) -> bool {
+        return self.is_object_kind(kind) || self.is_value_kind(kind);
+    }
+
+    // NOTE: Arrays are assigned like values, no matter their elements.
+    fn is_value_kind(&self, kind: &Token) -> bool {
+        let kind = Self::normalize(kind);
+
+        return VALUE_KINDS.contains(&kind.as_str()) || self.kinds.contains(&kind) || kind.ends_with("()");
+    }
+
+    // Returns the value VB6 gives to new variables of a data type, which is
+    // unknown for `Type`s and the data types which aren't known (see
+    // `is_object_kind`).
+    //
+    // TODO: `Enum`s are kept along with `Type`s, so they have none either.
+    pub fn get_default_value(&self, kind: &Token) -> Option<Token> {
+        let is_object_kind = self.is_object_kind(kind);
+        let kind = Self::normalize(kind);
+
+        return match kind.as_str() {
+            "string" => Some(Token::Identifier(b"vbNullString".to_vec())),
+            "boolean" => Some(Token::Identifier(b"False".to_vec())),
+            "variant" => Some(Token::Identifier(b"Empty".to_vec())),
+            "date" => Some(Token::Number(b"0".to_vec())),
+
+            _ if NUMERIC_KINDS.contains(&kind.as_str()) => Some(Token::Number(b"0".to_vec())),
+            _ if is_object_kind => Some(Token::Nothing),
+            _ => None,
+        };
+    }
+
+    // Returns whether an `Enum` gets the functions which convert its values
+    // to and from their names (see `Transformer::transform_enum`), which is
+    // asked for by an attribute:
+    //
+    //     Attribute Color.Nabe_Helpers = True
+    pub fn has_helpers(&self, kind: &Token) -> bool {
+        return self.helpers.contains(&Self::normalize(kind));
+    }
+
+    pub fn is_helpers_attribute(name: &Token) -> bool {
+        return Self::normalize(name) == "nabe_helpers";
+    }
+
+    pub fn get_enum_members(&self, kind: &Token) -> Option<&Vec<(Token, Option<i64>)>> {
+        return self.enums.get(&Self::normalize(kind));
+    }
+
+    // NOTE: Members are written either alone (i.e. `Red`) or qualified by
+    // the `Enum`'s name (i.e. `Color.Red`).
+    pub fn is_enum_member(&self, statement: &Statement) -> bool {
+        let (kind, name) = match statement {
+            Statement::Value(data) => (None, &data.value),
+
+            Statement::Member(MemberStatement { object, member }) => match &**object {
+                Statement::Value(data) => (Some(Self::normalize(&data.value)), member),
+                _ => return false,
+            },
+
+            _ => return false,
+        };
+
+        let name = Self::normalize(name);
+
+        return self.enums.iter()
+            .filter(|(enum_name, _)| kind.is_none() || kind.as_ref() == Some(enum_name))
+            .any(|(_, members)| members.iter().any(|(member, _)| Self::normalize(member) == name));
+    }
+
+    // NOTE: Like VB6 does, members without a value are worth the previous
+    // one plus one (the first one is `0`). Values which aren't literals
+    // aren't known, and so aren't the ones after them.
+    fn get_enum_values(data: &EnumStatement) -> Vec<(Token, Option<i64>)> {
+        let mut members = vec!();
+        let mut next_value = Some(0);
+
+        for attribute in &data.attributes {
+            if let Statement::EnumAttribute(attribute) = attribute {
+                let value = match &attribute.value {
+                    Some(value) => Self::get_enum_value(value),
+                    None => next_value,
+                };
+
+                members.push((attribute.name.clone(), value));
+                next_value = value.map(|value| value + 1);
+            }
+        }
+
+        return members;
+    }
+
+    // Returns the value of an enum's member, which is a whole number.
+    //
+    // NOTE: Like VB6 does, prefixed numbers are written in two's complement
+    // (i.e. `&HFFFF` is `-1`, see `infer_value_kind`).
+    fn get_enum_value(value: &Token) -> Option<i64> {
+        let lexeme = String::from_utf8_lossy(&value.get_lexeme()).to_lowercase();
+        let (digits, is_long) = match lexeme.strip_suffix('&') {
+            Some(digits) => (digits, true),
+            None => (lexeme.as_str(), false),
+        };
+
+        let (digits, radix) = match (digits.strip_prefix("&h"), digits.strip_prefix("&o")) {
+            (Some(digits), _) => (digits, 16),
+            (_, Some(digits)) => (digits, 8),
+            _ => return digits.parse().ok(),
+        };
+
+        let value = i64::from_str_radix(digits, radix).ok()?;
+
+        return match is_long {
+            false if (0x8000..=0xFFFF).contains(&value) => Some(value - 0x1_0000),
+            true if (0x8000_0000..=0xFFFF_FFFF).contains(&value) => Some(value - 0x1_0000_0000),
+            _ => Some(value),
+        };
+    }
+
+    // Returns the method which disposes of the objects of a data type (see
+    // `Transformer::transform_using`), which is `Close` unless it's given
+    // by an attribute:
+    //
+    //     Attribute ADODB.Connection.Nabe_Dispose = "Close"
+    pub fn get_dispose_method(&self, kind: &Token) -> Option<Token> {
+        return match self.dispose_methods.get(&Self::normalize(kind)) {
+            Some(method) => method.clone(),
+            None => Some(Token::Identifier(b"Close".to_vec())),
+        };
+    }
+
+    pub fn is_dispose_attribute(name: &Token) -> bool {
+        return Self::normalize(name) == "nabe_dispose";
+    }
+
+    pub fn get_base(&self) -> i64 {
+        return self.base;
+    }
+
+    // NOTE: Arrays' data type is the one of their elements followed by
+    // parentheses (i.e. `Long()`).
+    fn get_declared_kind(data: &VariableStatement) -> Token {
+        let kind = Self::kind_or_variant(&data.kind);
+
+        return match data.bounds {
+            Some(_) => Self::get_array_kind(&kind),
+            None => kind,
+        };
+    }
+
+    fn get_array_kind(kind: &Token) -> Token {
+        return Token::Identifier([kind.get_lexeme().as_slice(), b"()"].concat());
+    }
+
+    // Returns the data type of an array's elements, if it's an array.
+    pub fn get_element_kind(kind: &Token) -> Option<Token> {
+        return Some(Token::Identifier(kind.get_lexeme().strip_suffix(b"()")?.to_vec()));
+    }
+
+    pub fn is_variant_kind(&self, kind: &Token) -> bool {
+        return Self::normalize(kind) == "variant";
+    }
+
+    // Returns whether an expression is known to evaluate to an object
+    // reference.
+    pub fn is_object(&self, statement: &Statement) -> bool {
+        match statement {
+            Statement::New(_) => return true,
+
+            Statement::Value(ValueStatement { value: name @ Token::Identifier(_) }) if self.objects.contains(&Self::normalize(name)) => {
+                return true;
+            },
+
+            _ => (),
+        }
+
+        return match self.infer_kind(statement) {
+            Some(kind) => self.is_object_kind(&kind),
+            None => false,
+        };
+    }
+
+    // Returns whether assigning a value to something of the given data type
+    // needs `Set`, which is known either from the data type or, when it's
+    // missing, unknown or a `Variant`, from the value.
+    pub fn is_object_assignment(&self, kind: Option<&Token>, value: &Statement) -> bool {
+        return match kind {
+            Some(kind) if self.is_object_kind(kind) => true,
+            Some(kind) if self.is_value_kind(kind) && !self.is_variant_kind(kind) => false,
+            _ => self.is_object(value),
+        };
+    }
+
+    // Returns the data type an expression evaluates to, if it's known.
+    //
+    // TODO: Arrays' elements and objects' members.
+    pub fn infer_kind(&self, statement: &Statement) -> Option<Token> {
+        return match statement {
+            Statement::Value(data) => self.infer_value_kind(&data.value),
+            Statement::New(data) => Some(data.kind.clone()),
+
+            Statement::Unary(data) => {
+                let kind = self.infer_kind(&data.value)?;
+
+                match (&data.operator, Self::normalize(&kind).as_str()) {
+                    (Token::Not, "boolean") => Some(kind),
+                    (_, "single" | "double" | "currency") if data.operator == Token::Not => None,
+                    (_, name) if NUMERIC_KINDS.contains(&name) => Some(kind),
+
+                    _ => None,
+                }
+            },
+
+            Statement::Binary(data) => self.infer_binary_kind(data),
+            Statement::TypeOf(_) => Some(Token::Identifier(b"Boolean".to_vec())),
+            Statement::InterpolatedString(_) => Some(Token::Identifier(b"String".to_vec())),
+
+            // NOTE: Elements of different data types are `Variant`s.
+            Statement::ArrayLiteral(data) => {
+                let mut kind: Option<Token> = None;
+
+                for element in &data.elements {
+                    let element_kind = match element {
+                        Statement::ArrayLiteral(_) => Self::get_element_kind(&self.infer_kind(element)?)?,
+                        _ => self.infer_kind(element).unwrap_or(Token::Identifier(b"Variant".to_vec())),
+                    };
+
+                    kind = match kind {
+                        None => Some(element_kind),
+                        Some(kind) if Self::normalize(&kind) == Self::normalize(&element_kind) => Some(kind),
+                        Some(kind) => Some(self.widen_kinds(&kind, &element_kind).unwrap_or(Token::Identifier(b"Variant".to_vec()))),
+                    };
+                }
+
+                Some(Self::get_array_kind(&kind.unwrap_or(Token::Identifier(b"Variant".to_vec()))))
+            },
+
+            // NOTE: Calls to variables are arrays' elements.
+            Statement::Call(data) => match &*data.callee {
+                Statement::Value(callee) if callee.value.get_lexeme().ends_with(b"$") => {
+                    Some(Token::Identifier(b"String".to_vec()))
+                },
+
+                Statement::Value(callee) if self.find_kind(&callee.value).is_some() => {
+                    Self::get_element_kind(&self.find_kind(&callee.value)?)
+                },
+
+                Statement::Value(callee) => self.procedures.get(&Self::normalize(&callee.value)).cloned(),
+                _ => None,
+            },
+
+            Statement::Conditional(data) => self.infer_common_kind(&[&data.when_true, &data.when_false]),
+
+            // NOTE: The arms of a `Match` expression are their values (see
+            // `MatchStatement`).
+            Statement::Match(data) => {
+                let values = data.arms.iter().map(|arm| arm.body.first()).collect::<Option<Vec<_>>>()?;
+
+                self.infer_common_kind(&values)
+            },
+
+            _ => None,
+        };
+    }
+
+    // Returns the data type which holds every one of the given values (i.e.
+    // the ones an `If(...)` chooses from), if it's known.
+    pub fn infer_common_kind(&self, values: &[&Statement]) -> Option<Token> {
+        let mut common_kind: Option<Token> = None;
+
+        for value in values {
+            let kind = self.infer_kind(value)?;
+
+            common_kind = match common_kind {
+                None => Some(kind),
+                Some(common_kind) if Self::normalize(&common_kind) == Self::normalize(&kind) => Some(common_kind),
+
+                Some(common_kind) if self.is_object_kind(&common_kind) && self.is_object_kind(&kind) => {
+                    Some(Token::Identifier(b"Object".to_vec()))
+                },
+
+                Some(common_kind) => Some(self.widen_kinds(&common_kind, &kind)?),
+            };
+        }
+
+        return common_kind;
+    }
+
+    fn infer_value_kind(&self, value: &Token) -> Option<Token> {
+        let kind = match value {
+            Token::String(_) | Token::EscapedString(_) | Token::RawString(_) => "String",
+            Token::Nothing => "Object",
+
+            Token::Number(lexeme) => {
+                let lexeme = String::from_utf8_lossy(lexeme).to_lowercase();
+
+                // NOTE: Like VB6 does, integer literals are `Integer`s when
+                // they fit in one (prefixed ones are written in two's
+                // complement, so `&HFFFF` is `-1`).
+                let (digits, radix) = match (lexeme.strip_prefix("&h"), lexeme.strip_prefix("&o")) {
+                    (Some(digits), _) => (digits, 16),
+                    (_, Some(digits)) => (digits, 8),
+                    _ => (lexeme.as_str(), 10),
+                };
+
+                let integer_limit = match radix {
+                    10 => 0x7FFF,
+                    _ => 0xFFFF,
+                };
+
+                if digits.ends_with('&') {
+                    "Long"
+                } else if radix == 10 && digits.contains(|c| c == '.' || c == 'e') {
+                    "Double"
+                } else {
+                    match i64::from_str_radix(digits, radix) {
+                        Ok(number) if number <= integer_limit => "Integer",
+                        Ok(number) if number <= 0x7FFF_FFFF || radix != 10 => "Long",
+                        _ => "Double",
+                    }
+                }
+            },
+
+            // NOTE: The lexer keeps strings as identifiers, quotes included.
+            Token::Identifier(lexeme) if lexeme.starts_with(b"\"") => "String",
+
+            // NOTE: Variables and functions named with the string type
+            // character (i.e. `Format$`).
+            Token::Identifier(lexeme) if lexeme.ends_with(b"$") => "String",
+
+            Token::Identifier(_) => match Self::normalize(value).as_str() {
+                "true" | "false" => "Boolean",
+                "me" => "Object",
+
+                // NOTE: Functions may be called without parentheses.
+                name => return match self.find_kind(value) {
+                    Some(kind) => Some(kind),
+                    None => self.procedures.get(name).cloned(),
+                },
+            },
+
+            _ => return None,
+        };
+
+        return Some(Token::Identifier(kind.as_bytes().to_vec()));
+    }
+
+    fn infer_binary_kind(&self, data: &BinaryStatement) -> Option<Token> {
+        let boolean = Some(Token::Identifier(b"Boolean".to_vec()));
+
+        // NOTE: These don't depend on their operands.
+        match data.operator {
+            Token::Assignment | Token::NotEqual | Token::Less | Token::LessOrEqual |
+            Token::Greater | Token::GreaterOrEqual | Token::Like | Token::Is |
+            Token::AndAlso | Token::OrElse => return boolean,
+
+            Token::Ampersand => return Some(Token::Identifier(b"String".to_vec())),
+            Token::Divide | Token::Caret => return Some(Token::Identifier(b"Double".to_vec())),
+
+            _ => (),
+        }
+
+        let left = self.infer_kind(&data.left)?;
+        let right = self.infer_kind(&data.right)?;
+
+        let left_name = Self::normalize(&left);
+        let right_name = Self::normalize(&right);
+
+        return match data.operator {
+            Token::Plus if left_name == "string" && right_name == "string" => Some(left),
+
+            Token::And | Token::Or | Token::Xor | Token::Eqv | Token::Imp => {
+                match (left_name.as_str(), right_name.as_str()) {
+                    ("boolean", "boolean") => boolean,
+
+                    // NOTE: Bitwise operations on whole numbers.
+                    _ => match Self::is_whole_kind(&left_name) && Self::is_whole_kind(&right_name) {
+                        true => self.widen_kinds(&left, &right),
+                        false => None,
+                    },
+                }
+            },
+
+            // NOTE: These work on whole numbers, rounding their operands.
+            Token::IntegerDivide | Token::Mod => {
+                match Self::is_whole_kind(&left_name) && Self::is_whole_kind(&right_name) {
+                    true => self.widen_kinds(&left, &right),
+                    false => Some(Token::Identifier(b"Long".to_vec())),
+                }
+            },
+
+            Token::Plus | Token::Minus | Token::Times => self.widen_kinds(&left, &right),
+
+            _ => None,
+        };
+    }
+
+    // Returns the narrowest numeric data type which holds both given ones.
+    fn widen_kinds(&self, left: &Token, right: &Token) -> Option<Token> {
+        let left_rank = NUMERIC_KINDS.iter().position(|kind| *kind == Self::normalize(left))?;
+        let right_rank = NUMERIC_KINDS.iter().position(|kind| *kind == Self::normalize(right))?;
+
+        // NOTE: VB6 turns `Currency` mixed with floating point numbers into
+        // `Double`.
+        let currency_rank = 3;
+
+        let is_mixed = (left_rank == currency_rank && right_rank > currency_rank) ||
+            (right_rank == currency_rank && left_rank > currency_rank);
+
+        return match (is_mixed, left_rank >= right_rank) {
+            (true, _) => Some(Token::Identifier(b"Double".to_vec())),
+            (false, true) => Some(left.clone()),
+            (false, false) => Some(right.clone()),
+        };
+    }
+
+    fn is_whole_kind(name: &str) -> bool {
+        return matches!(name, "byte" | "integer" | "long");
+    }
+
+    fn kind_or_variant(kind: &Option<Token>) -> Token {
+        return match kind {
+            Some(kind) => kind.clone(),
+            None => Token::Identifier(b"Variant".to_vec()),
+        };
+    }
+
+    fn normalize(token: &Token) -> String {
+        return String::from_utf8_lossy(&token.get_lexeme()).to_lowercase();
+    }
+}
@@ -11,8 +11,18 @@ pub enum Token {
     Number(Vec<u8>),
     String(Vec<u8>),
 
+    // NOTE: Nabe's `$"..."` strings, whose lexeme is what's between the
+    // quotes (see `Parser::parse_interpolated_string`).
+    InterpolatedString(Vec<u8>),
+
+    // NOTE: Nabe's `e"..."` strings, with escape sequences (i.e. `\n`), and
+    // multi-line `"""` ones, whose lexeme is their (unindented) lines.
+    EscapedString(Vec<u8>),
+    RawString(Vec<u8>),
+
     Public,
     Private,
+    Friend,
     Static,
     Dim,
 
@@ -21,8 +31,14 @@ pub enum Token {
 
     As,
     If,
+    Then,
+    Else,
+    ElseIf,
+    Case,
     Sub,
     Function,
+    Property,
+    Get,
     Type,
     Enum,
     Const,
@@ -30,19 +46,56 @@ pub enum Token {
 
     Exit,
     Return,
+    GoTo,
+    Call,
+
+    On,
+    Resume,
+
+    // NOTE: This isn't a keyword (see `Parser::consume_contextual`), it's
+    // only the kind of the transformer's blocks for `Try`s.
+    Try,
+
+    Event,
+    RaiseEvent,
+    Implements,
+
+    Let,
+    Set,
+    New,
+    Nothing,
+
+    ReDim,
+    Preserve,
 
     Do,
     Loop,
+    Until,
 
     While,
     Wend,
 
     For,
+    To,
+    Step,
     Next,
 
+    Not,
     And,
     Or,
     Xor,
+    Eqv,
+    Imp,
+
+    // NOTE: These aren't keywords either, the parser turns the identifiers
+    // into them (see `Parser::parse_binary`).
+    AndAlso,
+    OrElse,
+
+    Mod,
+    Like,
+    Is,
+    TypeOf,
 
     LeftParentheses,
     RightParentheses,
@@ -50,10 +103,16 @@ pub enum Token {
     LeftBracket,
     RightBracket,
 
+    LeftBrace,
+    RightBrace,
+
     Plus,
     Minus,
     Times,
     Divide,
+    IntegerDivide,
+    Caret,
+    Ampersand,
 
     Less,
     LessOrEqual,
@@ -61,8 +120,22 @@ pub enum Token {
     Greater,
     GreaterOrEqual,
 
+    NotEqual,
+
     Assignment,
+    PlusAssignment,
+    MinusAssignment,
+    TimesAssignment,
+    DivideAssignment,
+    AmpersandAssignment,
+    Range,
+    ExclusiveRange,
     Dot,
+    Comma,
+    Colon,
+    Semicolon,
+    Hash,
+    NewLine,
 
     Option,
     Explicit,
@@ -82,9 +155,13 @@ impl Token {
         return match &self {
             Token::Keyword(lexeme) | Token::Identifier(lexeme) => lexeme.clone(),
             Token::Number(lexeme) | Token::String(lexeme) => lexeme.clone(),
+            Token::InterpolatedString(lexeme) => [b"$\"", lexeme.as_slice(), b"\""].concat(),
+            Token::EscapedString(lexeme) => [b"e\"", lexeme.as_slice(), b"\""].concat(),
+            Token::RawString(lexeme) => [b"\"\"\"\n", lexeme.as_slice(), b"\n\"\"\""].concat(),
 
             Token::Public => b"public".to_vec(),
             Token::Private => b"private".to_vec(),
+            Token::Friend => b"friend".to_vec(),
             Token::Static => b"static".to_vec(),
             Token::Dim => b"dim".to_vec(),
 
@@ -93,19 +170,84 @@ impl Token {
 
             Token::As => b"as".to_vec(),
             Token::If => b"if".to_vec(),
+            Token::Then => b"then".to_vec(),
+            Token::Else => b"else".to_vec(),
+            Token::ElseIf => b"elseif".to_vec(),
+            Token::Case => b"case".to_vec(),
             Token::Sub => b"sub".to_vec(),
             Token::Function => b"function".to_vec(),
+            Token::Property => b"property".to_vec(),
+            Token::Get => b"get".to_vec(),
             Token::Type => b"type".to_vec(),
+            Token::Enum => b"enum".to_vec(),
+            Token::Const => b"const".to_vec(),
             Token::End => b"end".to_vec(),
 
             Token::Exit => b"exit".to_vec(),
             Token::Return => b"return".to_vec(),
+            Token::GoTo => b"goto".to_vec(),
+            Token::Call => b"call".to_vec(),
+
+            Token::On => b"on".to_vec(),
+            Token::Resume => b"resume".to_vec(),
+
+            Token::Try => b"try".to_vec(),
+
+            Token::Event => b"event".to_vec(),
+            Token::RaiseEvent => b"raiseevent".to_vec(),
+            Token::Implements => b"implements".to_vec(),
+
+            Token::Let => b"let".to_vec(),
+            Token::Set => b"set".to_vec(),
+            Token::New => b"new".to_vec(),
+            Token::Nothing => b"nothing".to_vec(),
+
+            Token::ReDim => b"redim".to_vec(),
+            Token::Preserve => b"preserve".to_vec(),
+
+            Token::Do => b"do".to_vec(),
+            Token::Loop => b"loop".to_vec(),
+            Token::Until => b"until".to_vec(),
+
+            Token::While => b"while".to_vec(),
+            Token::Wend => b"wend".to_vec(),
+
+            Token::For => b"for".to_vec(),
+            Token::To => b"to".to_vec(),
+            Token::Step => b"step".to_vec(),
+            Token::Next => b"next".to_vec(),
+
+            Token::Not => b"not".to_vec(),
+            Token::And => b"and".to_vec(),
+            Token::Or => b"or".to_vec(),
+            Token::Xor => b"xor".to_vec(),
+            Token::Eqv => b"eqv".to_vec(),
+            Token::Imp => b"imp".to_vec(),
+
+            Token::AndAlso => b"andalso".to_vec(),
+            Token::OrElse => b"orelse".to_vec(),
+
+            Token::Mod => b"mod".to_vec(),
+            Token::Like => b"like".to_vec(),
+            Token::Is => b"is".to_vec(),
+            Token::TypeOf => b"typeof".to_vec(),
 
             Token::LeftParentheses => b"(".to_vec(),
             Token::RightParentheses => b")".to_vec(),
 
+            Token::LeftBracket => b"[".to_vec(),
+            Token::RightBracket => b"]".to_vec(),
+
+            Token::LeftBrace => b"{".to_vec(),
+            Token::RightBrace => b"}".to_vec(),
+
             Token::Plus => b"+".to_vec(),
             Token::Minus => b"-".to_vec(),
+            Token::Times => b"*".to_vec(),
+            Token::Divide => b"/".to_vec(),
+            Token::IntegerDivide => b"\\".to_vec(),
+            Token::Caret => b"^".to_vec(),
+            Token::Ampersand => b"&".to_vec(),
 
             Token::Less => b"<".to_vec(),
             Token::LessOrEqual => b"<=".to_vec(),
@@ -113,7 +255,22 @@ impl Token {
             Token::Greater => b">".to_vec(),
             Token::GreaterOrEqual => b">=".to_vec(),
 
+            Token::NotEqual => b"<>".to_vec(),
+
+            Token::Assignment => b"=".to_vec(),
+            Token::PlusAssignment => b"+=".to_vec(),
+            Token::MinusAssignment => b"-=".to_vec(),
+            Token::TimesAssignment => b"*=".to_vec(),
+            Token::DivideAssignment => b"/=".to_vec(),
+            Token::AmpersandAssignment => b"&=".to_vec(),
+            Token::Range => b"..".to_vec(),
+            Token::ExclusiveRange => b"..<".to_vec(),
             Token::Dot => b".".to_vec(),
+            Token::Comma => b",".to_vec(),
+            Token::Colon => b":".to_vec(),
+            Token::Semicolon => b";".to_vec(),
+            Token::Hash => b"#".to_vec(),
+            Token::NewLine => b"\n".to_vec(),
 
             Token::Option => b"option".to_vec(),
             Token::Explicit => b"explicit".to_vec(),
@@ -122,9 +279,58 @@ impl Token {
             Token::Module => b"module".to_vec(),
 
             Token::Attribute => b"attribute".to_vec(),
+        };
+    }
+
+    // Returns how tightly a binary operator binds its operands (the higher, the
+    // tighter), following VB6's operator precedence.
+    pub fn get_precedence(&self) -> Option<u8> {
+        return match &self {
+            Token::Imp => Some(1),
+            Token::Eqv => Some(2),
+            Token::Xor => Some(3),
+            Token::Or | Token::OrElse => Some(4),
+            Token::And | Token::AndAlso => Some(5),
+
+            // NOTE: `Not` stands here, see `get_unary_precedence`.
+
+            Token::Assignment | Token::NotEqual | Token::Less | Token::LessOrEqual |
+            Token::Greater | Token::GreaterOrEqual | Token::Like | Token::Is => Some(7),
+
+            Token::Ampersand => Some(8),
+            Token::Plus | Token::Minus => Some(9),
+            Token::Mod => Some(10),
+            Token::IntegerDivide => Some(11),
+            Token::Times | Token::Divide => Some(12),
+
+            // NOTE: Negation stands here, see `get_unary_precedence`.
+
+            Token::Caret => Some(14),
+
+            _ => None,
+        };
+    }
+
+    // Returns the binary operator a compound assignment (i.e. `+=`) applies.
+    pub fn get_compound_operator(&self) -> Option<Token> {
+        return match &self {
+            Token::PlusAssignment => Some(Token::Plus),
+            Token::MinusAssignment => Some(Token::Minus),
+            Token::TimesAssignment => Some(Token::Times),
+            Token::DivideAssignment => Some(Token::Divide),
+            Token::AmpersandAssignment => Some(Token::Ampersand),
+
+            _ => None,
+        };
+    }
+
+    // NOTE: See `get_precedence`.
+    pub fn get_unary_precedence(&self) -> Option<u8> {
+        return match &self {
+            Token::Not => Some(6),
+            Token::Minus => Some(13),
 
-            // TODO: Temporary polyfill.
-            _ => b"__POLYFILL__".to_vec(),
+            _ => None,
         };
     }
 }
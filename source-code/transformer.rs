@@ -1,21 +1,212 @@
 use crate::token::Token;
 use crate::statement::*;
 use crate::viewer::Viewer;
+use crate::symbols::Symbols;
+use crate::scoper::Scoper;
 
 struct Block {
-    name: Token,
+    // NOTE: The keyword used to exit the block (i.e. `Sub`, `Function`,
+    // `Property`, `For` or `Do`), or to continue it (i.e. `While`). `Try`
+    // blocks are kept too, since exiting the procedure from them must run
+    // their `Finally` first. Labelled loops have a block for their label too,
+    // right before the loop's own, whose kind is the label.
+    kind: Token,
+
+    // NOTE: Only procedures have a name.
+    name: Option<Token>,
+
+    // NOTE: Only functions and `Property Get`s return a value, which is done
+    // by assigning it to their name.
+    returns_value: bool,
+
+    // NOTE: The declared data type of the returned value, if any.
+    return_kind: Option<Token>,
+
+    // NOTE: Only procedures with `Defer`s have these. The label of the
+    // deferred statements, which are run before leaving the procedure, the
+    // label of the error handler running them, along with the flag telling
+    // whether each one was reached (see `transform_procedure_body`).
+    defer_label: Option<Token>,
+    defer_error_label: Option<Token>,
+    deferred: Vec<(Token, Vec<Statement>)>,
+
+    // NOTE: The label placed at the end of a loop's body for `Continue` to
+    // jump to, which is only created once a `Continue` needs it.
+    continue_label: Option<Token>,
+
+    // NOTE: Only for labels. The label placed after a labelled loop for
+    // `Exit` to jump to, which is only created once an `Exit` needs it.
+    exit_label: Option<Token>,
+
+    // NOTE: Only for `Try` blocks. The jumps out of the `Try` which have to
+    // run its `Finally` first, and the variable which tells which one of them
+    // (if any) reached it (see `transform_jump`).
+    finally_label: Option<Token>,
+    exiting_flag: Option<Token>,
+    jumps: Vec<Jump>,
+}
+
+// The ways of leaving the blocks around a statement.
+#[derive(Clone, PartialEq)]
+enum Jump {
+    // NOTE: Exiting the procedure (i.e. `Exit Sub` or `Return`).
+    Procedure,
+
+    // NOTE: Exiting a loop either by its kind (i.e. `Exit For`) or by its
+    // label.
+    Loop(Token),
+
+    // NOTE: Going to the next iteration of a loop, either by its kind (i.e.
+    // `Continue Do`) or by its label.
+    Continue(Token),
+}
+
+impl Block {
+    fn new_procedure(kind: Token, name: Token, returns_value: bool, return_kind: Option<Token>) -> Self {
+        return Self {
+            kind: kind,
+            name: Some(name),
+            returns_value: returns_value,
+            return_kind: return_kind,
+            defer_label: None,
+            defer_error_label: None,
+            deferred: vec!(),
+            continue_label: None,
+            exit_label: None,
+            finally_label: None,
+            exiting_flag: None,
+            jumps: vec!(),
+        };
+    }
+
+    fn new_loop(kind: Token) -> Self {
+        return Self {
+            kind: kind,
+            name: None,
+            returns_value: false,
+            return_kind: None,
+            defer_label: None,
+            defer_error_label: None,
+            deferred: vec!(),
+            continue_label: None,
+            exit_label: None,
+            finally_label: None,
+            exiting_flag: None,
+            jumps: vec!(),
+        };
+    }
+
+    fn new_label(label: Token) -> Self {
+        return Self {
+            kind: label,
+            name: None,
+            returns_value: false,
+            return_kind: None,
+            defer_label: None,
+            defer_error_label: None,
+            deferred: vec!(),
+            continue_label: None,
+            exit_label: None,
+            finally_label: None,
+            exiting_flag: None,
+            jumps: vec!(),
+        };
+    }
+
+    fn new_try(finally_label: Token, exiting_flag: Token) -> Self {
+        return Self {
+            kind: Token::Try,
+            name: None,
+            returns_value: false,
+            return_kind: None,
+            defer_label: None,
+            defer_error_label: None,
+            deferred: vec!(),
+            continue_label: None,
+            exit_label: None,
+            finally_label: Some(finally_label),
+            exiting_flag: Some(exiting_flag),
+            jumps: vec!(),
+        };
+    }
+
+    fn is_procedure(&self) -> bool {
+        return matches!(self.kind, Token::Sub | Token::Function | Token::Property);
+    }
+
+    // NOTE: Labels are case insensitive, like anything else in VB6.
+    fn is_kind(&self, kind: &Token) -> bool {
+        return match (&self.kind, kind) {
+            (Token::Identifier(label), Token::Identifier(name)) => label.eq_ignore_ascii_case(name),
+            (block_kind, kind) => block_kind == kind,
+        };
+    }
 }
 
 struct Transformer {
     statements: Viewer<Statement>,
     blocks: Vec<Block>,
+    symbols: Symbols,
+
+    // NOTE: Used to give unique names to the generated variables and labels.
+    temporaries: usize,
+    labels: usize,
+
+    // The error handlers in effect, where the last one is the current one.
+    // They're written as the label of an `On Error` (see `OnErrorStatement`),
+    // so `None` is `On Error Resume Next`.
+    handlers: Vec<Option<Token>>,
+
+    // NOTE: The assignments of module variables' values, which are moved to
+    // the procedure run when the module is initialized (see
+    // `transform_initializers`).
+    initializers: Vec<Statement>,
+    initializer: Initializer,
+
+    // NOTE: The procedures generated for the module's declarations, which go
+    // after everything else (see `transform_enum`).
+    helpers: Vec<Statement>,
+}
+
+// The procedure which assigns module variables' values.
+enum Initializer {
+    // NOTE: Class modules and forms have an event raised when they're
+    // created (i.e. `Class_Initialize`).
+    Event(Token),
+
+    // NOTE: Modules don't, so the procedure is called at the beginning of
+    // every other procedure and only runs the first time.
+    Procedure(Token),
 }
 
 impl Transformer {
-    pub fn new(statements: Vec<Statement>) -> Self {
+    pub fn new(statements: Vec<Statement>, classes: &Vec<String>) -> Self {
+        let mut symbols = Symbols::new();
+
+        symbols.declare_module(&statements, classes);
+
+        // NOTE: Only class modules' headers have a kind, while forms' don't.
+        let initializer = match statements.iter().find_map(|statement| match statement {
+            Statement::Version(data) => Some(data.kind.is_some()),
+            _ => None,
+        }) {
+            Some(true) => Initializer::Event(Self::identifier("Class_Initialize")),
+            Some(false) => Initializer::Event(Self::identifier("Form_Initialize")),
+            None => Initializer::Procedure(Self::identifier("nabe_initialize")),
+        };
+
+        let initializers = Self::collect_initializers(&statements, &symbols);
+
         return Self {
             statements: Viewer::new(statements),
             blocks: vec!(),
+            symbols: symbols,
+            temporaries: 0,
+            labels: 0,
+            handlers: vec!(),
+            initializers: initializers,
+            initializer: initializer,
+            helpers: vec!(),
         };
     }
 
@@ -23,28 +214,360 @@ impl Transformer {
         let mut transformed_statements = vec!();
 
         while let Some(statement) = self.statements.next() {
-            let transformed_statement = match statement {
-                Statement::Function(data) => self.transform_function(data),
+            // NOTE: Nabe's attributes are only meant for the transformer (see
+            // `Symbols::get_dispose_method` and `Symbols::has_helpers`).
+            if let Statement::Attribute(AttributeStatement { name, .. }) = &statement {
+                if Symbols::is_dispose_attribute(name) || Symbols::is_helpers_attribute(name) {
+                    continue;
+                }
+            }
 
-                // No transformation aplicable.
-                _ => statement,
-            };
+            let transformed_statement = self.transform_module_statement(statement);
 
             transformed_statements.push(transformed_statement);
         }
 
+        for helper in std::mem::take(&mut self.helpers) {
+            let transformed_helper = self.transform_module_statement(helper);
+
+            transformed_statements.push(transformed_helper);
+        }
+
+        // NOTE: The initializer was already transformed if it's an event
+        // handler declared by the module.
+        if !self.initializers.is_empty() {
+            let name = match &self.initializer {
+                Initializer::Event(name) => name.clone(),
+                Initializer::Procedure(name) => name.clone(),
+            };
+
+            let initializer = self.transform_subroutine(SubroutineStatement {
+                scope: Token::Private,
+                name: name,
+                arguments: vec!(),
+                body: vec!(),
+            });
+
+            transformed_statements.push(initializer);
+        }
+
+        return transformed_statements;
+    }
+
+    // Returns the assignments of the module variables' values, keeping the
+    // conditionally compiled blocks they're inside of.
+    fn collect_initializers(statements: &Vec<Statement>, symbols: &Symbols) -> Vec<Statement> {
+        let mut initializers = vec!();
+
+        for statement in statements {
+            match statement {
+                Statement::Variable(VariableStatement { name, bounds, kind, value: Some(value), .. }) => match &**value {
+                    // NOTE: Fixed-size arrays have their bounds.
+                    Statement::ArrayLiteral(literal) => {
+                        let bounds = bounds.as_ref().filter(|bounds| !bounds.is_empty());
+
+                        initializers.append(&mut Self::assign_array(symbols, name.clone(), bounds, literal.clone()));
+                    },
+
+                    _ => {
+                        let kind = match kind {
+                            Some(kind) => Some(kind.clone()),
+                            None => symbols.find_kind(name),
+                        };
+
+                        let modifier = match symbols.is_object_assignment(kind.as_ref(), value) {
+                            true => Some(Token::Set),
+                            false => None,
+                        };
+
+                        initializers.push(Statement::Assignment(AssignmentStatement {
+                            modifier: modifier,
+                            left: Box::new(Self::value(name.clone())),
+                            right: value.clone(),
+                        }));
+                    },
+                },
+
+                Statement::DirectiveIf(data) => {
+                    let mut branches = vec!();
+
+                    for statement in &data.branches {
+                        if let Statement::DirectiveBranch(branch) = statement {
+                            branches.push(Statement::DirectiveBranch(DirectiveBranchStatement {
+                                condition: branch.condition.clone(),
+                                body: Self::collect_initializers(&branch.body, symbols),
+                            }));
+                        }
+                    }
+
+                    let has_initializers = branches.iter().any(|branch| match branch {
+                        Statement::DirectiveBranch(branch) => !branch.body.is_empty(),
+                        _ => false,
+                    });
+
+                    if has_initializers {
+                        initializers.push(Statement::DirectiveIf(DirectiveIfStatement {
+                            branches: branches,
+                        }));
+                    }
+                },
+
+                _ => (),
+            }
+        }
+
+        return initializers;
+    }
+
+    // Used for the conditionally compiled blocks which stand outside of
+    // functions and subroutines.
+    fn transform_module_body(&mut self, body: Vec<Statement>) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        for statement in body {
+            transformed_statements.push(self.transform_module_statement(statement));
+        }
+
         return transformed_statements;
     }
 
+    fn transform_module_statement(&mut self, statement: Statement) -> Statement {
+        return match statement {
+            Statement::Subroutine(data) => self.transform_subroutine(data),
+            Statement::Function(data) => self.transform_function(data),
+            Statement::Property(data) => self.transform_property(data),
+            Statement::Enum(data) if self.symbols.has_helpers(&data.name) => self.transform_enum(data),
+            Statement::DirectiveIf(data) => self.transform_directive_if(data, Transformer::transform_module_body),
+
+            // NOTE: The value is assigned by the initializer.
+            Statement::Variable(data) if data.value.is_some() => {
+                let kind = match data.kind {
+                    Some(kind) => kind,
+                    None => self.symbols.find_kind(&data.name).unwrap_or(Self::identifier("Variant")),
+                };
+
+                Self::declaration(data.scope, data.name, data.bounds, kind)
+            },
+
+            // No transformation aplicable.
+            _ => statement,
+        };
+    }
+
+    // Gives an `Enum` the hidden members `[_First]` and `[_Last]`, to iterate
+    // over its values, along with the functions which convert them to and
+    // from their names (see `Symbols::has_helpers`):
+    //
+    //     Public Function Color_ToString(ByVal value As Color) As String
+    //     Public Function Color_TryParse(ByVal text As String, value As Color) As Boolean
+    //
+    // NOTE: The hidden members are the first and the last ones declared, and
+    // names are parsed no matter their case.
+    fn transform_enum(&mut self, data: EnumStatement) -> Statement {
+        // NOTE: `Enum`s given helpers are always declared by the module (see
+        // `Symbols::declare_module`).
+        let members = self.symbols.get_enum_members(&data.name).cloned().unwrap_or_default();
+
+        // NOTE: `Match` needs an arm.
+        if members.is_empty() {
+            return Statement::Enum(data);
+        }
+
+        // NOTE: Members are iterated by value, which isn't always the order
+        // they're declared in. There's no need for the bounds when a value
+        // isn't known.
+        let values: Option<Vec<(Token, i64)>> = members.iter()
+            .map(|(member, value)| value.map(|value| (member.clone(), value)))
+            .collect();
+
+        let bounds = values.map(|values| {
+            // NOTE: There's always a member, so these can't fail.
+            let first = values.iter().min_by_key(|(_, value)| *value).unwrap().0.clone();
+            let last = values.iter().max_by_key(|(_, value)| *value).unwrap().0.clone();
+
+            (first, last)
+        });
+
+        let members: Vec<Token> = members.into_iter().map(|(member, _)| member).collect();
+
+        let scope = match data.scope {
+            Some(Token::Private) => Token::Private,
+            _ => Token::Public,
+        };
+
+        let function_name = |suffix: &str| {
+            Self::identifier(&format!("{}_{}", String::from_utf8_lossy(&data.name.get_lexeme()), suffix))
+        };
+
+        let to_string = function_name("ToString");
+        let try_parse = function_name("TryParse");
+
+        let argument = |modifier: Option<Token>, name: &str, kind: Token| Statement::Argument(ArgumentStatement {
+            modifier: modifier,
+            name: Self::identifier(name),
+            bounds: None,
+            kind: kind,
+        });
+
+        // NOTE: Hidden names (i.e. `[_Unknown]`) are written without their
+        // brackets.
+        let string = |member: &Token, is_lowercase: bool| {
+            let name = String::from_utf8_lossy(&member.get_lexeme()).trim_matches(|c| c == '[' || c == ']').to_string();
+
+            let name = match is_lowercase {
+                true => name.to_lowercase(),
+                false => name,
+            };
+
+            Self::string(&name)
+        };
+
+        let mut to_string_arms = vec!();
+        let mut try_parse_arms = vec!();
+
+        for member in &members {
+            let qualified_member = Statement::Member(MemberStatement {
+                object: Box::new(Self::value(data.name.clone())),
+                member: member.clone(),
+            });
+
+            to_string_arms.push(MatchArm {
+                patterns: Some(vec!(Pattern::Value(qualified_member.clone()))),
+                guard: None,
+                body: vec!(Self::assign(Self::value(to_string.clone()), string(member, false))),
+            });
+
+            try_parse_arms.push(MatchArm {
+                patterns: Some(vec!(Pattern::Value(string(member, true)))),
+                guard: None,
+                body: vec!(Self::assign(Self::value(Self::identifier("value")), qualified_member)),
+            });
+        }
+
+        try_parse_arms.push(MatchArm {
+            patterns: None,
+            guard: None,
+            body: vec!(Self::assign(Self::value(try_parse.clone()), Self::value(Self::identifier("False")))),
+        });
+
+        self.helpers.push(Statement::Function(FunctionStatement {
+            scope: scope.clone(),
+            name: to_string,
+            arguments: vec!(argument(Some(Token::ByVal), "value", data.name.clone())),
+            kind: Some(Self::identifier("String")),
+            body: vec!(Statement::Match(MatchStatement {
+                value: Box::new(Self::value(Self::identifier("value"))),
+                arms: to_string_arms,
+            })),
+        }));
+
+        self.helpers.push(Statement::Function(FunctionStatement {
+            scope: scope,
+            name: try_parse.clone(),
+            arguments: vec!(
+                argument(Some(Token::ByVal), "text", Self::identifier("String")),
+                argument(None, "value", data.name.clone()),
+            ),
+            kind: Some(Self::identifier("Boolean")),
+            body: vec!(
+                Self::assign(Self::value(try_parse), Self::value(Self::identifier("True"))),
+                Statement::Match(MatchStatement {
+                    value: Box::new(Statement::Call(CallStatement {
+                        callee: Box::new(Self::value(Self::identifier("LCase$"))),
+                        arguments: vec!(Self::value(Self::identifier("text"))),
+                    })),
+                    arms: try_parse_arms,
+                }),
+            ),
+        }));
+
+        let mut attributes = data.attributes;
+
+        if let Some((first, last)) = bounds {
+            for (name, member) in [("[_First]", first), ("[_Last]", last)] {
+                attributes.push(Statement::EnumAttribute(EnumAttributeStatement {
+                    name: Self::identifier(name),
+                    value: Some(member),
+                }));
+            }
+        }
+
+        return Statement::Enum(EnumStatement {
+            scope: data.scope,
+            name: data.name,
+            attributes: attributes,
+        });
+    }
+
+    // NOTE: See `Parser::parse_directive_if`.
+    fn transform_directive_if(
+        &mut self,
+        data: DirectiveIfStatement,
+        transform_body: fn(&mut Self, Vec<Statement>) -> Vec<Statement>,
+    ) -> Statement {
+        let mut transformed_branches = vec!();
+
+        for statement in data.branches {
+            let transformed_branch = match statement {
+                Statement::DirectiveBranch(branch) => DirectiveBranchStatement {
+                    condition: branch.condition,
+                    body: transform_body(self, branch.body),
+                },
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            };
+
+            transformed_branches.push(Statement::DirectiveBranch(transformed_branch));
+        }
+
+        return Statement::DirectiveIf(DirectiveIfStatement {
+            branches: transformed_branches,
+        });
+    }
+
+    fn transform_subroutine(&mut self, data: SubroutineStatement) -> Statement {
+        self.blocks.push(Block::new_procedure(Token::Sub, data.name.clone(), false, None));
+
+        let mut body = self.take_initializers(&data.name);
+
+        body.extend(data.body);
+
+        let body = Scoper::new(&self.symbols).scope(&data.name, &data.arguments, body);
+
+        self.symbols.declare_procedure(&data.name, None, &data.arguments, &body);
+
+        let transformed_data = SubroutineStatement {
+            scope: data.scope,
+            name: data.name,
+            arguments: data.arguments,
+            body: self.transform_procedure_body(body),
+        };
+
+        self.blocks.pop();
+
+        return Statement::Subroutine(transformed_data);
+    }
+
     fn transform_function(&mut self, data: FunctionStatement) -> Statement {
-        self.blocks.push(Block { name: data.name.clone() });
+        self.blocks.push(Block::new_procedure(Token::Function, data.name.clone(), true, data.kind.clone()));
+
+        let mut body = self.take_initializers(&data.name);
+
+        body.extend(data.body);
+
+        let body = Scoper::new(&self.symbols).scope(&data.name, &data.arguments, body);
+
+        self.symbols.declare_procedure(&data.name, data.kind.as_ref(), &data.arguments, &body);
 
         let transformed_data = FunctionStatement {
             scope: data.scope,
             name: data.name,
             arguments: data.arguments,
             kind: data.kind,
-            body: self.transform_function_body(data.body),
+            body: self.transform_procedure_body(body),
         };
 
         self.blocks.pop();
@@ -52,13 +575,345 @@ impl Transformer {
         return Statement::Function(transformed_data);
     }
 
-    fn transform_function_body(&mut self, body: Vec<Statement>) -> Vec<Statement> {
+    fn transform_property(&mut self, data: PropertyStatement) -> Statement {
+        let returns_value = data.accessor == Token::Get;
+
+        let return_kind = match returns_value {
+            true => data.kind.clone(),
+            false => None,
+        };
+
+        self.blocks.push(Block::new_procedure(Token::Property, data.name.clone(), returns_value, return_kind.clone()));
+
+        let mut body = self.take_initializers(&data.name);
+
+        body.extend(data.body);
+
+        let body = Scoper::new(&self.symbols).scope(&data.name, &data.arguments, body);
+
+        self.symbols.declare_procedure(&data.name, return_kind.as_ref(), &data.arguments, &body);
+
+        let transformed_data = PropertyStatement {
+            scope: data.scope,
+            accessor: data.accessor,
+            name: data.name,
+            arguments: data.arguments,
+            kind: data.kind,
+            body: self.transform_procedure_body(body),
+        };
+
+        self.blocks.pop();
+
+        return Statement::Property(transformed_data);
+    }
+
+    // Transforms a procedure's body, running its deferred statements (see
+    // `transform_defer`) in reverse order before leaving it, even because of
+    // an error:
+    //
+    //     On Error GoTo nabe_defer_error_1
+    //     <body>
+    //     nabe_defer_1:
+    //     On Error GoTo 0
+    //     If nabe_deferred_3 Then
+    //     nabe_deferred_3 = False
+    //     <the second deferred statement>
+    //     End If
+    //     If nabe_deferred_2 Then
+    //     nabe_deferred_2 = False
+    //     <the first deferred statement>
+    //     End If
+    //     <raise the error again, if any>
+    //     Exit Sub
+    //     nabe_defer_error_1:
+    //     <capture the error>
+    //     Resume nabe_defer_1
+    fn transform_procedure_body(&mut self, body: Vec<Statement>) -> Vec<Statement> {
+        // NOTE: The handlers set by `On Error` are in effect until the end of
+        // their procedure (see `transform_on_error`).
+        self.handlers.clear();
+
+        if !Self::has_defers(&body) {
+            return Self::hoist_declarations(self.transform_callable_body(body));
+        }
+
+        self.labels += 1;
+
+        let number = self.labels;
+        let name = |kind: &str| Self::identifier(&format!("nabe_{}_{}", kind, number));
+
+        let defer_label = name("defer");
+        let error_label = name("defer_error");
+        let error_variables = [name("error_number"), name("error_source"), name("error_description")];
+
+        self.get_procedure_mut().defer_label = Some(defer_label.clone());
+        self.get_procedure_mut().defer_error_label = Some(error_label.clone());
+        self.handlers.push(Some(error_label.clone()));
+
+        let mut body = self.transform_callable_body(body);
+
+        self.handlers.pop();
+
+        let procedure = self.get_procedure_mut();
+        let exit = Statement::Exit(ExitStatement {
+            block: procedure.kind.clone(),
+        });
+
+        let deferred = std::mem::take(&mut procedure.deferred);
+
+        let mut transformed_statements = vec!(
+            Self::declare(error_variables[0].clone(), "Long"),
+            Self::declare(error_variables[1].clone(), "String"),
+            Self::declare(error_variables[2].clone(), "String"),
+            Self::on_error(error_label.clone()),
+        );
+
+        transformed_statements.append(&mut body);
+        transformed_statements.push(Self::label(defer_label.clone()));
+        transformed_statements.push(Self::on_error(Token::Number(b"0".to_vec())));
+
+        for (flag, statements) in deferred.into_iter().rev() {
+            transformed_statements.push(Self::when(Self::value(flag), statements));
+        }
+
+        transformed_statements.push(Self::raise_error(&error_variables));
+        transformed_statements.push(exit);
+        transformed_statements.push(Self::label(error_label));
+        transformed_statements.append(&mut Self::capture_error(&error_variables));
+        transformed_statements.push(Self::resume(defer_label));
+
+        return Self::hoist_declarations(transformed_statements);
+    }
+
+    fn has_defers(body: &Vec<Statement>) -> bool {
+        return body.iter().any(|statement| match statement {
+            Statement::Defer(_) => true,
+
+            Statement::If(data) => data.get_bodies().into_iter().any(Self::has_defers),
+            Statement::For(data) => Self::has_defers(&data.body),
+            Statement::ForEach(data) => Self::has_defers(&data.body),
+            Statement::ForRange(data) => Self::has_defers(&data.body),
+            Statement::LabelledLoop(data) => Self::has_defers(&data.body),
+            Statement::Do(data) => Self::has_defers(&data.body),
+            Statement::While(data) => Self::has_defers(&data.body),
+            Statement::Try(data) => data.get_bodies().into_iter().any(Self::has_defers),
+            Statement::Using(data) => Self::has_defers(&data.body),
+            Statement::Match(data) => data.arms.iter().any(|arm| Self::has_defers(&arm.body)),
+            Statement::DirectiveIf(data) => data.get_bodies().into_iter().any(Self::has_defers),
+
+            _ => false,
+        });
+    }
+
+    // Lowers `Defer <statement>` into a flag telling that the statement has
+    // to be run before leaving the procedure (see `transform_procedure_body`).
+    //
+    // NOTE: Statements deferred inside a loop are only run once.
+    fn transform_defer(&mut self, data: DeferStatement) -> Vec<Statement> {
+        self.temporaries += 1;
+
+        let flag = Self::identifier(&format!("nabe_deferred_{}", self.temporaries));
+
+        let mut statements = vec!(Self::assign(Self::value(flag.clone()), Self::value(Self::identifier("False"))));
+
+        statements.append(&mut self.transform_callable_body(data.body));
+
+        self.get_procedure_mut().deferred.push((flag.clone(), statements));
+
+        return vec!(
+            Self::declare(flag.clone(), "Boolean"),
+            Self::assign(Self::value(flag), Self::value(Self::identifier("True"))),
+        );
+    }
+
+    // NOTE: Errors in procedures with `Defer`s are always handled, so that
+    // the deferred statements are run before raising them.
+    fn transform_on_error(&mut self, data: OnErrorStatement) -> Vec<Statement> {
+        let is_disabled = data.label == Some(Token::Number(b"0".to_vec()));
+
+        // NOTE: Procedures with `Defer`s handle errors to run them, so
+        // disabling the handler goes back to that one instead.
+        let data = match &self.get_procedure().defer_error_label {
+            Some(label) if is_disabled => OnErrorStatement {
+                label: Some(label.clone()),
+            },
+
+            _ => data,
+        };
+
+        // NOTE: The handler replaces the current one, which is restored when
+        // leaving the `Try`s inside it (see `transform_try`).
+        match self.handlers.last_mut() {
+            Some(handler) => *handler = data.label.clone(),
+            None => self.handlers.push(data.label.clone()),
+        }
+
+        return vec!(Statement::OnError(data));
+    }
+
+    // Moves the declarations inside blocks (see `Scoper`) to the beginning of
+    // the procedure, where they belong in VB6.
+    //
+    // NOTE: Declarations inside `#If`s are kept there, since they may not be
+    // compiled.
+    fn hoist_declarations(body: Vec<Statement>) -> Vec<Statement> {
+        let mut declarations = vec!();
+        let mut body = Self::take_declarations(body, false, &mut declarations);
+
+        declarations.append(&mut body);
+
+        return declarations;
+    }
+
+    fn take_declarations(body: Vec<Statement>, is_block: bool, declarations: &mut Vec<Statement>) -> Vec<Statement> {
+        let mut remaining_statements = vec!();
+
+        for statement in body {
+            match statement {
+                Statement::Variable(data) if is_block && data.scope == Token::Dim => {
+                    declarations.push(Statement::Variable(data));
+                },
+
+                Statement::If(data) => {
+                    let branches = data.branches.into_iter().map(|branch| match branch {
+                        Statement::IfBranch(branch) => Statement::IfBranch(IfBranchStatement {
+                            condition: branch.condition,
+                            body: Self::take_declarations(branch.body, true, declarations),
+                        }),
+
+                        branch => branch,
+                    }).collect();
+
+                    remaining_statements.push(Statement::If(IfStatement {
+                        branches: branches,
+                    }));
+                },
+
+                Statement::For(data) => {
+                    remaining_statements.push(Statement::For(ForStatement {
+                        counter: data.counter,
+                        start: data.start,
+                        end: data.end,
+                        step: data.step,
+                        body: Self::take_declarations(data.body, true, declarations),
+                    }));
+                },
+
+                Statement::ForEach(data) => {
+                    remaining_statements.push(Statement::ForEach(ForEachStatement {
+                        element: data.element,
+                        kind: data.kind,
+                        collection: data.collection,
+                        body: Self::take_declarations(data.body, true, declarations),
+                    }));
+                },
+
+                Statement::Do(data) => {
+                    remaining_statements.push(Statement::Do(DoStatement {
+                        header: data.header,
+                        body: Self::take_declarations(data.body, true, declarations),
+                        footer: data.footer,
+                    }));
+                },
+
+                Statement::While(data) => {
+                    remaining_statements.push(Statement::While(WhileStatement {
+                        condition: data.condition,
+                        body: Self::take_declarations(data.body, true, declarations),
+                    }));
+                },
+
+                statement => remaining_statements.push(statement),
+            }
+        }
+
+        return remaining_statements;
+    }
+
+    // Returns the statements to run at the beginning of a procedure so that
+    // module variables' values are assigned before they're used:
+    //
+    //     Static nabe_initialized As Boolean
+    //     If nabe_initialized Then
+    //     Exit Sub
+    //     End If
+    //     nabe_initialized = True
+    //     <initializers>
+    //
+    // NOTE: Modules' variables used from other modules before any of their
+    // procedures is called still have their default value.
+    fn take_initializers(&mut self, procedure: &Token) -> Vec<Statement> {
+        if self.initializers.is_empty() {
+            return vec!();
+        }
+
+        let is_initializer = |name: &Token| {
+            return procedure.get_lexeme().eq_ignore_ascii_case(&name.get_lexeme());
+        };
+
+        return match &self.initializer {
+            Initializer::Event(name) if is_initializer(name) => std::mem::take(&mut self.initializers),
+            Initializer::Event(_) => vec!(),
+
+            Initializer::Procedure(name) if is_initializer(name) => {
+                let initialized_flag = Self::identifier("nabe_initialized");
+
+                let mut statements = vec!(
+                    Statement::Variable(VariableStatement {
+                        scope: Token::Static,
+                        name: initialized_flag.clone(),
+                        bounds: None,
+                        kind: Some(Self::identifier("Boolean")),
+                        new: None,
+                        value: None,
+                    }),
+                    Self::when(Self::value(initialized_flag.clone()), vec!(Statement::Exit(ExitStatement {
+                        block: Token::Sub,
+                    }))),
+                    Self::assign(Self::value(initialized_flag), Self::value(Self::identifier("True"))),
+                );
+
+                statements.append(&mut self.initializers);
+
+                statements
+            },
+
+            Initializer::Procedure(name) => vec!(Statement::ProcedureCall(ProcedureCallStatement {
+                procedure: Box::new(Self::value(name.clone())),
+                arguments: vec!(),
+            })),
+        };
+    }
+
+    // Used for functions, subroutines and properties.
+    fn transform_callable_body(&mut self, body: Vec<Statement>) -> Vec<Statement> {
         let mut transformed_statements = vec!();
         let mut viewer = Viewer::new(body);
 
         while let Some(statement) = viewer.next() {
             let mut transformed_statement = match statement {
-                Statement::Return(data) => self.transform_function_return(data),
+                Statement::Variable(data) => self.transform_variable(data),
+                Statement::Return(data) => self.transform_return(data),
+                Statement::Assignment(data) => self.transform_assignment(data),
+                Statement::CompoundAssignment(data) => self.transform_compound_assignment(data),
+                Statement::ProcedureCall(data) => self.transform_procedure_call(data),
+                Statement::RaiseEvent(data) => self.transform_raise_event(data),
+                Statement::Continue(data) => self.transform_continue(data),
+                Statement::Defer(data) => self.transform_defer(data),
+                Statement::OnError(data) => self.transform_on_error(data),
+                Statement::Try(data) => self.transform_try(data),
+                Statement::Using(data) => self.transform_using(data),
+                Statement::Exit(data) if data.block == self.get_procedure().kind => self.transform_exit(),
+                Statement::Exit(data) if matches!(data.block, Token::For | Token::Do | Token::Identifier(_)) => self.transform_loop_exit(data),
+                Statement::If(data) => self.transform_if(data),
+                Statement::Match(data) => self.transform_match(data),
+                Statement::For(data) => self.transform_for(data),
+                Statement::ForEach(data) => self.transform_for_each(data),
+                Statement::ForRange(data) => self.transform_for_range(data),
+                Statement::LabelledLoop(data) => self.transform_labelled_loop(data),
+                Statement::Do(data) => self.transform_do(data),
+                Statement::While(data) => self.transform_while(data),
+                Statement::DirectiveIf(data) => vec!(self.transform_directive_if(data, Transformer::transform_callable_body)),
 
                 // No transformation aplicable.
                 _ => vec!(statement),
@@ -71,33 +926,3540 @@ impl Transformer {
         return transformed_statements;
     }
 
-    fn transform_function_return(&mut self, data: ReturnStatement) -> Vec<Statement> {
+    fn transform_return(&mut self, data: ReturnStatement) -> Vec<Statement> {
         let mut transformed_statements = vec!();
 
-        transformed_statements.push(Statement::Assignment(AssignmentStatement {
-            // TODO: Make sure `self.blocks` only contains methods/functions/subroutines
-            // names, and not loops or other kind of blocks.
-            left: self.blocks.last().unwrap().name.clone(),
+        let value = match data.value {
+            Some(value) => Some(Box::new(self.transform_expression(*value, &mut transformed_statements))),
+            None => None,
+        };
 
-            // NOTE: I unwrap thee because the parser should have already
-            // checked if a function's return is complete (empty returns
-            // are allowed only in subroutines).
-            //
-            // Failing to do so means the parser is broken, and panicking
-            // is a good way (TODO: or not?) to signal it.
-            right: Box::new(data.value.unwrap()),
-        }));
+        let block = self.get_procedure();
 
-        transformed_statements.push(Statement::Exit(ExitStatement {
-            block: Token::Function,
-        }));
+        // NOTE: Values returned from subroutines (and empty returns from
+        // functions) are reported by the analyzer, so here they're ignored
+        // and the block is just exited.
+        if let (Some(value), true) = (value, block.returns_value) {
+            let modifier = self.get_modifier(block.return_kind.as_ref(), &value);
+
+            transformed_statements.push(Statement::Assignment(AssignmentStatement {
+                modifier: modifier,
+                left: Box::new(Statement::Value(ValueStatement {
+                    // NOTE: Procedures always have a name.
+                    value: block.name.clone().unwrap(),
+                })),
+                right: value,
+            }));
+        }
+
+        transformed_statements.append(&mut self.transform_exit());
 
         return transformed_statements;
     }
-}
 
-pub fn transform(statements: Vec<Statement>) -> Vec<Statement> {
-    let mut transformer = Transformer::new(statements);
+    // NOTE: Objects must be assigned with `Set`.
+    fn get_modifier(&self, kind: Option<&Token>, value: &Statement) -> Option<Token> {
+        return match self.symbols.is_object_assignment(kind, value) {
+            true => Some(Token::Set),
+            false => None,
+        };
+    }
+
+    // Splits a declaration with a value into the declaration (whose data
+    // type is inferred if it's missing) and an assignment:
+    //
+    //     Dim x As <kind>
+    //     x = <value>
+    //
+    // `Static` variables keep their value between calls, so they're only
+    // assigned the first time:
+    //
+    //     Static x As <kind>
+    //     Static nabe_initialized_1 As Boolean
+    //     If Not nabe_initialized_1 Then
+    //     x = <value>
+    //     nabe_initialized_1 = True
+    //     End If
+    fn transform_variable(&mut self, data: VariableStatement) -> Vec<Statement> {
+        let value = match data.value {
+            Some(value) => value,
+            None => return vec!(Statement::Variable(data)),
+        };
 
-    return transformer.transform();
+        // NOTE: The inferred data type was stored when declaring the
+        // procedure's variables.
+        let kind = match data.kind {
+            Some(kind) => kind,
+            None => self.symbols.find_kind(&data.name).unwrap_or(Self::identifier("Variant")),
+        };
+
+        // NOTE: Fixed-size arrays have their bounds.
+        let fixed_bounds = data.bounds.clone().filter(|bounds| !bounds.is_empty());
+
+        let mut transformed_statements = vec!(Self::declaration(data.scope.clone(), data.name.clone(), data.bounds, kind.clone()));
+
+        let mut assignment = match *value {
+            Statement::ArrayLiteral(literal) => {
+                let assignment = Self::assign_array(&self.symbols, data.name, fixed_bounds.as_ref(), literal);
+
+                self.transform_callable_body(assignment)
+            },
+
+            value => {
+                let mut assignment = vec!();
+                let value = self.transform_expression(value, &mut assignment);
+
+                assignment.push(Statement::Assignment(AssignmentStatement {
+                    modifier: self.get_modifier(Some(&kind), &value),
+                    left: Box::new(Self::value(data.name)),
+                    right: Box::new(value),
+                }));
+
+                assignment
+            },
+        };
+
+        if data.scope != Token::Static {
+            transformed_statements.append(&mut assignment);
+
+            return transformed_statements;
+        }
+
+        self.temporaries += 1;
+
+        let initialized_flag = Self::identifier(&format!("nabe_initialized_{}", self.temporaries));
+
+        assignment.push(Self::assign(Self::value(initialized_flag.clone()), Self::value(Self::identifier("True"))));
+
+        transformed_statements.push(Statement::Variable(VariableStatement {
+            scope: Token::Static,
+            name: initialized_flag.clone(),
+            bounds: None,
+            kind: Some(Self::identifier("Boolean")),
+            new: None,
+            value: None,
+        }));
+
+        transformed_statements.push(Self::when(
+            Statement::Unary(UnaryStatement {
+                operator: Token::Not,
+                value: Box::new(Self::value(initialized_flag)),
+            }),
+            assignment,
+        ));
+
+        return transformed_statements;
+    }
+
+    // NOTE: Callables' bodies are only transformed inside a procedure, so
+    // there is always a procedure's block (even if it's inside loops).
+    fn get_procedure(&self) -> &Block {
+        return self.blocks.iter().rev().find(|block| block.is_procedure()).unwrap();
+    }
+
+    // NOTE: See `get_procedure`.
+    fn get_procedure_mut(&mut self) -> &mut Block {
+        return self.blocks.iter_mut().rev().find(|block| block.is_procedure()).unwrap();
+    }
+
+    // Exits the procedure, running the `Finally` of each `Try` around first.
+    fn transform_exit(&mut self) -> Vec<Statement> {
+        return self.transform_jump(Jump::Procedure);
+    }
+
+    // Leaves the blocks around, running the `Finally` of each `Try` in
+    // between first. When there's one, the jump is made once its `Finally`
+    // has been run (see `transform_try`):
+    //
+    //     nabe_exiting_1 = 1
+    //     GoTo nabe_finally_1
+    //
+    // NOTE: Jumps to loops which aren't around are reported by the analyzer,
+    // so here they're just dropped.
+    fn transform_jump(&mut self, jump: Jump) -> Vec<Statement> {
+        let target = match &jump {
+            Jump::Procedure => self.blocks.iter().rposition(|block| block.is_procedure()),
+            Jump::Loop(kind) => self.find_loop(kind),
+
+            // NOTE: The block of a label is right before the one of its loop,
+            // which is the one continued.
+            Jump::Continue(label @ Token::Identifier(_)) => self.find_loop(label).map(|index| index + 1),
+            Jump::Continue(kind) => self.find_loop(kind),
+        };
+
+        let target = match target {
+            Some(target) => target,
+            None => return vec!(),
+        };
+
+        let try_index = (target + 1..self.blocks.len()).rev().find(|index| self.blocks[*index].kind == Token::Try);
+
+        if let Some(try_index) = try_index {
+            let block = &mut self.blocks[try_index];
+
+            let number = match block.jumps.iter().position(|block_jump| *block_jump == jump) {
+                Some(position) => position + 1,
+                None => {
+                    block.jumps.push(jump);
+                    block.jumps.len()
+                },
+            };
+
+            // NOTE: `Try` blocks always have these.
+            let exiting_flag = block.exiting_flag.clone().unwrap();
+            let finally_label = block.finally_label.clone().unwrap();
+
+            return vec!(
+                Self::assign(Self::value(exiting_flag), Self::value(Token::Number(number.to_string().into_bytes()))),
+                Self::goto(finally_label),
+            );
+        }
+
+        let block = &mut self.blocks[target];
+
+        return match jump {
+            // NOTE: Deferred statements are run before leaving the procedure.
+            Jump::Procedure => match &block.defer_label {
+                Some(defer_label) => vec!(Self::goto(defer_label.clone())),
+                None => vec!(Statement::Exit(ExitStatement {
+                    block: block.kind.clone(),
+                })),
+            },
+
+            Jump::Loop(Token::Identifier(_)) => {
+                if block.exit_label.is_none() {
+                    self.labels += 1;
+
+                    block.exit_label = Some(Self::identifier(&format!("nabe_exit_{}", self.labels)));
+                }
+
+                vec!(Self::goto(block.exit_label.clone().unwrap()))
+            },
+
+            Jump::Loop(kind) => vec!(Statement::Exit(ExitStatement {
+                block: kind,
+            })),
+
+            Jump::Continue(_) => {
+                if block.continue_label.is_none() {
+                    self.labels += 1;
+
+                    block.continue_label = Some(Self::identifier(&format!("nabe_continue_{}", self.labels)));
+                }
+
+                vec!(Self::goto(block.continue_label.clone().unwrap()))
+            },
+        };
+    }
+
+    // Lowers a `Try` into `On Error GoTo`s to labels which capture the error,
+    // leave VB6's error handling mode (through `Resume`) and then run the
+    // `Catch` and `Finally` blocks:
+    //
+    //     On Error GoTo nabe_catch_1
+    //     <body>
+    //     GoTo nabe_finally_1
+    //     nabe_catch_1:
+    //     <capture the error>
+    //     Resume nabe_caught_1
+    //     nabe_caught_1:
+    //     On Error GoTo nabe_catch_failed_1
+    //     <restore the error, and catch's body>
+    //     GoTo nabe_finally_1
+    //     nabe_catch_failed_1:
+    //     <capture the error>
+    //     Resume nabe_finally_1
+    //     nabe_finally_1:
+    //     On Error GoTo <the previous handler>
+    //     <finally's body, and raise the error again if it wasn't caught>
+    //     If nabe_exiting_1 = 1 Then <the jump out of the Try, if any>
+    fn transform_try(&mut self, data: TryStatement) -> Vec<Statement> {
+        self.labels += 1;
+
+        let number = self.labels;
+        let name = |kind: &str| Self::identifier(&format!("nabe_{}_{}", kind, number));
+
+        let catch_label = name("catch");
+        let caught_label = name("caught");
+        let catch_failed_label = name("catch_failed");
+        let finally_label = name("finally");
+
+        let error_number = name("error_number");
+        let error_source = name("error_source");
+        let error_description = name("error_description");
+        let exiting_flag = name("exiting");
+
+        let mut transformed_statements = vec!(
+            Self::declare(error_number.clone(), "Long"),
+            Self::declare(error_source.clone(), "String"),
+            Self::declare(error_description.clone(), "String"),
+            Self::assign(Self::value(error_number.clone()), Self::value(Token::Number(b"0".to_vec()))),
+        );
+
+        let error_variables = [error_number.clone(), error_source.clone(), error_description.clone()];
+
+        // The body.
+        self.blocks.push(Block::new_try(finally_label.clone(), exiting_flag.clone()));
+        self.handlers.push(Some(catch_label.clone()));
+
+        let mut body = self.transform_callable_body(data.body);
+
+        self.handlers.pop();
+
+        transformed_statements.push(Self::on_error(catch_label.clone()));
+        transformed_statements.append(&mut body);
+        transformed_statements.push(Self::goto(finally_label.clone()));
+
+        // Capturing the error.
+        transformed_statements.push(Self::label(catch_label));
+        transformed_statements.append(&mut Self::capture_error(&error_variables));
+        transformed_statements.push(Self::resume(caught_label.clone()));
+        transformed_statements.push(Self::label(caught_label));
+
+        // The catch.
+        if let Some(catch) = data.catch {
+            self.handlers.push(Some(catch_failed_label.clone()));
+
+            let mut body = self.transform_callable_body(catch.body);
+
+            self.handlers.pop();
+
+            transformed_statements.push(Self::on_error(catch_failed_label.clone()));
+            transformed_statements.append(&mut Self::restore_error(&error_variables));
+
+            // NOTE: The error is caught, so it won't be raised again.
+            transformed_statements.push(Self::assign(Self::value(error_number.clone()), Self::value(Token::Number(b"0".to_vec()))));
+
+            if let Some(name) = catch.name {
+                if self.symbols.take_undeclared(&name) {
+                    transformed_statements.push(Self::declare(name.clone(), "ErrObject"));
+                }
+
+                transformed_statements.push(Statement::Assignment(AssignmentStatement {
+                    modifier: Some(Token::Set),
+                    left: Box::new(Self::value(name)),
+                    right: Box::new(Self::value(Self::identifier("Err"))),
+                }));
+            }
+
+            transformed_statements.append(&mut body);
+            transformed_statements.push(Self::goto(finally_label.clone()));
+
+            // NOTE: Errors inside the catch still run the `Finally` before
+            // being raised again.
+            transformed_statements.push(Self::label(catch_failed_label));
+            transformed_statements.append(&mut Self::capture_error(&error_variables));
+            transformed_statements.push(Self::resume(finally_label.clone()));
+        }
+
+        // NOTE: The `Try` block is popped before transforming the `Finally`,
+        // so exiting from it runs the outer ones only.
+        //
+        // NOTE: Blocks are pushed and popped in order, so this is the `Try`.
+        let block = self.blocks.pop().unwrap();
+
+        let mut finally = match data.finally {
+            Some(finally) => self.transform_callable_body(finally),
+            None => vec!(),
+        };
+
+        transformed_statements.push(Self::label(finally_label));
+        transformed_statements.push(self.restore_handler());
+        transformed_statements.append(&mut finally);
+
+        // Raising the error again if it wasn't caught.
+        transformed_statements.push(Self::raise_error(&error_variables));
+
+        // Jumping out of the `Try`, if that's why the `Finally` was reached
+        // (see `transform_jump`).
+        if !block.jumps.is_empty() {
+            transformed_statements.insert(0, Self::declare(exiting_flag.clone(), "Long"));
+            transformed_statements.insert(1, Self::assign(Self::value(exiting_flag.clone()), Self::value(Token::Number(b"0".to_vec()))));
+        }
+
+        for (index, jump) in block.jumps.into_iter().enumerate() {
+            let condition = Statement::Binary(BinaryStatement {
+                left: Box::new(Self::value(exiting_flag.clone())),
+                operator: Token::Assignment,
+                right: Box::new(Self::value(Token::Number((index + 1).to_string().into_bytes()))),
+            });
+
+            let jump = self.transform_jump(jump);
+
+            transformed_statements.push(Self::when(condition, jump));
+        }
+
+        return transformed_statements;
+    }
+
+    // Lowers a `Using` into a `Try` which disposes of the object on every
+    // exit (see `Symbols::get_dispose_method`):
+    //
+    //     Dim conn As ADODB.Connection
+    //     Set conn = New ADODB.Connection
+    //     Try
+    //         <body>
+    //     Finally
+    //         If Not conn Is Nothing Then
+    //             On Error Resume Next
+    //             conn.Close
+    //             On Error GoTo <the previous handler>
+    //         End If
+    //         Set conn = Nothing
+    //     End Try
+    //
+    // NOTE: Disposing of an object may fail (i.e. closing a connection which
+    // was never opened), which mustn't hide the error of the body. That one
+    // is raised again after the `Finally` (see `transform_try`).
+    fn transform_using(&mut self, data: UsingStatement) -> Vec<Statement> {
+        let mut finally = vec!();
+
+        if let Some(method) = self.symbols.get_dispose_method(&data.kind) {
+            let is_nothing = Statement::Binary(BinaryStatement {
+                left: Box::new(Self::value(data.name.clone())),
+                operator: Token::Is,
+                right: Box::new(Self::value(Token::Nothing)),
+            });
+
+            let dispose = Statement::ProcedureCall(ProcedureCallStatement {
+                procedure: Box::new(Statement::Member(MemberStatement {
+                    object: Box::new(Self::value(data.name.clone())),
+                    member: method,
+                })),
+                arguments: vec!(),
+            });
+
+            finally.push(Self::when(
+                Statement::Unary(UnaryStatement {
+                    operator: Token::Not,
+                    value: Box::new(is_nothing),
+                }),
+                vec!(
+                    Statement::OnError(OnErrorStatement {
+                        label: None,
+                    }),
+                    dispose,
+                    self.restore_handler(),
+                ),
+            ));
+        }
+
+        finally.push(Statement::Assignment(AssignmentStatement {
+            modifier: Some(Token::Set),
+            left: Box::new(Self::value(data.name.clone())),
+            right: Box::new(Self::value(Token::Nothing)),
+        }));
+
+        let body = vec!(
+            Statement::Assignment(AssignmentStatement {
+                modifier: Some(Token::Set),
+                left: Box::new(Self::value(data.name.clone())),
+                right: data.value,
+            }),
+            Statement::Try(TryStatement {
+                body: data.body,
+                catch: None,
+                finally: Some(finally),
+            }),
+        );
+
+        let mut transformed_statements = vec!(Self::declaration(Token::Dim, data.name, None, data.kind));
+
+        transformed_statements.append(&mut self.transform_callable_body(body));
+
+        return transformed_statements;
+    }
+
+    // NOTE: Temporaries are declared as local variables, so they're known
+    // to be free of side effects (see `has_side_effects`).
+    fn new_temporary(&mut self, kind: &str) -> Token {
+        self.temporaries += 1;
+
+        let name = Self::identifier(&format!("nabe_temporary_{}", self.temporaries));
+
+        self.symbols.declare_local(&name, &Self::identifier(kind));
+
+        return name;
+    }
+
+    // NOTE: Data types which aren't known are declared as `Variant`s.
+    fn kind_name(kind: &Option<Token>) -> String {
+        return match kind {
+            Some(kind) => String::from_utf8_lossy(&kind.get_lexeme()).to_string(),
+            None => String::from("Variant"),
+        };
+    }
+
+    // NOTE: Quotes are doubled in VB6's strings.
+    fn string(text: &str) -> Statement {
+        return Self::value(Token::String(format!("\"{}\"", text.replace('"', "\"\"")).into_bytes()));
+    }
+
+    fn identifier(name: &str) -> Token {
+        return Token::Identifier(name.as_bytes().to_vec());
+    }
+
+    fn value(value: Token) -> Statement {
+        return Statement::Value(ValueStatement {
+            value: value,
+        });
+    }
+
+    fn error_field(field: &str) -> Statement {
+        return Statement::Member(MemberStatement {
+            object: Box::new(Self::value(Self::identifier("Err"))),
+            member: Self::identifier(field),
+        });
+    }
+
+    // NOTE: Errors are kept in variables (its number, source and description)
+    // because `Resume` clears VB6's `Err`.
+    fn capture_error(variables: &[Token; 3]) -> Vec<Statement> {
+        return variables.iter().zip(["Number", "Source", "Description"])
+            .map(|(variable, field)| Self::assign(Self::value(variable.clone()), Self::error_field(field)))
+            .collect();
+    }
+
+    fn restore_error(variables: &[Token; 3]) -> Vec<Statement> {
+        return variables.iter().zip(["Number", "Source", "Description"])
+            .map(|(variable, field)| Self::assign(Self::error_field(field), Self::value(variable.clone())))
+            .collect();
+    }
+
+    // NOTE: See `capture_error`. There's no error when its number is `0`.
+    fn raise_error(variables: &[Token; 3]) -> Statement {
+        let raise = Statement::ProcedureCall(ProcedureCallStatement {
+            procedure: Box::new(Statement::Member(MemberStatement {
+                object: Box::new(Self::value(Self::identifier("Err"))),
+                member: Self::identifier("Raise"),
+            })),
+            arguments: variables.iter().cloned().map(Self::value).collect(),
+        });
+
+        return Self::when(
+            Statement::Binary(BinaryStatement {
+                left: Box::new(Self::value(variables[0].clone())),
+                operator: Token::NotEqual,
+                right: Box::new(Self::value(Token::Number(b"0".to_vec()))),
+            }),
+            vec!(raise),
+        );
+    }
+
+    fn declare(name: Token, kind: &str) -> Statement {
+        return Statement::Variable(VariableStatement {
+            scope: Token::Dim,
+            name: name,
+            bounds: None,
+            kind: Some(Self::identifier(kind)),
+            new: None,
+            value: None,
+        });
+    }
+
+    // NOTE: Arrays' data types (see `Symbols::get_element_kind`) are declared
+    // as arrays of their elements, which are dynamic unless they have bounds.
+    fn declaration(scope: Token, name: Token, bounds: Option<Vec<ArrayBound>>, kind: Token) -> Statement {
+        let (bounds, kind) = match Symbols::get_element_kind(&kind) {
+            Some(element_kind) => (Some(bounds.unwrap_or(vec!())), element_kind),
+            None => (bounds, kind),
+        };
+
+        return Statement::Variable(VariableStatement {
+            scope: scope,
+            name: name,
+            bounds: bounds,
+            kind: Some(kind),
+            new: None,
+            value: None,
+        });
+    }
+
+    // Lowers an array literal into the assignment of each element, resizing
+    // the array first unless it has a fixed size:
+    //
+    //     ReDim items(0 To 1, 0 To 2)
+    //     items(0, 0) = 1
+    //     items(0, 1) = 2
+    //     ...
+    //
+    // NOTE: The lengths of the literal's dimensions are checked by the
+    // analyzer, along with the declared bounds (when they're known).
+    fn assign_array(symbols: &Symbols, name: Token, bounds: Option<&Vec<ArrayBound>>, literal: ArrayLiteralStatement) -> Vec<Statement> {
+        let shape = match literal.get_shape() {
+            Some(shape) => shape,
+            None => return vec!(),
+        };
+
+        let base = Self::value(Token::Number(symbols.get_base().to_string().into_bytes()));
+
+        let lower_bounds: Vec<Statement> = match bounds {
+            Some(bounds) => bounds.iter().map(|bound| match &bound.lower {
+                Some(lower) => *lower.clone(),
+                None => base.clone(),
+            }).collect(),
+
+            None => shape.iter().map(|_| base.clone()).collect(),
+        };
+
+        let mut statements = vec!();
+
+        // NOTE: Dynamic arrays can't be resized to have no elements, so
+        // they're emptied instead.
+        if bounds.is_none() {
+            if shape.contains(&0) {
+                return vec!(Statement::ProcedureCall(ProcedureCallStatement {
+                    procedure: Box::new(Self::value(Self::identifier("Erase"))),
+                    arguments: vec!(Self::value(name)),
+                }));
+            }
+
+            statements.push(Statement::ReDim(ReDimStatement {
+                preserve: None,
+                name: name.clone(),
+                bounds: shape.iter().zip(&lower_bounds).map(|(length, lower)| ArrayBound {
+                    lower: Some(Box::new(lower.clone())),
+                    upper: Box::new(Self::offset(lower, *length as i64 - 1)),
+                }).collect(),
+                kind: None,
+            }));
+        }
+
+        let element_kind = symbols.find_kind(&name).and_then(|kind| Symbols::get_element_kind(&kind));
+
+        let mut elements = vec!((vec!(), Statement::ArrayLiteral(literal)));
+
+        // NOTE: The nested literals are replaced by their elements (along
+        // with their indexes) until only the elements are left.
+        while let Some((indexes, element)) = elements.pop() {
+            match element {
+                Statement::ArrayLiteral(literal) => {
+                    for (index, element) in literal.elements.into_iter().enumerate().rev() {
+                        let mut element_indexes = indexes.clone();
+
+                        element_indexes.push(Self::offset(&lower_bounds[indexes.len()], index as i64));
+                        elements.push((element_indexes, element));
+                    }
+                },
+
+                element => {
+                    let modifier = match symbols.is_object_assignment(element_kind.as_ref(), &element) {
+                        true => Some(Token::Set),
+                        false => None,
+                    };
+
+                    statements.push(Statement::Assignment(AssignmentStatement {
+                        modifier: modifier,
+                        left: Box::new(Statement::Call(CallStatement {
+                            callee: Box::new(Self::value(name.clone())),
+                            arguments: indexes,
+                        })),
+                        right: Box::new(element),
+                    }));
+                },
+            }
+        }
+
+        return statements;
+    }
+
+    // Returns a bound moved by some elements, which is computed beforehand
+    // when it's a literal.
+    fn offset(bound: &Statement, offset: i64) -> Statement {
+        return match (ArrayBound::get_integer(bound), offset) {
+            (Some(bound), _) => Self::value(Token::Number((bound + offset).to_string().into_bytes())),
+            (None, 0) => bound.clone(),
+
+            (None, _) if offset < 0 => Statement::Binary(BinaryStatement {
+                left: Box::new(bound.clone()),
+                operator: Token::Minus,
+                right: Box::new(Self::value(Token::Number((-offset).to_string().into_bytes()))),
+            }),
+
+            (None, _) => Statement::Binary(BinaryStatement {
+                left: Box::new(bound.clone()),
+                operator: Token::Plus,
+                right: Box::new(Self::value(Token::Number(offset.to_string().into_bytes()))),
+            }),
+        };
+    }
+
+    fn assign(left: Statement, right: Statement) -> Statement {
+        return Statement::Assignment(AssignmentStatement {
+            modifier: None,
+            left: Box::new(left),
+            right: Box::new(right),
+        });
+    }
+
+    fn when(condition: Statement, body: Vec<Statement>) -> Statement {
+        return Statement::If(IfStatement {
+            branches: vec!(Statement::IfBranch(IfBranchStatement {
+                condition: Some(Box::new(condition)),
+                body: body,
+            })),
+        });
+    }
+
+    fn label(name: Token) -> Statement {
+        return Statement::Label(LabelStatement {
+            name: name,
+        });
+    }
+
+    fn goto(label: Token) -> Statement {
+        return Statement::GoTo(GoToStatement {
+            label: label,
+        });
+    }
+
+    fn on_error(label: Token) -> Statement {
+        return Statement::OnError(OnErrorStatement {
+            label: Some(label),
+        });
+    }
+
+    // Goes back to the error handler in effect (see `transform_on_error`),
+    // or disables error handling if there's none.
+    fn restore_handler(&self) -> Statement {
+        return Statement::OnError(OnErrorStatement {
+            label: match self.handlers.last() {
+                Some(handler) => handler.clone(),
+                None => Some(Token::Number(b"0".to_vec())),
+            },
+        });
+    }
+
+    fn resume(label: Token) -> Statement {
+        return Statement::Resume(ResumeStatement {
+            target: Some(label),
+        });
+    }
+
+    fn transform_if(&mut self, data: IfStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+        let mut transformed_branches = vec!();
+        let mut branches = data.branches.into_iter();
+
+        while let Some(statement) = branches.next() {
+            let branch = match statement {
+                Statement::IfBranch(branch) => branch,
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            };
+
+            let condition = match branch.condition {
+                // NOTE: An `ElseIf`'s condition is only evaluated when the
+                // previous ones don't hold, so when it needs statements to be
+                // computed, it (along with the rest of the branches) goes into
+                // an `Else` where they can be placed.
+                Some(condition) if !transformed_branches.is_empty() && self.needs_lowering(&condition) => {
+                    let mut remaining_branches = vec!(Statement::IfBranch(IfBranchStatement {
+                        condition: Some(condition),
+                        body: branch.body,
+                    }));
+
+                    remaining_branches.extend(branches.by_ref());
+
+                    transformed_branches.push(Statement::IfBranch(IfBranchStatement {
+                        condition: None,
+                        body: self.transform_if(IfStatement {
+                            branches: remaining_branches,
+                        }),
+                    }));
+
+                    break;
+                },
+
+                Some(condition) => Some(Box::new(self.transform_expression(*condition, &mut transformed_statements))),
+                None => None,
+            };
+
+            transformed_branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: condition,
+                body: self.transform_callable_body(branch.body),
+            }));
+        }
+
+        transformed_statements.push(Statement::If(IfStatement {
+            branches: transformed_branches,
+        }));
+
+        return transformed_statements;
+    }
+
+    // Lowers a `Match` into an `If` whose branches test the arms' patterns
+    // against the value (see `match_condition`), binding it when they ask
+    // for it:
+    //
+    //     If TypeOf shape Is Circle Then      ' Case TypeOf Circle As c
+    //         Dim c As Circle
+    //         Set c = shape
+    //         <arm's body>
+    //     ElseIf shape Is Nothing Then        ' Case Nothing
+    //         <arm's body>
+    //     End If
+    fn transform_match(&mut self, data: MatchStatement) -> Vec<Statement> {
+        let mut statements = vec!();
+        let value = self.match_value(*data.value, &data.arms, &mut statements);
+        let mut branches = vec!();
+
+        for arm in data.arms {
+            let mut body = vec!();
+
+            if let Some((kind, name)) = arm.get_binding() {
+                body.push(Self::declaration(Token::Dim, name.clone(), None, kind.clone()));
+                body.push(Statement::Assignment(AssignmentStatement {
+                    modifier: Some(Token::Set),
+                    left: Box::new(Self::value(name.clone())),
+                    right: Box::new(value.clone()),
+                }));
+            }
+
+            let condition = self.match_condition(&value, &arm);
+
+            body.extend(arm.body);
+
+            branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: condition,
+                body: body,
+            }));
+        }
+
+        statements.push(Statement::If(IfStatement {
+            branches: branches,
+        }));
+
+        return self.transform_callable_body(statements);
+    }
+
+    // NOTE: The value is compared by each pattern, so it's computed once when
+    // it may have side effects. Bound values are always kept in a variable,
+    // which replaces them in the guards (see `replace_binding`).
+    fn match_value(&mut self, value: Statement, arms: &Vec<MatchArm>, statements: &mut Vec<Statement>) -> Statement {
+        let is_variable = matches!(&value, Statement::Value(ValueStatement { value: Token::Identifier(_) }));
+        let is_bound = arms.iter().any(|arm| arm.get_binding().is_some());
+
+        if !self.has_side_effects(&value) && !self.needs_lowering(&value) && (is_variable || !is_bound) {
+            return value;
+        }
+
+        // NOTE: Only objects can be matched by `TypeOf` and `Nothing`.
+        let is_object = self.symbols.is_object(&value) || arms.iter()
+            .flat_map(|arm| arm.patterns.iter().flatten())
+            .any(|pattern| matches!(pattern, Pattern::TypeOf(..) | Pattern::Nothing));
+
+        let kind = match (self.symbols.infer_kind(&value), is_object) {
+            (Some(kind), _) if self.symbols.is_object_kind(&kind) => kind,
+            (_, true) => Self::identifier("Object"),
+            (Some(kind), false) => kind,
+            (None, false) => Self::identifier("Variant"),
+        };
+
+        let modifier = match self.symbols.is_object_kind(&kind) {
+            true => Some(Token::Set),
+            false => None,
+        };
+
+        let kind = String::from_utf8_lossy(&kind.get_lexeme()).to_string();
+        let name = self.new_temporary(&kind);
+
+        statements.push(Self::declare(name.clone(), &kind));
+        statements.push(Statement::Assignment(AssignmentStatement {
+            modifier: modifier,
+            left: Box::new(Self::value(name.clone())),
+            right: Box::new(value),
+        }));
+
+        return Self::value(name);
+    }
+
+    // Returns the condition under which an arm matches, which is `None` for
+    // `Case Else`.
+    //
+    // NOTE: The patterns after the one which matches are only evaluated when
+    // they have no side effects, and so is the guard when none does.
+    fn match_condition(&mut self, value: &Statement, arm: &MatchArm) -> Option<Box<Statement>> {
+        let conditions: Vec<Statement> = arm.patterns.as_ref()?.iter()
+            .map(|pattern| self.pattern_condition(value, pattern))
+            .collect();
+
+        let operator = match conditions.iter().any(|condition| self.has_side_effects(condition)) {
+            true => Token::OrElse,
+            false => Token::Or,
+        };
+
+        // NOTE: There's always a pattern (see `Parser::parse_match_arms`).
+        let mut conditions = conditions.into_iter();
+        let mut condition = conditions.next().unwrap();
+
+        for next_condition in conditions {
+            condition = Statement::Binary(BinaryStatement {
+                left: Box::new(condition),
+                operator: operator.clone(),
+                right: Box::new(next_condition),
+            });
+        }
+
+        if let Some(guard) = &arm.guard {
+            let mut guard = *guard.clone();
+
+            self.replace_binding(arm, value, &mut guard);
+
+            condition = Statement::Binary(BinaryStatement {
+                left: Box::new(condition),
+                operator: Token::AndAlso,
+                right: Box::new(guard),
+            });
+        }
+
+        return Some(Box::new(condition));
+    }
+
+    fn pattern_condition(&self, value: &Statement, pattern: &Pattern) -> Statement {
+        let compare = |operator: Token, pattern: &Statement| Statement::Binary(BinaryStatement {
+            left: Box::new(value.clone()),
+            operator: operator,
+            right: Box::new(pattern.clone()),
+        });
+
+        return match pattern {
+            Pattern::Value(pattern) if self.symbols.is_object(pattern) => compare(Token::Is, pattern),
+            Pattern::Value(pattern) => compare(Token::Assignment, pattern),
+
+            Pattern::Range(start, end) => Statement::Binary(BinaryStatement {
+                left: Box::new(compare(Token::GreaterOrEqual, start)),
+                operator: Token::And,
+                right: Box::new(compare(Token::LessOrEqual, end)),
+            }),
+
+            Pattern::TypeOf(kind, _) => Statement::TypeOf(TypeOfStatement {
+                value: Box::new(value.clone()),
+                kind: kind.clone(),
+            }),
+
+            Pattern::Nothing => compare(Token::Is, &Self::value(Token::Nothing)),
+        };
+    }
+
+    // Replaces the variable bound by an arm with the matched value, which is
+    // kept in a variable (see `match_value`).
+    fn replace_binding(&self, arm: &MatchArm, value: &Statement, statement: &mut Statement) {
+        if let (Some((_, name)), Statement::Value(value)) = (arm.get_binding(), value) {
+            Scoper::new(&self.symbols).replace(statement, name, &value.value);
+        }
+    }
+
+    // NOTE: The bounds and the step are evaluated once, before the loop.
+    fn transform_for(&mut self, data: ForStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        let mut operands = vec!(*data.start, *data.end);
+
+        if let Some(step) = data.step {
+            operands.push(*step);
+        }
+
+        let mut operands = self.transform_operands(operands, &mut transformed_statements).into_iter();
+
+        // NOTE: There are as many operands as there were before.
+        let start = operands.next().unwrap();
+        let end = operands.next().unwrap();
+        let step = operands.next().map(Box::new);
+
+        transformed_statements.push(Statement::For(ForStatement {
+            counter: data.counter,
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step,
+            body: self.transform_loop_body(Token::For, data.body),
+        }));
+
+        return transformed_statements;
+    }
+
+    // Lowers `For i In start ..< end Step step` into a `For` whose end is
+    // computed once, before the loop:
+    //
+    //     nabe_temporary_1 = end
+    //     For i = start To nabe_temporary_1 - 1 Step step
+    //
+    // When the step isn't a number, its sign is only known once it's
+    // computed, so the end is adjusted before the loop:
+    //
+    //     nabe_temporary_1 = end
+    //     If Sgn(step) < 0 Then
+    //         nabe_temporary_1 = nabe_temporary_1 + 1
+    //     Else
+    //         nabe_temporary_1 = nabe_temporary_1 - 1
+    //     End If
+    //     For i = start To nabe_temporary_1 Step step
+    //
+    // NOTE: Exclusive ends are meant for whole numbers.
+    fn transform_for_range(&mut self, data: ForRangeStatement) -> Vec<Statement> {
+        let mut statements = vec!();
+
+        let kind = match &self.symbols.find_kind(&data.counter) {
+            Some(kind) => String::from_utf8_lossy(&kind.get_lexeme()).to_string(),
+            None => String::from("Variant"),
+        };
+
+        let is_exclusive = data.operator == Token::ExclusiveRange;
+        let step_number = data.step.as_deref().and_then(ArrayBound::get_integer);
+        let is_step_unknown = is_exclusive && data.step.is_some() && step_number.is_none();
+
+        // NOTE: The start is still computed before the end.
+        let start = match self.has_side_effects(&data.start) {
+            true => {
+                let name = self.new_temporary(&kind);
+
+                statements.push(Self::declare(name.clone(), &kind));
+                statements.push(Self::assign(Self::value(name.clone()), *data.start));
+
+                Self::value(name)
+            },
+
+            false => *data.start,
+        };
+
+        let end = match ArrayBound::get_integer(&data.end) {
+            Some(_) if !is_step_unknown => *data.end,
+
+            _ => {
+                let name = self.new_temporary(&kind);
+
+                statements.push(Self::declare(name.clone(), &kind));
+                statements.push(Self::assign(Self::value(name.clone()), *data.end));
+
+                Self::value(name)
+            },
+        };
+
+        // NOTE: The step is used twice, so it's computed once (after the end).
+        let step = match data.step {
+            Some(step) if is_step_unknown && self.has_side_effects(&step) => {
+                let name = self.new_temporary(&kind);
+
+                statements.push(Self::declare(name.clone(), &kind));
+                statements.push(Self::assign(Self::value(name.clone()), *step));
+
+                Some(Box::new(Self::value(name)))
+            },
+
+            step => step,
+        };
+
+        let end = match (is_exclusive, step_number, &step) {
+            (true, _, Some(step)) if is_step_unknown => {
+                let is_negative = Statement::Binary(BinaryStatement {
+                    left: Box::new(Statement::Call(CallStatement {
+                        callee: Box::new(Self::value(Self::identifier("Sgn"))),
+                        arguments: vec!((**step).clone()),
+                    })),
+                    operator: Token::Less,
+                    right: Box::new(Self::value(Token::Number(b"0".to_vec()))),
+                });
+
+                statements.push(Statement::If(IfStatement {
+                    branches: vec!(
+                        Statement::IfBranch(IfBranchStatement {
+                            condition: Some(Box::new(is_negative)),
+                            body: vec!(Self::assign(end.clone(), Self::offset(&end, 1))),
+                        }),
+                        Statement::IfBranch(IfBranchStatement {
+                            condition: None,
+                            body: vec!(Self::assign(end.clone(), Self::offset(&end, -1))),
+                        }),
+                    ),
+                }));
+
+                end
+            },
+
+            (true, Some(step), _) if step < 0 => Self::offset(&end, 1),
+            (true, _, _) => Self::offset(&end, -1),
+            (false, _, _) => end,
+        };
+
+        statements.push(Statement::For(ForStatement {
+            counter: data.counter,
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step,
+            body: data.body,
+        }));
+
+        return self.transform_callable_body(statements);
+    }
+
+    // Lowers a `For Each` over an array into a `For` over its indexes, so that
+    // the element doesn't have to be a `Variant` (as VB6 needs):
+    //
+    //     Dim item As Customer
+    //     For nabe_temporary_1 = LBound(items) To UBound(items)
+    //     Set item = items(nabe_temporary_1)
+    //     <body>
+    //     Next
+    //
+    // `For Each` over anything else is kept, but typed elements are assigned
+    // from a `Variant` one:
+    //
+    //     Dim item As Customer
+    //     For Each nabe_temporary_1 In customers
+    //     Set item = nabe_temporary_1
+    //     <body>
+    //     Next
+    //
+    // TODO: Arrays with more than one dimension.
+    fn transform_for_each(&mut self, data: ForEachStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        let collection_kind = self.symbols.infer_kind(&data.collection);
+        let element_kind = collection_kind.as_ref().and_then(Symbols::get_element_kind);
+
+        // NOTE: Untyped elements of anything but arrays need no lowering.
+        if data.kind.is_none() && element_kind.is_none() {
+            let collection = self.transform_expression(*data.collection, &mut transformed_statements);
+
+            transformed_statements.push(Statement::ForEach(ForEachStatement {
+                element: data.element,
+                kind: None,
+                collection: Box::new(collection),
+                body: self.transform_loop_body(Token::For, data.body),
+            }));
+
+            return transformed_statements;
+        }
+
+        if let Some(kind) = &data.kind {
+            transformed_statements.push(Self::declaration(Token::Dim, data.element.clone(), None, kind.clone()));
+        }
+
+        // NOTE: Untyped elements are declared somewhere else, if at all.
+        let kind = match &data.kind {
+            Some(kind) => Some(kind.clone()),
+            None => self.symbols.find_kind(&data.element).or(element_kind.clone()),
+        };
+
+        let collection = self.transform_expression(*data.collection, &mut transformed_statements);
+
+        let (collection_kind, Some(_)) = (collection_kind, element_kind) else {
+            let variant = self.new_temporary("Variant");
+
+            transformed_statements.push(Self::declare(variant.clone(), "Variant"));
+
+            let mut body = vec!(Statement::Assignment(AssignmentStatement {
+                modifier: self.get_modifier(kind.as_ref(), &Self::value(variant.clone())),
+                left: Box::new(Self::value(data.element)),
+                right: Box::new(Self::value(variant.clone())),
+            }));
+
+            body.extend(data.body);
+
+            transformed_statements.push(Statement::ForEach(ForEachStatement {
+                element: variant,
+                kind: None,
+                collection: Box::new(collection),
+                body: self.transform_loop_body(Token::For, body),
+            }));
+
+            return transformed_statements;
+        };
+
+        // NOTE: The array is only evaluated once, like VB6 does.
+        let collection = match self.has_side_effects(&collection) {
+            true => {
+                // NOTE: Arrays always have a known data type here.
+                let collection_kind = collection_kind.unwrap();
+                let array = self.new_temporary(&String::from_utf8_lossy(&collection_kind.get_lexeme()));
+
+                transformed_statements.push(Self::declaration(Token::Dim, array.clone(), None, collection_kind));
+                transformed_statements.push(Self::assign(Self::value(array.clone()), collection));
+
+                Self::value(array)
+            },
+
+            false => collection,
+        };
+
+        let index = self.new_temporary("Long");
+
+        transformed_statements.push(Self::declare(index.clone(), "Long"));
+
+        let element = Statement::Call(CallStatement {
+            callee: Box::new(collection.clone()),
+            arguments: vec!(Self::value(index.clone())),
+        });
+
+        let mut body = vec!(Statement::Assignment(AssignmentStatement {
+            modifier: self.get_modifier(kind.as_ref(), &element),
+            left: Box::new(Self::value(data.element)),
+            right: Box::new(element),
+        }));
+
+        body.extend(data.body);
+
+        let bound = |function: &str| Statement::Call(CallStatement {
+            callee: Box::new(Self::value(Self::identifier(function))),
+            arguments: vec!(collection.clone()),
+        });
+
+        transformed_statements.append(&mut self.transform_for(ForStatement {
+            counter: index,
+            start: Box::new(bound("LBound")),
+            end: Box::new(bound("UBound")),
+            step: None,
+            body: body,
+        }));
+
+        return transformed_statements;
+    }
+
+    // NOTE: Conditions which need statements to be computed are checked inside
+    // the loop's body instead, exiting the loop when they don't hold.
+    fn transform_do(&mut self, data: DoStatement) -> Vec<Statement> {
+        let exit = Statement::Exit(ExitStatement {
+            block: Token::Do,
+        });
+
+        let (header, mut header_statements) = match data.header {
+            Some(header) if self.needs_lowering(&header.value) => (None, self.transform_loop_check(header, exit.clone())),
+            header => (header, vec!()),
+        };
+
+        let (footer, mut footer_statements) = match data.footer {
+            Some(footer) if self.needs_lowering(&footer.value) => (None, self.transform_loop_check(footer, exit)),
+            footer => (footer, vec!()),
+        };
+
+        // NOTE: The footer's check goes after the label `Continue` jumps to,
+        // so it's run too.
+        let mut body = self.transform_loop_body(Token::Do, data.body);
+
+        header_statements.append(&mut body);
+        header_statements.append(&mut footer_statements);
+
+        return vec!(Statement::Do(DoStatement {
+            header: header,
+            body: header_statements,
+            footer: footer,
+        }));
+    }
+
+    // NOTE: See `transform_do`. Since `While` loops can't be exited, a `GoTo`
+    // to a label after them is used instead.
+    fn transform_while(&mut self, data: WhileStatement) -> Vec<Statement> {
+        if !self.needs_lowering(&data.condition) {
+            return vec!(Statement::While(WhileStatement {
+                condition: data.condition,
+                body: self.transform_loop_body(Token::While, data.body),
+            }));
+        }
+
+        self.labels += 1;
+
+        let end_label = Self::identifier(&format!("nabe_end_{}", self.labels));
+
+        let condition = LoopCondition {
+            kind: Token::While,
+            value: data.condition,
+        };
+
+        let mut body = self.transform_loop_check(condition, Self::goto(end_label.clone()));
+
+        body.append(&mut self.transform_loop_body(Token::While, data.body));
+
+        return vec!(
+            Statement::While(WhileStatement {
+                condition: Box::new(Self::value(Self::identifier("True"))),
+                body: body,
+            }),
+            Self::label(end_label),
+        );
+    }
+
+    // Computes a loop's condition, running `exit` when it says the loop is
+    // over.
+    fn transform_loop_check(&mut self, condition: LoopCondition, exit: Statement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        let value = self.transform_expression(*condition.value, &mut transformed_statements);
+
+        // NOTE: `Not` works bitwise in VB6 (so `Not 2` is `True`), thus an
+        // empty branch is used to negate the condition.
+        let check = match condition.kind {
+            Token::Until => vec!(
+                Statement::IfBranch(IfBranchStatement {
+                    condition: Some(Box::new(value)),
+                    body: vec!(exit),
+                }),
+            ),
+
+            _ => vec!(
+                Statement::IfBranch(IfBranchStatement {
+                    condition: Some(Box::new(value)),
+                    body: vec!(),
+                }),
+                Statement::IfBranch(IfBranchStatement {
+                    condition: None,
+                    body: vec!(exit),
+                }),
+            ),
+        };
+
+        transformed_statements.push(Statement::If(IfStatement {
+            branches: check,
+        }));
+
+        return transformed_statements;
+    }
+
+    fn transform_loop_body(&mut self, kind: Token, body: Vec<Statement>) -> Vec<Statement> {
+        self.blocks.push(Block::new_loop(kind));
+
+        let mut transformed_body = self.transform_callable_body(body);
+
+        // NOTE: The loop's block is always the last one once its body has
+        // been transformed.
+        let block = self.blocks.pop().unwrap();
+
+        // NOTE: Jumping to the end of the body runs whatever the loop does
+        // before the next iteration (i.e. incrementing the counter of a `For`
+        // or checking the condition of a `Loop While`).
+        if let Some(label) = block.continue_label {
+            transformed_body.push(Statement::Label(LabelStatement {
+                name: label,
+            }));
+        }
+
+        return transformed_body;
+    }
+
+    // Lowers the loop of a label, placing a label after it when it's exited
+    // (see `transform_loop_exit`):
+    //
+    //     For ...
+    //         For ...
+    //             GoTo nabe_exit_1
+    //         Next
+    //     Next
+    //     nabe_exit_1:
+    //
+    // NOTE: The loop's label isn't kept, so the same one can be used by many
+    // loops of a procedure.
+    fn transform_labelled_loop(&mut self, data: LabelledLoopStatement) -> Vec<Statement> {
+        self.blocks.push(Block::new_label(data.label));
+
+        let mut transformed_statements = self.transform_callable_body(data.body);
+
+        // NOTE: See `transform_loop_body`.
+        let block = self.blocks.pop().unwrap();
+
+        if let Some(label) = block.exit_label {
+            transformed_statements.push(Self::label(label));
+        }
+
+        return transformed_statements;
+    }
+
+    // Lowers `Exit outer` into a `GoTo` to the end of the loop labelled
+    // `outer`. `Exit For` and `Exit Do` are kept unless they leave a `Try`.
+    fn transform_loop_exit(&mut self, data: ExitStatement) -> Vec<Statement> {
+        // NOTE: Unlike labels, these are checked by VB6 itself.
+        if !matches!(data.block, Token::Identifier(_)) && self.find_loop(&data.block).is_none() {
+            return vec!(Statement::Exit(data));
+        }
+
+        return self.transform_jump(Jump::Loop(data.block));
+    }
+
+    // Finds the block of the innermost loop of a kind, or the one of a label.
+    //
+    // NOTE: Loops can't be continued (or exited) from another procedure.
+    fn find_loop(&self, kind: &Token) -> Option<usize> {
+        return self.blocks.iter().enumerate().rev()
+            .take_while(|(_, block)| !block.is_procedure())
+            .find(|(_, block)| block.is_kind(kind))
+            .map(|(index, _)| index);
+    }
+
+    // Lowers `Continue` into a `GoTo` to the end of the innermost loop of the
+    // same kind (or the labelled one).
+    fn transform_continue(&mut self, data: ContinueStatement) -> Vec<Statement> {
+        return self.transform_jump(Jump::Continue(data.block));
+    }
+
+    // Lowers `left += right` into `left = left + right`.
+    fn transform_assignment(&mut self, data: AssignmentStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        // NOTE: Arrays are assigned one element at a time, after resizing
+        // them.
+        if let (Statement::ArrayLiteral(_), Statement::Value(ValueStatement { value: name })) = (&*data.right, &*data.left) {
+            if data.modifier == Some(Token::Let) && self.symbols.take_undeclared(name) {
+                let kind = self.symbols.find_kind(name).unwrap_or(Self::identifier("Variant"));
+
+                transformed_statements.push(Self::declaration(Token::Dim, name.clone(), None, kind));
+            }
+
+            let assignment = match *data.right {
+                Statement::ArrayLiteral(literal) => Self::assign_array(&self.symbols, name.clone(), None, literal),
+
+                // TODO: Is it correct to `panic`?
+                //
+                // TODO: Add a message?
+                _ => unreachable!(),
+            };
+
+            transformed_statements.append(&mut self.transform_callable_body(assignment));
+
+            return transformed_statements;
+        }
+
+        let right = self.transform_expression(*data.right, &mut transformed_statements);
+
+        // NOTE: `Let` declares the variable the first time it's assigned when
+        // it isn't declared anywhere else (see `Symbols::declare_procedure`).
+        let declared = match (&data.modifier, &*data.left) {
+            (Some(Token::Let), Statement::Value(ValueStatement { value: name })) if self.symbols.take_undeclared(name) => {
+                Some(name.clone())
+            },
+
+            _ => None,
+        };
+
+        let modifier = match declared {
+            Some(name) => {
+                let kind = self.symbols.find_kind(&name).unwrap_or(Self::identifier("Variant"));
+
+                transformed_statements.push(Self::declaration(Token::Dim, name, None, kind.clone()));
+
+                self.get_modifier(Some(&kind), &right)
+            },
+
+            None => data.modifier,
+        };
+
+        transformed_statements.push(Statement::Assignment(AssignmentStatement {
+            modifier: modifier,
+            left: data.left,
+            right: Box::new(right),
+        }));
+
+        return transformed_statements;
+    }
+
+    fn transform_procedure_call(&mut self, data: ProcedureCallStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        let arguments = self.transform_operands(data.arguments, &mut transformed_statements);
+
+        transformed_statements.push(Statement::ProcedureCall(ProcedureCallStatement {
+            procedure: data.procedure,
+            arguments: arguments,
+        }));
+
+        return transformed_statements;
+    }
+
+    fn transform_raise_event(&mut self, data: RaiseEventStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        let arguments = self.transform_operands(data.arguments, &mut transformed_statements);
+
+        transformed_statements.push(Statement::RaiseEvent(RaiseEventStatement {
+            name: data.name,
+            arguments: arguments,
+        }));
+
+        return transformed_statements;
+    }
+
+    fn transform_compound_assignment(&mut self, data: CompoundAssignmentStatement) -> Vec<Statement> {
+        let mut transformed_statements = vec!();
+
+        // NOTE: The left side is written twice once lowered, so its side
+        // effects (i.e. an array index such as `items(NextIndex())`) must be
+        // taken out of it to happen only once.
+        let left = self.hoist_arguments(*data.left, &mut transformed_statements);
+
+        let right = self.transform_expression(*data.right, &mut transformed_statements);
+
+        // NOTE: `get_compound_operator` can't fail for compound operators.
+        let operator = data.operator.get_compound_operator().unwrap();
+
+        transformed_statements.push(Statement::Assignment(AssignmentStatement {
+            modifier: None,
+            left: Box::new(left.clone()),
+            right: Box::new(Statement::Binary(BinaryStatement {
+                left: Box::new(left),
+                operator: operator,
+                right: Box::new(right),
+            })),
+        }));
+
+        return transformed_statements;
+    }
+
+    // Lowers what VB6 lacks inside an expression (i.e. `AndAlso` or `If()`),
+    // pushing the statements which compute it to `statements`.
+    fn transform_expression(&mut self, expression: Statement, statements: &mut Vec<Statement>) -> Statement {
+        return match expression {
+            Statement::Binary(data) if data.operator == Token::AndAlso || data.operator == Token::OrElse => {
+                self.transform_short_circuit(data, statements)
+            },
+
+            Statement::Binary(data) => {
+                let mut operands = self.transform_operands(vec!(*data.left, *data.right), statements);
+
+                // NOTE: There are as many operands as there were before.
+                let right = operands.pop().unwrap();
+                let left = operands.pop().unwrap();
+
+                Statement::Binary(BinaryStatement {
+                    left: Box::new(left),
+                    operator: data.operator,
+                    right: Box::new(right),
+                })
+            },
+
+            Statement::Unary(data) => Statement::Unary(UnaryStatement {
+                operator: data.operator,
+                value: Box::new(self.transform_expression(*data.value, statements)),
+            }),
+
+            Statement::Conditional(data) => self.transform_conditional(data, statements),
+            Statement::Match(data) => self.transform_match_expression(data, statements),
+
+            Statement::TypeOf(data) => Statement::TypeOf(TypeOfStatement {
+                value: Box::new(self.transform_expression(*data.value, statements)),
+                kind: data.kind,
+            }),
+
+            Statement::InterpolatedString(data) => {
+                let concatenation = self.transform_interpolated_string(data);
+
+                self.transform_expression(concatenation, statements)
+            },
+
+            Statement::Value(ValueStatement { value: Token::EscapedString(lexeme) }) => Self::transform_string(&lexeme, true),
+            Statement::Value(ValueStatement { value: Token::RawString(lexeme) }) => Self::transform_string(&lexeme, false),
+
+            // TODO: The callee is evaluated before the arguments, but it's not
+            // hoisted when they need statements to be computed.
+            Statement::Call(data) => Statement::Call(CallStatement {
+                callee: Box::new(self.transform_expression(*data.callee, statements)),
+                arguments: self.transform_operands(data.arguments, statements),
+            }),
+
+            Statement::Member(data) => Statement::Member(MemberStatement {
+                object: Box::new(self.transform_expression(*data.object, statements)),
+                member: data.member,
+            }),
+
+            // No transformation aplicable.
+            _ => expression,
+        };
+    }
+
+    // Lowers an interpolated string into a concatenation of its parts, where
+    // the embedded expressions are converted with `CStr` (or `Format$`, when
+    // they have a format):
+    //
+    //     "Total: " & Format$(amount, "0.00") & " for " & CStr(count)
+    fn transform_interpolated_string(&mut self, data: InterpolatedStringStatement) -> Statement {
+        let mut parts = vec!();
+
+        for part in data.parts {
+            let part = match part {
+                Statement::Interpolation(InterpolationStatement { value, format: Some(format) }) => {
+                    Statement::Call(CallStatement {
+                        callee: Box::new(Self::value(Self::identifier("Format$"))),
+                        arguments: vec!(*value, Self::value(format)),
+                    })
+                },
+
+                // NOTE: Strings don't need to be converted.
+                Statement::Interpolation(InterpolationStatement { value, format: None }) => {
+                    match self.symbols.infer_kind(&value) {
+                        Some(kind) if kind.get_lexeme().eq_ignore_ascii_case(b"string") => *value,
+
+                        _ => Statement::Call(CallStatement {
+                            callee: Box::new(Self::value(Self::identifier("CStr"))),
+                            arguments: vec!(*value),
+                        }),
+                    }
+                },
+
+                _ => part,
+            };
+
+            parts.push(part);
+        }
+
+        return Self::concatenate(parts);
+    }
+
+    // Lowers Nabe's strings into a concatenation of VB6's strings and the
+    // characters they can't hold (i.e. line breaks or non-ASCII characters):
+    //
+    //     "Name:" & vbTab & "Jos" & ChrW$(&HE9&) & vbCrLf
+    //
+    // NOTE: Unknown escape sequences are kept as they are.
+    fn transform_string(characters: &[u8], has_escapes: bool) -> Statement {
+        let mut parts = vec!();
+        let mut literal = vec!();
+        let mut push_part = |literal: &mut Vec<u8>, part: Option<Statement>| {
+            if !literal.is_empty() {
+                parts.push(Self::value(Token::Identifier([b"\"", literal.as_slice(), b"\""].concat())));
+                literal.clear();
+            }
+
+            if let Some(part) = part {
+                parts.push(part);
+            }
+        };
+
+        let character_code = |function: &str, code: u32| Statement::Call(CallStatement {
+            callee: Box::new(Self::value(Self::identifier(function))),
+            arguments: vec!(Self::value(Token::Number(format!("&H{:X}&", code).into_bytes()))),
+        });
+
+        // NOTE: Raw strings' line breaks are written as `\n` too. Bytes which
+        // aren't UTF-8 are kept as they are, since they're (probably) written
+        // in the system's code page already.
+        for chunk in characters.utf8_chunks() {
+            let mut characters = chunk.valid().chars().peekable();
+
+            while let Some(character) = characters.next() {
+                let escaped = match (character, has_escapes) {
+                    ('\\', true) => characters.next(),
+                    _ => None,
+                };
+
+                match (character, escaped) {
+                    ('\n', _) | (_, Some('n')) => push_part(&mut literal, Some(Self::value(Self::identifier("vbCrLf")))),
+                    (_, Some('r')) => push_part(&mut literal, Some(Self::value(Self::identifier("vbCr")))),
+                    (_, Some('t')) => push_part(&mut literal, Some(Self::value(Self::identifier("vbTab")))),
+                    (_, Some('0')) => push_part(&mut literal, Some(Self::value(Self::identifier("vbNullChar")))),
+                    (_, Some('\\')) => literal.push(b'\\'),
+
+                    // NOTE: Quotes are doubled in VB6's strings.
+                    ('"', _) | (_, Some('"')) => literal.extend(b"\"\""),
+
+                    (_, Some(kind @ ('x' | 'u'))) => {
+                        let digits = match kind {
+                            'x' => 2,
+                            _ => 4,
+                        };
+
+                        let hexadecimal = characters.clone().take(digits).collect::<String>();
+
+                        // NOTE: `from_str_radix` accepts a sign too.
+                        let is_valid = hexadecimal.len() == digits
+                            && hexadecimal.chars().all(|character| character.is_ascii_hexdigit());
+
+                        match u32::from_str_radix(&hexadecimal, 16) {
+                            Ok(code) if is_valid => {
+                                for _ in 0..digits { characters.next(); }
+
+                                let function = match kind {
+                                    'x' => "Chr$",
+                                    _ => "ChrW$",
+                                };
+
+                                push_part(&mut literal, Some(character_code(function, code)));
+                            },
+
+                            _ => literal.extend(format!("\\{}", kind).into_bytes()),
+                        }
+                    },
+
+                    (_, Some(other)) => literal.extend(format!("\\{}", other).into_bytes()),
+
+                    // NOTE: VB6's source code isn't Unicode, so characters outside
+                    // of ASCII are written by their code (characters outside of
+                    // the BMP need two, since VB6's strings are UTF-16).
+                    (character, None) if !character.is_ascii() => {
+                        let mut units = [0u16; 2];
+
+                        for unit in character.encode_utf16(&mut units) {
+                            push_part(&mut literal, Some(character_code("ChrW$", *unit as u32)));
+                        }
+                    },
+
+                    (character, None) => literal.push(character as u8),
+                }
+            }
+
+            literal.extend(chunk.invalid());
+        }
+
+        push_part(&mut literal, None);
+
+        return Self::concatenate(parts);
+    }
+
+    // Joins some strings with `&`, where no strings at all is `""`.
+    fn concatenate(parts: Vec<Statement>) -> Statement {
+        let mut concatenation: Option<Statement> = None;
+
+        for part in parts {
+            concatenation = match concatenation {
+                Some(left) => Some(Statement::Binary(BinaryStatement {
+                    left: Box::new(left),
+                    operator: Token::Ampersand,
+                    right: Box::new(part),
+                })),
+
+                None => Some(part),
+            };
+        }
+
+        return match concatenation {
+            Some(concatenation) => concatenation,
+            None => Self::value(Self::identifier("\"\"")),
+        };
+    }
+
+    // NOTE: Operands are evaluated from left to right, so when one of them
+    // needs statements to be computed, the ones before it which may have side
+    // effects are computed before those statements too.
+    fn transform_operands(&mut self, operands: Vec<Statement>, statements: &mut Vec<Statement>) -> Vec<Statement> {
+        let mut transformed_operands: Vec<Statement> = vec!();
+
+        for operand in operands {
+            let mut operand_statements = vec!();
+            let transformed_operand = self.transform_expression(operand, &mut operand_statements);
+
+            if !operand_statements.is_empty() {
+                for previous_operand in transformed_operands.iter_mut() {
+                    if self.has_side_effects(previous_operand) {
+                        *previous_operand = self.hoist(previous_operand.clone(), statements);
+                    }
+                }
+
+                statements.append(&mut operand_statements);
+            }
+
+            transformed_operands.push(transformed_operand);
+        }
+
+        return transformed_operands;
+    }
+
+    // Lowers `left AndAlso right` (and `OrElse`) into a `Boolean` temporary
+    // computed by an `If`, so `right` is only evaluated when needed:
+    //
+    //     If left Then nabe_temporary_1 = right Else nabe_temporary_1 = False
+    fn transform_short_circuit(&mut self, data: BinaryStatement, statements: &mut Vec<Statement>) -> Statement {
+        let left = self.transform_expression(*data.left, statements);
+
+        let mut right_statements = vec!();
+        let right = self.transform_expression(*data.right, &mut right_statements);
+
+        let name = self.new_temporary("Boolean");
+
+        right_statements.push(Self::assign(Self::value(name.clone()), right));
+
+        let (then_body, else_body) = match data.operator {
+            Token::AndAlso => (right_statements, vec!(Self::assign(Self::value(name.clone()), Self::value(Self::identifier("False"))))),
+            _ => (vec!(Self::assign(Self::value(name.clone()), Self::value(Self::identifier("True")))), right_statements),
+        };
+
+        statements.push(Self::declare(name.clone(), "Boolean"));
+        statements.push(Statement::If(IfStatement {
+            branches: vec!(
+                Statement::IfBranch(IfBranchStatement {
+                    condition: Some(Box::new(left)),
+                    body: then_body,
+                }),
+                Statement::IfBranch(IfBranchStatement {
+                    condition: None,
+                    body: else_body,
+                }),
+            ),
+        }));
+
+        return Self::value(name);
+    }
+
+    // Lowers `If(condition, a, b)` into a temporary computed by an `If`, so
+    // only the chosen value is evaluated:
+    //
+    //     If condition Then nabe_temporary_1 = a Else nabe_temporary_1 = b
+    fn transform_conditional(&mut self, data: ConditionalStatement, statements: &mut Vec<Statement>) -> Statement {
+        let condition = self.transform_expression(*data.condition, statements);
+
+        let mut when_true_statements = vec!();
+        let when_true = self.transform_expression(*data.when_true, &mut when_true_statements);
+
+        let mut when_false_statements = vec!();
+        let when_false = self.transform_expression(*data.when_false, &mut when_false_statements);
+
+        // NOTE: The temporary has the data type of both values (see
+        // `Symbols::infer_common_kind`), so it's later assigned with `Set`
+        // only when they're objects (i.e. when returned).
+        let kind = self.symbols.infer_common_kind(&[&when_true, &when_false]);
+        let name = self.new_temporary(&Self::kind_name(&kind));
+
+        let mut branches = vec!();
+
+        for (condition, value, mut body) in [
+            (Some(Box::new(condition)), when_true, when_true_statements),
+            (None, when_false, when_false_statements),
+        ] {
+            let modifier = match self.symbols.is_object_assignment(kind.as_ref(), &value) {
+                true => Some(Token::Set),
+                false => None,
+            };
+
+            body.push(Statement::Assignment(AssignmentStatement {
+                modifier: modifier,
+                left: Box::new(Self::value(name.clone())),
+                right: Box::new(value),
+            }));
+
+            branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: condition,
+                body: body,
+            }));
+        }
+
+        statements.push(Self::declare(name.clone(), &Self::kind_name(&kind)));
+        statements.push(Statement::If(IfStatement {
+            branches: branches,
+        }));
+
+        return Self::value(name);
+    }
+
+    // Lowers a `Match` expression into a temporary assigned by each arm (see
+    // `transform_match`), like `If(condition, a, b)` is:
+    //
+    //     If color = Red Then nabe_temporary_1 = "Stop" Else ...
+    //
+    // NOTE: Arms have no statements to declare the bound variables, so the
+    // matched value replaces them.
+    fn transform_match_expression(&mut self, data: MatchStatement, statements: &mut Vec<Statement>) -> Statement {
+        let mut match_statements = vec!();
+        let value = self.match_value(*data.value, &data.arms, &mut match_statements);
+
+        // NOTE: See `MatchStatement`.
+        let values: Vec<Statement> = data.arms.iter()
+            .map(|arm| {
+                let mut arm_value = arm.body[0].clone();
+
+                self.replace_binding(arm, &value, &mut arm_value);
+
+                arm_value
+            })
+            .collect();
+
+        // NOTE: See `transform_conditional`.
+        let kind = self.symbols.infer_common_kind(&values.iter().collect::<Vec<_>>());
+        let name = self.new_temporary(&Self::kind_name(&kind));
+        let mut branches = vec!();
+
+        for (arm, arm_value) in data.arms.iter().zip(values) {
+            let modifier = match self.symbols.is_object_assignment(kind.as_ref(), &arm_value) {
+                true => Some(Token::Set),
+                false => None,
+            };
+
+            branches.push(Statement::IfBranch(IfBranchStatement {
+                condition: self.match_condition(&value, arm),
+                body: vec!(Statement::Assignment(AssignmentStatement {
+                    modifier: modifier,
+                    left: Box::new(Self::value(name.clone())),
+                    right: Box::new(arm_value),
+                })),
+            }));
+        }
+
+        match_statements.push(Self::declare(name.clone(), &Self::kind_name(&kind)));
+        match_statements.push(Statement::If(IfStatement {
+            branches: branches,
+        }));
+
+        statements.append(&mut self.transform_callable_body(match_statements));
+
+        return Self::value(name);
+    }
+
+    // Returns whether an expression needs statements to be computed (see
+    // `transform_expression`).
+    fn needs_lowering(&self, expression: &Statement) -> bool {
+        return match expression {
+            Statement::Conditional(_) => true,
+            Statement::Match(_) => true,
+            Statement::TypeOf(data) => self.needs_lowering(&data.value),
+
+            Statement::Binary(data) => {
+                data.operator == Token::AndAlso || data.operator == Token::OrElse ||
+                    self.needs_lowering(&data.left) || self.needs_lowering(&data.right)
+            },
+
+            Statement::Unary(data) => self.needs_lowering(&data.value),
+            Statement::InterpolatedString(data) => data.parts.iter().any(|part| self.needs_lowering(part)),
+            Statement::Interpolation(data) => self.needs_lowering(&data.value),
+            Statement::Call(data) => self.needs_lowering(&data.callee) || data.arguments.iter().any(|argument| self.needs_lowering(argument)),
+            Statement::Member(data) => self.needs_lowering(&data.object),
+
+            _ => false,
+        };
+    }
+
+    // Replaces the calls' arguments and the members' objects which may have
+    // side effects with temporary variables, pushing their declarations and
+    // assignments to `statements`.
+    fn hoist_arguments(&mut self, statement: Statement, statements: &mut Vec<Statement>) -> Statement {
+        return match statement {
+            Statement::Call(data) => {
+                let callee = self.hoist_arguments(*data.callee, statements);
+                let mut arguments = vec!();
+
+                for argument in data.arguments {
+                    let argument = match self.has_side_effects(&argument) {
+                        true => self.hoist(argument, statements),
+                        false => argument,
+                    };
+
+                    arguments.push(argument);
+                }
+
+                Statement::Call(CallStatement {
+                    callee: Box::new(callee),
+                    arguments: arguments,
+                })
+            },
+
+            Statement::Member(data) => {
+                let object = match self.hoist_arguments(*data.object, statements) {
+                    // NOTE: Indexing a variable (i.e. an array) has no side
+                    // effects once its arguments are hoisted.
+                    Statement::Call(call) if !self.has_side_effects(&call.callee) => Statement::Call(call),
+
+                    object if self.has_side_effects(&object) => {
+                        let name = self.new_temporary("Object");
+
+                        statements.push(Self::declare(name.clone(), "Object"));
+                        statements.push(Statement::Assignment(AssignmentStatement {
+                            modifier: Some(Token::Set),
+                            left: Box::new(Self::value(name.clone())),
+                            right: Box::new(object),
+                        }));
+
+                        Self::value(name)
+                    },
+
+                    object => object,
+                };
+
+                Statement::Member(MemberStatement {
+                    object: Box::new(object),
+                    member: data.member,
+                })
+            },
+
+            _ => statement,
+        };
+    }
+
+    // NOTE: VB6 allows calling functions without parentheses, so identifiers
+    // are only known to be free of side effects when they're declared
+    // variables.
+    fn has_side_effects(&self, statement: &Statement) -> bool {
+        return match statement {
+            Statement::Value(data) => match &data.value {
+                Token::Identifier(lexeme) if lexeme.starts_with(b"\"") => false,
+
+                // NOTE: `Me` is the object running the procedure.
+                Token::Identifier(lexeme) if lexeme.eq_ignore_ascii_case(b"me") => false,
+
+                Token::Identifier(_) => self.symbols.find_kind(&data.value).is_none() && !self.symbols.is_enum_member(statement),
+                _ => false,
+            },
+
+            Statement::Member(_) => !self.symbols.is_enum_member(statement),
+            Statement::TypeOf(data) => self.has_side_effects(&data.value),
+
+            Statement::Unary(data) => self.has_side_effects(&data.value),
+            Statement::Binary(data) => self.has_side_effects(&data.left) || self.has_side_effects(&data.right),
+            Statement::InterpolatedString(data) => data.parts.iter().any(|part| self.has_side_effects(part)),
+            Statement::Interpolation(data) => self.has_side_effects(&data.value),
+
+            _ => true,
+        };
+    }
+
+    // Stores a value in a new temporary variable, returning the variable.
+    fn hoist(&mut self, value: Statement, statements: &mut Vec<Statement>) -> Statement {
+        let name = self.new_temporary("Variant");
+
+        // NOTE: Values whose data type isn't known (i.e. calls' results) are
+        // assumed not to be objects.
+        let modifier = match self.symbols.is_object(&value) {
+            true => Some(Token::Set),
+            false => None,
+        };
+
+        statements.push(Self::declare(name.clone(), "Variant"));
+        statements.push(Statement::Assignment(AssignmentStatement {
+            modifier: modifier,
+            left: Box::new(Self::value(name.clone())),
+            right: Box::new(value),
+        }));
+
+        return Self::value(name);
+    }
+}
+
+// NOTE: The `classes` are the ones declared by other modules (see
+// `Symbols::declare_module`).
+pub fn transform(statements: Vec<Statement>, classes: &Vec<String>) -> Vec<Statement> {
+    let mut transformer = Transformer::new(statements, classes);
+
+    return transformer.transform();
+}
+
+#[cfg(test)]
+mod tests {
+    fn compile(source: &str) -> String {
+        let tokens = crate::lex(&source.as_bytes().to_vec());
+
+        return String::from_utf8(crate::generate(crate::transform(crate::parse(&tokens), &vec!(String::from("CCustomer"))))).unwrap();
+    }
+
+    #[test]
+    fn returns_from_subs_and_properties() {
+        let source = "\
+Public Sub Save(ByVal force As Boolean)
+    If Not force Then Return
+    Debug.Print \"saved\"
+End Sub
+
+Public Property Get Name() As String
+    Return \"nabe\"
+End Property
+";
+
+        let expected = "\
+public sub Save(byval force as Boolean)
+if not force then
+exit sub
+end if
+Debug.Print \"saved\"
+end sub
+public property get Name() as String
+Name = \"nabe\"
+exit property
+end property
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn returns_objects_with_set() {
+        let source = "\
+Public Function Items() As Collection
+    Return New Collection
+End Function
+
+Public Function Count() As Long
+    Dim total As Long
+    Return total
+End Function
+
+Public Function Same(found As Collection) As Collection
+    Return found
+End Function
+
+Public Function Open() As Dictionary
+    Dim opened As Variant
+    Set opened = CreateObject(\"Scripting.Dictionary\")
+    Return opened
+End Function
+";
+
+        let expected = "\
+public function Items() as Collection
+set Items = new Collection
+exit function
+end function
+public function Count() as Long
+dim total as Long
+Count = total
+exit function
+end function
+public function Same(found as Collection) as Collection
+set Same = found
+exit function
+end function
+public function Open() as Dictionary
+dim opened as Variant
+set opened = CreateObject(\"Scripting.Dictionary\")
+set Open = opened
+exit function
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("set ").count(), 4);
+    }
+
+    #[test]
+    fn lowers_compound_assignments() {
+        let source = "\
+Public Sub Main()
+    Dim total As Long
+    Dim text As String
+    total += 2
+    total -= 1
+    total *= 3
+    total /= 2
+    text &= \"!\"
+    counts(Pick()) += 1
+    GetCustomer().Orders += 1
+    Current.Total -= 2
+    Me.Total *= 2
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim total as Long
+dim text as String
+total = total + 2
+total = total - 1
+total = total * 3
+total = total / 2
+text = text & \"!\"
+dim nabe_temporary_1 as Variant
+nabe_temporary_1 = Pick()
+counts(nabe_temporary_1) = counts(nabe_temporary_1) + 1
+dim nabe_temporary_2 as Object
+set nabe_temporary_2 = GetCustomer()
+nabe_temporary_2.Orders = nabe_temporary_2.Orders + 1
+dim nabe_temporary_3 as Object
+set nabe_temporary_3 = Current
+nabe_temporary_3.Total = nabe_temporary_3.Total - 2
+Me.Total = Me.Total * 2
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("Pick()").count(), 1);
+        assert_eq!(code.matches("GetCustomer()").count(), 1);
+        assert_eq!(code.matches("Current").count(), 1);
+    }
+
+    #[test]
+    fn lowers_continues_to_the_end_of_their_loops() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    For i = 1 To 10
+        If i Mod 2 = 0 Then Continue For
+        Do While i < 5
+            i = i + 1
+            If i = 3 Then Continue Do
+            Debug.Print i
+        Loop
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim i as Long
+for i = 1 to 10
+if i mod 2 = 0 then
+goto nabe_continue_1
+end if
+do while i < 5
+i = i + 1
+if i = 3 then
+goto nabe_continue_2
+end if
+Debug.Print i
+nabe_continue_2:
+loop
+nabe_continue_1:
+next
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn lowers_trys_into_error_handlers() {
+        let source = "\
+Public Sub Main()
+    Try
+        Debug.Print 1 / 0
+    Catch ex
+        Debug.Print ex.Description
+    End Try
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+Debug.Print 1 / 0
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+on error goto nabe_catch_failed_1
+Err.Number = nabe_error_number_1
+Err.Source = nabe_error_source_1
+Err.Description = nabe_error_description_1
+nabe_error_number_1 = 0
+dim ex as ErrObject
+set ex = Err
+Debug.Print ex.Description
+goto nabe_finally_1
+nabe_catch_failed_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_finally_1
+nabe_finally_1:
+on error goto 0
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn runs_the_finally_before_exiting() {
+        let source = "\
+Public Function Load() As Boolean
+    Try
+        If Not Ready() Then Exit Function
+        Load = True
+    Finally
+        Debug.Print \"cleanup\"
+    End Try
+End Function
+";
+
+        let expected = "\
+public function Load() as Boolean
+dim nabe_exiting_1 as Long
+nabe_exiting_1 = 0
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+if not Ready() then
+nabe_exiting_1 = 1
+goto nabe_finally_1
+end if
+Load = True
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto 0
+Debug.Print \"cleanup\"
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+if nabe_exiting_1 = 1 then
+exit function
+end if
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(code.find("Debug.Print \"cleanup\"").unwrap() < code.rfind("exit function").unwrap());
+    }
+
+    #[test]
+    fn short_circuits_and_also_and_or_else() {
+        let source = "\
+Public Sub Main(items As Collection)
+    If items Is Nothing OrElse items.Count = 0 Then Exit Sub
+    If items.Count > 1 AndAlso IsEmpty(items(2)) Then Debug.Print \"empty\"
+End Sub
+";
+
+        let expected = "\
+public sub Main(items as Collection)
+dim nabe_temporary_1 as Boolean
+if items is nothing then
+nabe_temporary_1 = True
+else
+nabe_temporary_1 = items.Count = 0
+end if
+if nabe_temporary_1 then
+exit sub
+end if
+dim nabe_temporary_2 as Boolean
+if items.Count > 1 then
+nabe_temporary_2 = IsEmpty(items(2))
+else
+nabe_temporary_2 = False
+end if
+if nabe_temporary_2 then
+Debug.Print \"empty\"
+end if
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(!code.contains(" or ") && !code.contains(" and "));
+    }
+
+    #[test]
+    fn evaluates_only_the_chosen_value_of_conditionals() {
+        let source = "\
+Public Sub Main(ByVal n As Long)
+    Debug.Print If(n > 0, First(), Second())
+End Sub
+";
+
+        let expected = "\
+public sub Main(byval n as Long)
+dim nabe_temporary_1 as Variant
+if n > 0 then
+nabe_temporary_1 = First()
+else
+nabe_temporary_1 = Second()
+end if
+Debug.Print nabe_temporary_1
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("First()").count(), 1);
+        assert_eq!(code.matches("Second()").count(), 1);
+    }
+
+    #[test]
+    fn infers_the_data_type_of_initialized_variables() {
+        let source = "\
+Public Sub Main()
+    Dim count = 10
+    Dim ratio = count / 4
+    Let name = \"nabe\" & count
+    Dim customer = New CCustomer
+    Dim anything = Pick()
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim count as Integer
+count = 10
+dim ratio as Double
+ratio = count / 4
+dim name as String
+name = \"nabe\" & count
+dim customer as CCustomer
+set customer = new CCustomer
+dim anything as Variant
+anything = Pick()
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn assigns_the_values_of_declarations() {
+        let source = "\
+Private total As Long = 10
+
+Public Sub Main()
+    Dim name As String = \"nabe\"
+    Debug.Print name & total
+End Sub
+";
+
+        let expected = "\
+private total as Long
+public sub Main()
+nabe_initialize
+dim name as String
+name = \"nabe\"
+Debug.Print name & total
+end sub
+private sub nabe_initialize()
+static nabe_initialized as Boolean
+if nabe_initialized then
+exit sub
+end if
+nabe_initialized = True
+total = 10
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn lowers_interpolated_strings_into_concatenations() {
+        let source = "\
+Public Sub Main()
+    Dim name As String
+    Dim total As Double
+    name = \"nabe\"
+    Debug.Print $\"Hello {name}, you owe {total:0.00} ({Len(name)} letters) {{braces}}\"
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim name as String
+dim total as Double
+name = \"nabe\"
+Debug.Print \"Hello \" & name & \", you owe \" & Format$(total, \"0.00\") & \" (\" & CStr(Len(name)) & \" letters) {braces}\"
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn lowers_escaped_and_raw_strings() {
+        let source = "\
+Public Sub Main()
+    Debug.Print e\"Name:\\tJosé\\n\\\"quoted\\\"\\x41\"
+    Debug.Print \"\"\"
+        first line
+          second line
+        \"\"\"
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+Debug.Print \"Name:\" & vbTab & \"Jos\" & ChrW$(&HE9&) & vbCrLf & \"\"\"quoted\"\"\" & Chr$(&H41&)
+Debug.Print \"first line\" & vbCrLf & \"  second line\"
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn keeps_code_page_bytes_and_invalid_escapes_of_strings() {
+        // NOTE: `é` is `\xE9` in Windows-1252, which isn't valid UTF-8.
+        let source = b"\
+Public Sub Main()
+    Debug.Print e\"Caf\xE9 \\u+0E9 \\x-1 \\u00G1 \\x4\"
+End Sub
+";
+
+        let expected = b"\
+public sub Main()
+Debug.Print \"Caf\xE9 \\u+0E9 \\x-1 \\u00G1 \\x4\"
+end sub
+";
+
+        let tokens = crate::lex(&source.to_vec());
+        let code = crate::generate(crate::transform(crate::parse(&tokens), &vec!()));
+
+        assert_eq!(code, expected.to_vec());
+    }
+
+    #[test]
+    fn lowers_array_literals_into_assignments() {
+        let source = "\
+Public Sub Main()
+    Dim numbers() As Long
+    numbers = {1, 2, 3}
+    Dim names = {\"a\", \"b\"}
+    Dim mixed = {1, \"two\"}
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim numbers() as Long
+redim numbers(0 to 2)
+numbers(0) = 1
+numbers(1) = 2
+numbers(2) = 3
+dim names() as String
+redim names(0 to 1)
+names(0) = \"a\"
+names(1) = \"b\"
+dim mixed() as Variant
+redim mixed(0 to 1)
+mixed(0) = 1
+mixed(1) = \"two\"
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn iterates_typed_arrays_by_their_indexes() {
+        let source = "\
+Public Sub Main(customers() As CCustomer, items As Collection)
+    For Each customer As CCustomer In customers
+        Debug.Print customer.Name
+    Next
+    For Each item As CCustomer In items
+        Debug.Print item.Name
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main(customers() as CCustomer, items as Collection)
+dim customer as CCustomer
+dim nabe_temporary_1 as Long
+for nabe_temporary_1 = LBound(customers) to UBound(customers)
+set customer = customers(nabe_temporary_1)
+Debug.Print customer.Name
+next
+dim item as CCustomer
+dim nabe_temporary_2 as Variant
+for each nabe_temporary_2 in items
+set item = nabe_temporary_2
+Debug.Print item.Name
+next
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn lowers_inclusive_and_exclusive_ranges() {
+        let source = "\
+Public Sub Main(items() As Long)
+    Dim i As Long
+    For i In 1 .. 10
+        Debug.Print i
+    Next
+    For i In 0 ..< UBound(items) + 1
+        Debug.Print items(i)
+    Next
+    For i In 10 ..< 0 Step -1
+        Debug.Print i
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main(items() as Long)
+dim i as Long
+for i = 1 to 10
+Debug.Print i
+next
+dim nabe_temporary_1 as Long
+nabe_temporary_1 = UBound(items) + 1
+for i = 0 to nabe_temporary_1 - 1
+Debug.Print items(i)
+next
+for i = 10 to 1 step -1
+Debug.Print i
+next
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("UBound(items)").count(), 1);
+    }
+
+    #[test]
+    fn exits_and_continues_labelled_loops() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    Dim j As Long
+    rows: For i = 1 To 10
+        For j = 1 To 10
+            If i * j = 42 Then Exit rows
+            If j > i Then Continue rows
+        Next
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim i as Long
+dim j as Long
+for i = 1 to 10
+for j = 1 to 10
+if i * j = 42 then
+goto nabe_exit_1
+end if
+if j > i then
+goto nabe_continue_2
+end if
+next
+nabe_continue_2:
+next
+nabe_exit_1:
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn renames_block_scoped_variables() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    For i = 1 To 2
+        Dim total As Long
+        total = total + i
+    Next
+    If i > 0 Then
+        Dim total As String
+        total = \"done\"
+    End If
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim total as Long
+dim nabe_total_2 as String
+dim i as Long
+for i = 1 to 2
+total = 0
+total = total + i
+next
+if i > 0 then
+nabe_total_2 = vbNullString
+nabe_total_2 = \"done\"
+end if
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn runs_deferred_statements_when_returning() {
+        let source = "\
+Public Function Load(conn As Object) As Boolean
+    conn.Open
+    Defer conn.Close
+    If conn.State = 0 Then Return False
+    Load = True
+End Function
+";
+
+        let expected = "\
+public function Load(conn as Object) as Boolean
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+on error goto nabe_defer_error_1
+conn.Open
+dim nabe_deferred_1 as Boolean
+nabe_deferred_1 = True
+if conn.State = 0 then
+Load = False
+goto nabe_defer_1
+end if
+Load = True
+nabe_defer_1:
+on error goto 0
+if nabe_deferred_1 then
+nabe_deferred_1 = False
+conn.Close
+end if
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+exit function
+nabe_defer_error_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_defer_1
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn runs_deferred_statements_in_reverse_order_on_errors_and_early_exits() {
+        let source = "\
+Public Function Load(conn As Object) As Boolean
+    conn.Open
+    Defer conn.Close
+    Dim i As Long
+    For i = 1 To 3
+        Defer Debug.Print i
+        If i = 2 Then Return False
+    Next
+    Err.Raise 5
+End Function
+";
+
+        let expected = "\
+public function Load(conn as Object) as Boolean
+dim nabe_deferred_2 as Boolean
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+on error goto nabe_defer_error_1
+conn.Open
+dim nabe_deferred_1 as Boolean
+nabe_deferred_1 = True
+dim i as Long
+for i = 1 to 3
+nabe_deferred_2 = True
+if i = 2 then
+Load = False
+goto nabe_defer_1
+end if
+next
+Err.Raise 5
+nabe_defer_1:
+on error goto 0
+if nabe_deferred_2 then
+nabe_deferred_2 = False
+Debug.Print i
+end if
+if nabe_deferred_1 then
+nabe_deferred_1 = False
+conn.Close
+end if
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+exit function
+nabe_defer_error_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_defer_1
+end function
+";
+
+        let code = compile(source);
+        let position = |text: &str| code.find(text).unwrap();
+
+        assert_eq!(code, expected);
+        assert!(position("Load = False") < position("Debug.Print i"));
+        assert!(position("Debug.Print i") < position("conn.Close"));
+        assert!(position("conn.Close") < code.rfind("Err.Raise").unwrap());
+    }
+
+    #[test]
+    fn disposes_using_objects_on_every_exit() {
+        let source = "\
+Public Function Count(sql As String) As Long
+    Using conn As New ADODB.Connection
+        conn.Open \"dsn\"
+        If sql = \"\" Then Exit Function
+        Count = conn.Execute(sql).RecordCount
+    End Using
+End Function
+";
+
+        let expected = "\
+public function Count(sql as String) as Long
+dim conn as ADODB.Connection
+set conn = new ADODB.Connection
+dim nabe_exiting_1 as Long
+nabe_exiting_1 = 0
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+conn.Open \"dsn\"
+if sql = \"\" then
+nabe_exiting_1 = 1
+goto nabe_finally_1
+end if
+Count = conn.Execute(sql).RecordCount
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto 0
+if not conn is nothing then
+on error resume next
+conn.Close
+on error goto 0
+end if
+set conn = nothing
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+if nabe_exiting_1 = 1 then
+exit function
+end if
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(code.find("conn.Close").unwrap() < code.rfind("exit function").unwrap());
+    }
+
+    #[test]
+    fn lowers_matches_into_if_chains() {
+        let source = "\
+Public Function Describe(shape As Object, ByVal n As Long) As String
+    Match n
+        Case 0
+            Describe = \"none\"
+        Case 1 To 9 When n <> 5
+            Describe = \"few\"
+        Case Else
+            Describe = \"many\"
+    End Match
+    Match shape
+        Case TypeOf CCircle As circle
+            Describe = Describe & circle.Radius
+        Case Nothing
+            Describe = \"\"
+    End Match
+End Function
+";
+
+        let expected = "\
+public function Describe(shape as Object, byval n as Long) as String
+dim nabe_temporary_1 as Boolean
+dim circle as CCircle
+if n = 0 then
+Describe = \"none\"
+else
+if n >= 1 and n <= 9 then
+nabe_temporary_1 = n <> 5
+else
+nabe_temporary_1 = False
+end if
+if nabe_temporary_1 then
+Describe = \"few\"
+else
+Describe = \"many\"
+end if
+end if
+if typeof shape is CCircle then
+set circle = shape
+Describe = Describe & circle.Radius
+elseif shape is nothing then
+Describe = \"\"
+end if
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(code.contains("if typeof shape is CCircle then"));
+    }
+
+    #[test]
+    fn generates_helpers_only_for_marked_enums() {
+        let source = "\
+Attribute Color.Nabe_Helpers = True
+
+Public Enum Color
+    Red
+    Green
+    Blue = 5
+End Enum
+
+Public Enum Size
+    Small
+    Large
+End Enum
+";
+
+        let expected = "\
+public enum Color
+Red
+Green
+Blue = 5
+[_First] = Red
+[_Last] = Blue
+end enum
+public enum Size
+Small
+Large
+end enum
+public function Color_ToString(byval value as Color) as String
+if value = Color.Red then
+Color_ToString = \"Red\"
+elseif value = Color.Green then
+Color_ToString = \"Green\"
+elseif value = Color.Blue then
+Color_ToString = \"Blue\"
+end if
+end function
+public function Color_TryParse(byval text as String, value as Color) as Boolean
+Color_TryParse = True
+dim nabe_temporary_1 as String
+nabe_temporary_1 = LCase$(text)
+if nabe_temporary_1 = \"red\" then
+value = Color.Red
+elseif nabe_temporary_1 = \"green\" then
+value = Color.Green
+elseif nabe_temporary_1 = \"blue\" then
+value = Color.Blue
+else
+Color_TryParse = False
+end if
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(code.contains("public function Color_ToString("));
+        assert!(!code.contains("Size_"));
+    }
+
+    #[test]
+    fn returns_with_set_only_known_objects() {
+        let source = "\
+Public Function Answer() As VbMsgBoxResult
+    Return MsgBox(\"Continue?\", vbYesNo)
+End Function
+
+Public Function Location() As RECT
+    Dim area As RECT
+    Return area
+End Function
+
+Public Function Customer() As CCustomer
+    Dim found As CCustomer
+    Return found
+End Function
+
+Public Function Items() As Collection
+    Dim found As New Collection
+    Return found
+End Function
+
+Public Function Anything() As Unknown
+    Return Nothing
+End Function
+";
+
+        let expected = "\
+public function Answer() as VbMsgBoxResult
+Answer = MsgBox(\"Continue?\", vbYesNo)
+exit function
+end function
+public function Location() as RECT
+dim area as RECT
+Location = area
+exit function
+end function
+public function Customer() as CCustomer
+dim found as CCustomer
+set Customer = found
+exit function
+end function
+public function Items() as Collection
+dim found as new Collection
+set Items = found
+exit function
+end function
+public function Anything() as Unknown
+set Anything = nothing
+exit function
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn assigns_initializers_with_set_only_when_known_objects() {
+        let source = "\
+Private answer As VbMsgBoxResult = vbNo
+Private items As Collection = New Collection
+
+Public Sub Main()
+    Dim choice As VbMsgBoxResult = vbYes
+    Dim customer As CCustomer = Nothing
+End Sub
+";
+
+        let expected = "\
+private answer as VbMsgBoxResult
+private items as Collection
+public sub Main()
+nabe_initialize
+dim choice as VbMsgBoxResult
+choice = vbYes
+dim customer as CCustomer
+set customer = nothing
+end sub
+private sub nabe_initialize()
+static nabe_initialized as Boolean
+if nabe_initialized then
+exit sub
+end if
+nabe_initialized = True
+answer = vbNo
+set items = new Collection
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn runs_the_finally_of_the_trys_a_loop_is_left_from() {
+        let source = "\
+Public Sub Main()
+    Dim i As Long
+    outer: For i = 1 To 10
+        Try
+            If i = 2 Then Continue For
+            If i = 3 Then Exit For
+            If i = 4 Then Exit outer
+            If i = 5 Then Exit Sub
+        Finally
+            Debug.Print i
+        End Try
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+dim nabe_exiting_1 as Long
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+dim i as Long
+for i = 1 to 10
+nabe_exiting_1 = 0
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+if i = 2 then
+nabe_exiting_1 = 1
+goto nabe_finally_1
+end if
+if i = 3 then
+nabe_exiting_1 = 2
+goto nabe_finally_1
+end if
+if i = 4 then
+nabe_exiting_1 = 3
+goto nabe_finally_1
+end if
+if i = 5 then
+nabe_exiting_1 = 4
+goto nabe_finally_1
+end if
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto 0
+Debug.Print i
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+if nabe_exiting_1 = 1 then
+goto nabe_continue_2
+end if
+if nabe_exiting_1 = 2 then
+exit for
+end if
+if nabe_exiting_1 = 3 then
+goto nabe_exit_3
+end if
+if nabe_exiting_1 = 4 then
+exit sub
+end if
+nabe_continue_2:
+next
+nabe_exit_3:
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn declares_conditionals_temporaries_with_their_data_type() {
+        let source = "\
+Public Function Pick(ByVal n As Long, first As CCustomer, second As CCustomer) As String
+    Dim count As Long
+    count = If(n > 0, n, 0)
+    Dim chosen As CCustomer
+    Set chosen = If(n > 0, first, second)
+    Pick = If(n > 0, \"some\", \"none\")
+End Function
+";
+
+        let expected = "\
+public function Pick(byval n as Long, first as CCustomer, second as CCustomer) as String
+dim count as Long
+dim nabe_temporary_1 as Long
+if n > 0 then
+nabe_temporary_1 = n
+else
+nabe_temporary_1 = 0
+end if
+count = nabe_temporary_1
+dim chosen as CCustomer
+dim nabe_temporary_2 as CCustomer
+if n > 0 then
+set nabe_temporary_2 = first
+else
+set nabe_temporary_2 = second
+end if
+set chosen = nabe_temporary_2
+dim nabe_temporary_3 as String
+if n > 0 then
+nabe_temporary_3 = \"some\"
+else
+nabe_temporary_3 = \"none\"
+end if
+Pick = nabe_temporary_3
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn declares_matches_temporaries_with_their_data_type() {
+        let source = "\
+Public Function Offset() As Long
+    Offset = 1
+End Function
+
+Public Function Size(n As Long) As String
+    Size = Match n + Offset()
+        Case 1 Then \"none\"
+        Case 2 To 9 Then \"few\"
+        Case Else Then \"many\"
+    End Match
+End Function
+";
+
+        let expected = "\
+public function Offset() as Long
+Offset = 1
+end function
+public function Size(n as Long) as String
+dim nabe_temporary_1 as Long
+nabe_temporary_1 = n + Offset()
+dim nabe_temporary_2 as String
+if nabe_temporary_1 = 1 then
+nabe_temporary_2 = \"none\"
+elseif nabe_temporary_1 >= 2 and nabe_temporary_1 <= 9 then
+nabe_temporary_2 = \"few\"
+else
+nabe_temporary_2 = \"many\"
+end if
+Size = nabe_temporary_2
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn adjusts_exclusive_ends_by_the_sign_of_the_step() {
+        let source = "\
+Public Sub Main(ByVal n As Long, ByVal s As Long)
+    Dim i As Long
+    For i In 0 ..< 10 Step s
+        Debug.Print i
+    Next
+    For i In n ..< 0 Step NextStep()
+        Debug.Print i
+    Next
+    For i In 0 ..< n Step -2
+        Debug.Print i
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main(byval n as Long, byval s as Long)
+dim i as Long
+dim nabe_temporary_1 as Long
+nabe_temporary_1 = 10
+if Sgn(s) < 0 then
+nabe_temporary_1 = nabe_temporary_1 + 1
+else
+nabe_temporary_1 = nabe_temporary_1 - 1
+end if
+for i = 0 to nabe_temporary_1 step s
+Debug.Print i
+next
+dim nabe_temporary_2 as Long
+nabe_temporary_2 = 0
+dim nabe_temporary_3 as Long
+nabe_temporary_3 = NextStep()
+if Sgn(nabe_temporary_3) < 0 then
+nabe_temporary_2 = nabe_temporary_2 + 1
+else
+nabe_temporary_2 = nabe_temporary_2 - 1
+end if
+for i = n to nabe_temporary_2 step nabe_temporary_3
+Debug.Print i
+next
+dim nabe_temporary_4 as Long
+nabe_temporary_4 = n
+for i = 0 to nabe_temporary_4 + 1 step -2
+Debug.Print i
+next
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn disposes_without_hiding_errors_when_leaving_usings() {
+        let source = "\
+Public Sub Main(names As Collection)
+    Dim name As Variant
+    For Each name In names
+        Using conn As New ADODB.Connection
+            If name = \"\" Then Exit For
+            conn.Open name
+        End Using
+    Next
+End Sub
+";
+
+        let expected = "\
+public sub Main(names as Collection)
+dim conn as ADODB.Connection
+dim nabe_exiting_1 as Long
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+dim name as Variant
+for each name in names
+set conn = new ADODB.Connection
+nabe_exiting_1 = 0
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+if name = \"\" then
+nabe_exiting_1 = 1
+goto nabe_finally_1
+end if
+conn.Open name
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto 0
+if not conn is nothing then
+on error resume next
+conn.Close
+on error goto 0
+end if
+set conn = nothing
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+if nabe_exiting_1 = 1 then
+exit for
+end if
+next
+end sub
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn bounds_enums_by_their_smallest_and_largest_values() {
+        let source = "\
+Attribute Level.Nabe_Helpers = True
+
+Public Enum Level
+    High = 10
+    Higher
+    Low = &HFFFF
+    Middle = 5
+End Enum
+";
+
+        let expected = "\
+public enum Level
+High = 10
+Higher
+Low = &HFFFF
+Middle = 5
+[_First] = Low
+[_Last] = Higher
+end enum
+public function Level_ToString(byval value as Level) as String
+if value = Level.High then
+Level_ToString = \"High\"
+elseif value = Level.Higher then
+Level_ToString = \"Higher\"
+elseif value = Level.Low then
+Level_ToString = \"Low\"
+elseif value = Level.Middle then
+Level_ToString = \"Middle\"
+end if
+end function
+public function Level_TryParse(byval text as String, value as Level) as Boolean
+Level_TryParse = True
+dim nabe_temporary_1 as String
+nabe_temporary_1 = LCase$(text)
+if nabe_temporary_1 = \"high\" then
+value = Level.High
+elseif nabe_temporary_1 = \"higher\" then
+value = Level.Higher
+elseif nabe_temporary_1 = \"low\" then
+value = Level.Low
+elseif nabe_temporary_1 = \"middle\" then
+value = Level.Middle
+else
+Level_TryParse = False
+end if
+end function
+";
+
+        assert_eq!(compile(source), expected);
+    }
+
+    #[test]
+    fn restores_the_error_handlers_of_procedures_after_trys() {
+        let source = "\
+Public Sub Main()
+    On Error GoTo Handler
+    Try
+        Debug.Print 1 / 0
+    Finally
+        Debug.Print \"finally\"
+    End Try
+    Exit Sub
+Handler:
+    Debug.Print Err.Description
+End Sub
+
+Public Sub Other()
+    On Error Resume Next
+    Try
+        Debug.Print 1 / 0
+    End Try
+    Debug.Print \"next\"
+End Sub
+";
+
+        let expected = "\
+public sub Main()
+on error goto Handler
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+Debug.Print 1 / 0
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto Handler
+Debug.Print \"finally\"
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+exit sub
+Handler:
+Debug.Print Err.Description
+end sub
+public sub Other()
+on error resume next
+dim nabe_error_number_2 as Long
+dim nabe_error_source_2 as String
+dim nabe_error_description_2 as String
+nabe_error_number_2 = 0
+on error goto nabe_catch_2
+Debug.Print 1 / 0
+goto nabe_finally_2
+nabe_catch_2:
+nabe_error_number_2 = Err.Number
+nabe_error_source_2 = Err.Source
+nabe_error_description_2 = Err.Description
+resume nabe_caught_2
+nabe_caught_2:
+nabe_finally_2:
+on error resume next
+if nabe_error_number_2 <> 0 then
+Err.Raise nabe_error_number_2, nabe_error_source_2, nabe_error_description_2
+end if
+Debug.Print \"next\"
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("on error goto Handler").count(), 2);
+        assert!(!code.contains("on error goto 0"));
+    }
+
+    #[test]
+    fn keeps_the_error_handler_of_procedures_after_usings() {
+        let source = "\
+Public Function Count(sql As String) As Long
+    On Error GoTo Failed
+    Using conn As New ADODB.Connection
+        conn.Open \"dsn\"
+        Count = conn.Execute(sql).RecordCount
+    End Using
+    Err.Raise 5
+    Exit Function
+Failed:
+    Count = -1
+End Function
+";
+
+        let expected = "\
+public function Count(sql as String) as Long
+on error goto Failed
+dim conn as ADODB.Connection
+set conn = new ADODB.Connection
+dim nabe_error_number_1 as Long
+dim nabe_error_source_1 as String
+dim nabe_error_description_1 as String
+nabe_error_number_1 = 0
+on error goto nabe_catch_1
+conn.Open \"dsn\"
+Count = conn.Execute(sql).RecordCount
+goto nabe_finally_1
+nabe_catch_1:
+nabe_error_number_1 = Err.Number
+nabe_error_source_1 = Err.Source
+nabe_error_description_1 = Err.Description
+resume nabe_caught_1
+nabe_caught_1:
+nabe_finally_1:
+on error goto Failed
+if not conn is nothing then
+on error resume next
+conn.Close
+on error goto Failed
+end if
+set conn = nothing
+if nabe_error_number_1 <> 0 then
+Err.Raise nabe_error_number_1, nabe_error_source_1, nabe_error_description_1
+end if
+Err.Raise 5
+exit function
+Failed:
+Count = -1
+end function
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert_eq!(code.matches("on error goto Failed").count(), 3);
+        assert!(!code.contains("on error goto 0"));
+    }
+
+    #[test]
+    fn resets_only_variables_whose_default_value_is_known() {
+        let source = "\
+Type Buffer
+    Size As Long
+End Type
+
+Public Sub Main()
+    Dim i As Long
+    For i = 1 To 2
+        Dim buf As Buffer
+        Dim answer As VbMsgBoxResult
+        Dim items As Collection
+        Dim total As Long
+        total = total + i
+    Next
+End Sub
+";
+
+        let expected = "\
+type Buffer
+Size as Long
+end type
+public sub Main()
+dim buf as Buffer
+dim answer as VbMsgBoxResult
+dim items as Collection
+dim total as Long
+dim i as Long
+for i = 1 to 2
+set items = nothing
+total = 0
+total = total + i
+next
+end sub
+";
+
+        let code = compile(source);
+
+        assert_eq!(code, expected);
+        assert!(!code.contains("set buf") && !code.contains("set answer"));
+    }
 }